@@ -1,6 +1,29 @@
 use super::*;
+use crate::bytes::{self, LeBytes};
+use crate::diff::DiffItem;
+use crate::error::{DecodeError, DuplicateError};
+use crate::operation::Op;
+use crate::order::Order;
+use crate::traversal::TraversalOrder;
+use cursor::Cursor;
+use iter::Iter;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{ControlFlow, Range, Sub};
+
+#[cfg(test)]
+thread_local! {
+    /// Scans for the minimum or maximum performed on this thread (observed by the tests).
+    static REFINDS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Upper bound of the height of a tree fitting in memory: below `1.45 * log2(n + 2)` for the
+/// `n < 2^64` nodes.
+const MAX_HEIGHT: usize = 96;
 
 impl<T: PartialOrd + Clone> AVLTree<T> {
     /// Creates a new empty `AVLTree`.
@@ -9,7 +32,131 @@ impl<T: PartialOrd + Clone> AVLTree<T> {
             root: None,
             min_value: None,
             max_value: None,
+            pool: NodePool::new(),
+            rotations: Rotations::new(),
+        }
+    }
+
+    /// Creates a new empty `AVLTree` prepared for `capacity` insertions.
+    ///
+    /// With the `pool` feature enabled, `capacity` node allocations are made upfront and
+    /// handed out by the following insertions (see `pooled_nodes`). Without the feature the
+    /// nodes are always allocated on insertion, so this is the same as `new`.
+    ///
+    /// # Complexity:
+    /// *O*(capacity) with the `pool` feature, *O*(1) otherwise.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut tree = Self::new();
+        tree.pool = NodePool::with_capacity(capacity);
+        tree
+    }
+
+    /// Creates a new empty `AVLTree` (an alias for `new`).
+    pub fn empty() -> Self {
+        Self::new()
+    }
+
+    /// Creates a `AVLTree` containing exactly one element, the `value`.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn with_value(value: T) -> Self {
+        let mut tree = Self::new();
+        tree.insert(value);
+        tree
+    }
+
+    /// Creates a `AVLTree` by replaying the recorded operations of the `log` in order,
+    /// reproducing exactly the tree (including its shape) that they have produced.
+    ///
+    /// # Complexity:
+    /// The sum of the complexities of the replayed `insert`s and `remove`s.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn from_insertion_log(log: &[Op<T>]) -> Self {
+        let mut tree = Self::new();
+        for op in log {
+            match op {
+                Op::Insert(value) => tree.insert(value.clone()),
+                Op::Remove(value) => tree.remove(value),
+            }
+        }
+        tree
+    }
+
+    /// Builds a balanced `AVLTree` out of arbitrary (unsorted, possibly repeating) values.
+    ///
+    /// The values are sorted and deduplicated first, then the tree is built bottom-up from the
+    /// sorted sequence, which avoids the rotations of `n` separate balancing insertions.
+    /// Values that are not comparable to themselves (e.g. `NaN`) are skipped.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) - dominated by sorting.
+    pub fn from_unsorted(iter: impl IntoIterator<Item = T>) -> Self {
+        let mut values: Vec<T> = iter
+            .into_iter()
+            .filter(|value| value.partial_cmp(value).is_some())
+            .collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        values.dedup_by(|a, b| (*a).partial_cmp(b) == Some(Ordering::Equal));
+
+        let mut tree = Self::new();
+        tree.rebuild_from_sorted(values);
+        tree
+    }
+
+    /// Replaces the contents of the tree with strictly ascending `values`.
+    fn rebuild_from_sorted(&mut self, values: Vec<T>) {
+        self.min_value = values.first().cloned();
+        self.max_value = values.last().cloned();
+
+        let len = values.len();
+        self.root = Self::build_balanced(&mut values.into_iter(), len, &mut self.pool);
+    }
+
+    /// Builds a perfectly balanced subtree out of the next `len` values of an ascending sequence.
+    fn build_balanced(
+        values: &mut impl Iterator<Item = T>,
+        len: usize,
+        pool: &mut NodePool<AVLNode<T>>,
+    ) -> Option<Box<AVLNode<T>>> {
+        if len == 0 {
+            return None;
+        }
+
+        let left = Self::build_balanced(values, len / 2, pool);
+        let mut node = pool.alloc(AVLNode::new(values.next()?));
+        node.left = left;
+        node.right = Self::build_balanced(values, len - len / 2 - 1, pool);
+        node.update_height();
+
+        Some(node)
+    }
+
+    /// Moves the values out of a subtree in ascending order.
+    fn drain_sorted(root: Option<Box<AVLNode<T>>>, pool: &mut NodePool<AVLNode<T>>) -> Vec<T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = root;
+
+        while current.is_some() || !stack.is_empty() {
+            while let Some(mut node) = current {
+                current = node.left.take();
+                stack.push(node);
+            }
+
+            if let Some(node) = stack.pop() {
+                let node = pool.release(node);
+                current = node.right;
+                result.push(node.value);
+            }
         }
+
+        result
     }
 
     /// Checks if the tree is empty.
@@ -22,6 +169,41 @@ impl<T: PartialOrd + Clone> AVLTree<T> {
         self.root.is_none()
     }
 
+    /// Removes all elements from the tree.
+    ///
+    /// With the `pool` feature enabled, the node allocations are kept for reuse by the following
+    /// insertions instead of being returned to the allocator.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            let node = self.pool.release(node);
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+
+        self.min_value = None;
+        self.max_value = None;
+    }
+
+    /// Returns the number of node allocations kept for reuse by the following insertions.
+    ///
+    /// The pool is filled by `with_capacity`, `clear` and `remove` and drained by `insert`.
+    /// The rebuilding operations (e.g. `retain`, `split_off` or `append`) recycle the
+    /// allocations of the nodes they take apart.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    #[cfg(feature = "pool")]
+    pub fn pooled_nodes(&self) -> usize {
+        self.pool.len()
+    }
+
     /// Inserts a `value` into the tree while maintaining AVL balance properties.
     ///
     /// Automatically performs rotations to maintain balance factor ∈ [-1, 0, 1].
@@ -29,997 +211,3976 @@ impl<T: PartialOrd + Clone> AVLTree<T> {
     /// # Complexity:
     /// *O*(log n) - guaranteed due to AVL balancing.
     pub fn insert(&mut self, value: T) {
-        self.root = AVLTree::insert_rec(self.root.take(), value);
+        self.root =
+            AVLTree::insert_rec(self.root.take(), value, &mut self.pool, &mut self.rotations);
 
         self.min_value = self.refind_min();
         self.max_value = self.refind_max();
     }
 
-    fn insert_rec(node: Option<Box<AVLNode<T>>>, value: T) -> Option<Box<AVLNode<T>>> {
+    /// Inserts a `value` like `insert` and returns whether it has been added, i.e. `false` if
+    /// an equal element was already present (or the `value` is incomparable and was skipped).
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn try_insert(&mut self, value: T) -> bool {
+        let len = self.len();
+        self.insert(value);
+        self.len() > len
+    }
+
+    /// Inserts a `value` into the tree, replacing the stored element that compares equal to it.
+    ///
+    /// Returns the replaced element, or `None` if the `value` was not present and has been
+    /// inserted (the tree-set analogue of `HashSet::replace`).
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn insert_or_replace(&mut self, value: T) -> Option<T> {
+        let mut cursor = &mut self.root;
+
+        while let Some(current_node) = cursor {
+            match value.partial_cmp(&current_node.value) {
+                Some(Ordering::Less) => cursor = &mut current_node.left,
+                Some(Ordering::Greater) => cursor = &mut current_node.right,
+                Some(Ordering::Equal) => {
+                    let replaced = std::mem::replace(&mut current_node.value, value);
+
+                    if self
+                        .min_value
+                        .as_ref()
+                        .and_then(|min| min.partial_cmp(&replaced))
+                        == Some(Ordering::Equal)
+                    {
+                        self.min_value = Some(current_node.value.clone());
+                    }
+                    if self
+                        .max_value
+                        .as_ref()
+                        .and_then(|max| max.partial_cmp(&replaced))
+                        == Some(Ordering::Equal)
+                    {
+                        self.max_value = Some(current_node.value.clone());
+                    }
+
+                    return Some(replaced);
+                }
+                None => return None,
+            }
+        }
+
+        self.insert(value);
+        None
+    }
+
+    fn insert_rec(
+        node: Option<Box<AVLNode<T>>>,
+        value: T,
+        pool: &mut NodePool<AVLNode<T>>,
+        rotations: &mut Rotations,
+    ) -> Option<Box<AVLNode<T>>> {
         match node {
-            None => Some(Box::new(AVLNode::new(value))),
+            None => Some(pool.alloc(AVLNode::new(value))),
             Some(mut n) => {
                 match value.partial_cmp(&n.value) {
                     Some(Ordering::Less) => {
-                        n.left = AVLTree::insert_rec(n.left.take(), value);
+                        n.left = AVLTree::insert_rec(n.left.take(), value, pool, rotations);
                     }
                     Some(Ordering::Greater) => {
-                        n.right = AVLTree::insert_rec(n.right.take(), value);
+                        n.right = AVLTree::insert_rec(n.right.take(), value, pool, rotations);
                     }
                     _ => return Some(n),
                 }
 
                 n.update_height();
-                Some(n.rebalance())
+                Some(n.rebalance_counting(rotations))
             }
         }
     }
 
-    /// Removes a `value` from the tree while maintaining AVL balance properties.
+    /// Inserts a `value` like `insert`, recording the intermediate shapes of the tree for
+    /// visualizing the rebalancing step by step.
     ///
-    /// Performs automatic rebalancing through rotations after deletion.
+    /// Returns the snapshots of the tree (in the format of `level_order_with_nulls_cloned`)
+    /// right after the plain BST insertion and after every single rotation that follows it,
+    /// so a double rotation produces two snapshots. If the `value` is already present,
+    /// nothing is inserted and no snapshots are returned.
     ///
     /// # Complexity:
-    /// *O*(log n) - guaranteed due to AVL balancing.
-    pub fn remove(&mut self, value: &T)
-    where
-        T: PartialOrd + Clone,
-    {
-        self.root = Self::remove_node(self.root.take(), value);
+    /// *O*(n) per snapshot, the insertion itself is *O*(log n).
+    pub fn insert_recording(&mut self, value: T) -> Vec<Vec<Option<T>>> {
+        // Directions (`true` for left) from the root to the vacant link of the `value`.
+        let mut path = Vec::new();
+        let mut cursor = &self.root;
+        while let Some(node) = cursor {
+            match value.partial_cmp(&node.value) {
+                Some(Ordering::Less) => {
+                    path.push(true);
+                    cursor = &node.left;
+                }
+                Some(Ordering::Greater) => {
+                    path.push(false);
+                    cursor = &node.right;
+                }
+                _ => return Vec::new(),
+            }
+        }
+
+        *Self::link_at(&mut self.root, &path) = Some(self.pool.alloc(AVLNode::new(value)));
+        let mut snapshots = vec![self.level_order_with_nulls_cloned()];
+
+        // Update and rebalance the ancestors bottom-up, one rotation at a time.
+        for depth in (0..path.len()).rev() {
+            loop {
+                let link = Self::link_at(&mut self.root, &path[..depth]);
+                let mut node = link.take().unwrap();
+                node.update_height();
+
+                let (node, rotated) = node.rebalance_step_counting(&mut self.rotations);
+                *link = Some(node);
+                if !rotated {
+                    break;
+                }
+                snapshots.push(self.level_order_with_nulls_cloned());
+            }
+        }
 
         self.min_value = self.refind_min();
         self.max_value = self.refind_max();
-    }
 
-    fn remove_node(node: Option<Box<AVLNode<T>>>, value: &T) -> Option<Box<AVLNode<T>>>
-    where
-        T: PartialOrd + Clone,
-    {
-        match node {
-            None => None,
-            Some(mut n) => {
-                match value.partial_cmp(&n.value) {
-                    Some(Ordering::Less) => {
-                        n.left = Self::remove_node(n.left.take(), value);
-                    }
-                    Some(Ordering::Greater) => {
-                        n.right = Self::remove_node(n.right.take(), value);
-                    }
-                    Some(Ordering::Equal) => {
-                        // Found the node to delete
-                        return match (n.left.take(), n.right.take()) {
-                            (None, None) => None,
-                            (Some(left), None) => Some(left),
-                            (None, Some(right)) => Some(right),
-                            (Some(left), Some(right)) => {
-                                let (min_val, new_right) = Self::detach_min(right);
-                                n.value = min_val;
-                                n.right = new_right;
-                                n.left = Some(left);
-                                n.update_height();
-                                Some(Box::new(*n.rebalance()))
-                            }
-                        };
-                    }
-                    None => return Some(n),
-                }
+        snapshots
+    }
 
-                n.update_height();
-                Some(Box::new(*n.rebalance()))
-            }
+    /// Follows the directions of a `path` (`true` for left) from a link.
+    fn link_at<'a>(
+        mut link: &'a mut Option<Box<AVLNode<T>>>,
+        path: &[bool],
+    ) -> &'a mut Option<Box<AVLNode<T>>> {
+        for &left in path {
+            let node = link.as_mut().unwrap();
+            link = if left {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
         }
+        link
     }
 
-    fn detach_min(mut node: Box<AVLNode<T>>) -> (T, Option<Box<AVLNode<T>>>)
+    /// Removes a `value` from the tree while maintaining AVL balance properties.
+    ///
+    /// Performs automatic rebalancing through rotations after deletion.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn remove<Q>(&mut self, value: &Q)
     where
-        T: PartialOrd + Clone,
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
     {
-        if let Some(left) = node.left.take() {
-            let (min_val, new_left) = Self::detach_min(left);
-            node.left = new_left;
-            node.update_height();
-            let balanced = node.rebalance();
-            (min_val, Some(Box::new(*balanced)))
-        } else {
-            (node.value.clone(), node.right)
-        }
+        self.take(value);
     }
 
-    /// Checks if the tree contains a `value`.
+    /// Removes a `value` from the tree like `remove` and returns the stored element equal to
+    /// it, or `None` if there was no such element.
     ///
     /// # Complexity:
     /// *O*(log n) - guaranteed due to AVL balancing.
     ///
     /// The logic is the same as in `BST`.
-    pub fn contains(&self, value: &T) -> bool {
-        let mut cursor = &self.root;
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut removed = None;
+        self.root = Self::remove_node(
+            self.root.take(),
+            value,
+            &mut removed,
+            &mut self.pool,
+            &mut self.rotations,
+        );
 
-        while let Some(current_node) = cursor {
-            match value.partial_cmp(&current_node.value) {
-                Some(Ordering::Less) => cursor = &current_node.left,
-                Some(Ordering::Greater) => cursor = &current_node.right,
-                Some(Ordering::Equal) => return true,
-                None => return false,
-            }
+        if let Some(element) = &removed {
+            self.refresh_bounds_after_removal(element);
         }
 
-        false
+        removed
     }
 
-    /// Returns a reference to the minimum element of the tree or `None` if tree is empty.
+    /// Updates the cached minimum and maximum after the `removed` element has been taken out
+    /// of the tree: a cache is searched again only if it held that element.
     ///
     /// # Complexity:
-    /// *O*(1) (due to storing the minimum element inside the tree structure).
-    ///
-    /// The logic is the same as in `BST`.
-    pub fn min(&self) -> Option<&T> {
-        self.min_value.as_ref()
+    /// *O*(log n) if an extreme element has been removed, *O*(1) otherwise.
+    fn refresh_bounds_after_removal(&mut self, removed: &T) {
+        if self
+            .min_value
+            .as_ref()
+            .is_some_and(|min| min.partial_cmp(removed) == Some(Ordering::Equal))
+        {
+            self.min_value = self.refind_min();
+        }
+        if self
+            .max_value
+            .as_ref()
+            .is_some_and(|max| max.partial_cmp(removed) == Some(Ordering::Equal))
+        {
+            self.max_value = self.refind_max();
+        }
     }
 
-    /// Returns a reference to the maximum element of the tree or `None` if tree is empty.
+    /// Removes the minimum element of the tree without returning it, detaching it directly
+    /// instead of searching for it like `remove`.
+    /// Returns whether an element was removed (`false` if the tree is empty).
     ///
     /// # Complexity:
-    /// *O*(1) (due to storing the maximum element inside the tree structure).
-    ///
-    /// The logic is the same as in `BST`.
-    pub fn max(&self) -> Option<&T> {
-        self.max_value.as_ref()
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn remove_min(&mut self) -> bool {
+        self.pop_min().is_some()
     }
 
-    /// Each time the tree is updated, you need to re-search for the minimum.
+    /// Removes the minimum element of the tree and returns it, or returns `None` if the tree
+    /// is empty. The cached `min` is updated and the tree stays balanced.
     ///
     /// # Complexity:
     /// *O*(log n) - guaranteed due to AVL balancing.
-    ///
-    /// The logic is the same as in `BST`.
-    fn refind_min(&self) -> Option<T> {
-        let mut cursor = &self.root;
+    pub fn pop_min(&mut self) -> Option<T> {
+        let root = self.root.take()?;
 
-        while let Some(current_node) = cursor {
-            match current_node.left {
-                Some(_) => cursor = &current_node.left,
-                None => return Some(current_node.value.clone()),
-            }
+        let (min_val, new_root) = Self::detach_min(root, &mut self.pool, &mut self.rotations);
+        self.root = new_root;
+
+        self.min_value = self.refind_min();
+        if self.root.is_none() {
+            self.max_value = None;
         }
-        None
+
+        Some(min_val)
     }
 
-    /// Each time the tree is updated, you need to re-search for the maximum.
+    /// Removes the maximum element of the tree without returning it, detaching it directly
+    /// instead of searching for it like `remove`.
+    /// Returns whether an element was removed (`false` if the tree is empty).
     ///
     /// # Complexity:
     /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn remove_max(&mut self) -> bool {
+        self.pop_max().is_some()
+    }
+
+    /// Removes the maximum element of the tree and returns it, or returns `None` if the tree
+    /// is empty. The cached `max` is updated and the tree stays balanced.
     ///
-    /// The logic is the same as in `BST`.
-    fn refind_max(&self) -> Option<T> {
-        let mut cursor = &self.root;
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn pop_max(&mut self) -> Option<T> {
+        let root = self.root.take()?;
 
-        while let Some(current_node) = cursor {
-            if current_node.right.is_some() {
-                cursor = &current_node.right;
-            } else {
-                return Some(current_node.value.clone());
-            }
+        let (max_val, new_root) = Self::detach_max(root, &mut self.pool, &mut self.rotations);
+        self.root = new_root;
+
+        self.max_value = self.refind_max();
+        if self.root.is_none() {
+            self.min_value = None;
         }
-        None
+
+        Some(max_val)
     }
 
-    /// Returns the height of the tree (longest path from root to leaf).
+    /// Keeps only the values for which `f` returns `true`, visiting them in ascending order.
     ///
-    /// # Complexity:
-    /// *O*(n) - visits all nodes.
+    /// The remaining values are rebuilt into a balanced tree and the min/max caches are refreshed.
     ///
-    /// The logic is the same as in `BST`.
-    pub fn height(&self) -> usize {
-        if self.root.is_none() {
-            return 0;
-        }
+    /// # Complexity:
+    /// *O*(n) - the tree is rebuilt from its in-order sequence.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let values = Self::drain_sorted(self.root.take(), &mut self.pool)
+            .into_iter()
+            .filter(|value| f(value))
+            .collect();
+
+        self.rebuild_from_sorted(values);
+    }
 
-        let mut height = 0;
-        let mut queue = VecDeque::new();
+    /// Removes every value for which `pred` returns `true` and returns them in ascending order
+    /// (the values are visited in ascending order).
+    ///
+    /// The remaining values are rebuilt into a balanced tree and the min/max caches are refreshed.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is rebuilt from its in-order sequence.
+    pub fn extract_if<F: FnMut(&T) -> bool>(&mut self, mut pred: F) -> Vec<T> {
+        let (extracted, values): (Vec<T>, Vec<T>) =
+            Self::drain_sorted(self.root.take(), &mut self.pool)
+                .into_iter()
+                .partition(|value| pred(value));
+
+        self.rebuild_from_sorted(values);
+        extracted
+    }
 
-        if let Some(root) = &self.root {
-            queue.push_back(root);
-        }
+    /// Removes every value outside of the inclusive range `[lo, hi]`, keeping only the
+    /// values `x` with `lo <= x <= hi`.
+    ///
+    /// The remaining values are rebuilt into a balanced tree. If `lo > hi` the tree is emptied.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is rebuilt from its in-order sequence.
+    pub fn retain_range(&mut self, lo: &T, hi: &T) {
+        let values = Self::drain_sorted(self.root.take(), &mut self.pool)
+            .into_iter()
+            .skip_while(|value| value < lo)
+            .take_while(|value| value <= hi)
+            .collect();
+
+        self.rebuild_from_sorted(values);
+    }
 
-        while !queue.is_empty() {
-            let level_size = queue.len();
+    /// Moves all values greater than or equal to `key` into a new tree, leaving the values less
+    /// than `key` in `self` (like `BTreeSet::split_off`).
+    ///
+    /// Both parts are rebuilt into balanced trees, so they keep the AVL balance properties.
+    ///
+    /// # Complexity:
+    /// *O*(n) - both parts are rebuilt from the in-order sequence.
+    pub fn split_off(&mut self, key: &T) -> Self {
+        let mut values = Self::drain_sorted(self.root.take(), &mut self.pool);
+        let rest = values.split_off(values.partition_point(|value| value < key));
 
-            for _ in 0..level_size {
-                let node = queue.pop_front().unwrap();
+        let mut other = Self::new();
+        other.pool = self.pool.split_off(rest.len());
+        other.rebuild_from_sorted(rest);
+        self.rebuild_from_sorted(values);
 
-                if let Some(left) = &node.left {
-                    queue.push_back(left);
-                }
-                if let Some(right) = &node.right {
-                    queue.push_back(right);
-                }
-            }
+        other
+    }
 
-            if !queue.is_empty() {
-                height += 1;
-            }
+    /// Removes all values of the inclusive range `[lo, hi]` from the tree and returns them as
+    /// a new tree.
+    ///
+    /// Both trees are rebuilt into balanced ones, so they keep the AVL balance properties.
+    /// If `lo > hi` nothing is removed.
+    ///
+    /// # Complexity:
+    /// *O*(n) - both trees are rebuilt from the in-order sequence.
+    pub fn drain_range(&mut self, lo: &T, hi: &T) -> Self {
+        let mut drained = Self::new();
+        if lo > hi {
+            return drained;
         }
 
-        height
+        let mut values = Self::drain_sorted(self.root.take(), &mut self.pool);
+        let start = values.partition_point(|value| value < lo);
+        let end = values.partition_point(|value| value <= hi);
+
+        drained.pool = self.pool.split_off(end - start);
+        drained.rebuild_from_sorted(values.drain(start..end).collect());
+        self.rebuild_from_sorted(values);
+
+        drained
     }
 
-    /// Returns references to the elements of the tree in the order of a preorder traversal.
+    /// Moves all values of `other` into `self`, leaving `other` empty (like `BTreeSet::append`).
+    ///
+    /// If a value is present in both trees, the copy from `self` is kept. The two in-order
+    /// sequences are merged and the result is rebuilt into a balanced tree.
     ///
     /// # Complexity:
-    /// *O*(n) - visits all nodes.
+    /// *O*(n + m) - both trees are visited once.
+    pub fn append(&mut self, other: &mut Self) {
+        let values = Self::merge_sorted(
+            Self::drain_sorted(self.root.take(), &mut self.pool),
+            Self::drain_sorted(other.root.take(), &mut other.pool),
+        );
+        self.pool.append(&mut other.pool);
+        self.rebuild_from_sorted(values);
+
+        other.min_value = None;
+        other.max_value = None;
+    }
+
+    /// Consumes `other` and moves all of its values into `self`, like `append` (the copy from
+    /// `self` is kept for values present in both trees).
     ///
-    /// # Example:
-    ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    ///
-    /// Then the result of this traversal will be like this: `vec![&4, &2, &1, &3, &5, &6]`.
-    ///
-    /// The logic is the same as in `BST`.
-    pub fn pre_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut stack = Vec::new();
-        let mut current = &self.root;
+    /// # Complexity:
+    /// *O*(n + m) - the in-order sequences are merged and rebuilt, instead of *O*(m log(n + m))
+    /// for `m` separate insertions.
+    pub fn merge(&mut self, mut other: AVLTree<T>) {
+        self.append(&mut other);
+    }
 
-        while !stack.is_empty() || current.is_some() {
-            while let Some(node) = current {
-                result.push(&node.value);
-                stack.push(node);
-                current = &node.left;
+    /// Merges two strictly ascending sequences into one, keeping the values of `left` on ties.
+    fn merge_sorted(left: Vec<T>, right: Vec<T>) -> Vec<T> {
+        let mut result = Vec::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            match a.partial_cmp(b) {
+                Some(Ordering::Greater) => result.extend(right.next()),
+                Some(Ordering::Equal) => {
+                    right.next();
+                    result.extend(left.next());
+                }
+                _ => result.extend(left.next()),
             }
-
-            current = &stack.pop().unwrap().right;
         }
 
+        result.extend(left);
+        result.extend(right);
         result
     }
 
-    /// Returns references to the elements of the tree in the order of a inorder traversal.
+    /// Builds a new balanced tree out of the `Some` results of `f` applied to the elements
+    /// in ascending order, dropping the `None`s (and the duplicates among the results).
     ///
     /// # Complexity:
-    /// *O*(n) - visits all nodes.
-    ///
-    /// # Example:
-    ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    /// Then the result of this traversal will be like this: `vec![&1, &2, &3, &4, &5, &6]`.
-    ///
-    /// The logic is the same as in `BST`.
-    pub fn in_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut stack = Vec::new();
-        let mut current = &self.root;
+    /// *O*(n log n) - dominated by sorting the results (see `from_unsorted`).
+    pub fn filter_map<U, F>(&self, f: F) -> AVLTree<U>
+    where
+        U: PartialOrd + Clone,
+        F: Fn(&T) -> Option<U>,
+    {
+        AVLTree::from_unsorted(self.in_order().into_iter().filter_map(f))
+    }
 
-        while !stack.is_empty() || current.is_some() {
-            while let Some(node) = current {
-                stack.push(node);
-                current = &node.left;
+    /// Removes the node equal to the `value` from the subtree (storing its element in
+    /// `removed`) and rebalances the subtree.
+    ///
+    /// The nodes on the search path are detached onto an explicit stack and rebalanced
+    /// bottom-up while they are reattached, so deep trees don't grow the call stack.
+    fn remove_node<Q>(
+        node: Option<Box<AVLNode<T>>>,
+        value: &Q,
+        removed: &mut Option<T>,
+        pool: &mut NodePool<AVLNode<T>>,
+        rotations: &mut Rotations,
+    ) -> Option<Box<AVLNode<T>>>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // Ancestors of the current subtree, with whether the subtree is their left child
+        let mut path: [Option<(Box<AVLNode<T>>, bool)>; MAX_HEIGHT] = [const { None }; MAX_HEIGHT];
+        let mut depth = 0;
+        let mut cursor = node;
+
+        let mut subtree = loop {
+            let Some(mut n) = cursor else {
+                break None;
+            };
+
+            match value.partial_cmp(n.value.borrow()) {
+                Some(Ordering::Less) => {
+                    cursor = n.left.take();
+                    path[depth] = Some((n, true));
+                    depth += 1;
+                }
+                Some(Ordering::Greater) => {
+                    cursor = n.right.take();
+                    path[depth] = Some((n, false));
+                    depth += 1;
+                }
+                Some(Ordering::Equal) => {
+                    // Found the node to delete
+                    break match (n.left.take(), n.right.take()) {
+                        (Some(left), Some(right)) => {
+                            let (min_val, new_right) = Self::detach_min(right, pool, rotations);
+                            *removed = Some(std::mem::replace(&mut n.value, min_val));
+                            n.right = new_right;
+                            n.left = Some(left);
+                            n.update_height();
+                            Some(n.rebalance_counting(rotations))
+                        }
+                        (left, right) => {
+                            *removed = Some(pool.release(n).value);
+                            left.or(right)
+                        }
+                    };
+                }
+                None => break Some(n),
             }
+        };
 
-            if let Some(node) = stack.pop() {
-                result.push(&node.value);
-                current = &node.right;
+        for (mut parent, is_left) in path[..depth].iter_mut().rev().filter_map(Option::take) {
+            if is_left {
+                parent.left = subtree;
+            } else {
+                parent.right = subtree;
             }
+            parent.update_height();
+            subtree = Some(parent.rebalance_counting(rotations));
         }
 
-        result
+        subtree
     }
 
-    /// Returns references to the elements of the tree in the order of a postorder traversal.
+    /// Detaches the minimum of the subtree, returning its element and the rebalanced rest.
+    fn detach_min(
+        mut node: Box<AVLNode<T>>,
+        pool: &mut NodePool<AVLNode<T>>,
+        rotations: &mut Rotations,
+    ) -> (T, Option<Box<AVLNode<T>>>)
+    where
+        T: PartialOrd + Clone,
+    {
+        // The left spine above the minimum
+        let mut path: [Option<Box<AVLNode<T>>>; MAX_HEIGHT] = [const { None }; MAX_HEIGHT];
+        let mut depth = 0;
+
+        while let Some(left) = node.left.take() {
+            path[depth] = Some(node);
+            depth += 1;
+            node = left;
+        }
+
+        let node = pool.release(node);
+        let mut subtree = node.right;
+
+        for mut parent in path[..depth].iter_mut().rev().filter_map(Option::take) {
+            parent.left = subtree;
+            parent.update_height();
+            subtree = Some(parent.rebalance_counting(rotations));
+        }
+
+        (node.value, subtree)
+    }
+
+    /// Detaches the maximum of the subtree (the mirror of `detach_min`).
+    fn detach_max(
+        mut node: Box<AVLNode<T>>,
+        pool: &mut NodePool<AVLNode<T>>,
+        rotations: &mut Rotations,
+    ) -> (T, Option<Box<AVLNode<T>>>)
+    where
+        T: PartialOrd + Clone,
+    {
+        // The right spine above the maximum
+        let mut path: [Option<Box<AVLNode<T>>>; MAX_HEIGHT] = [const { None }; MAX_HEIGHT];
+        let mut depth = 0;
+
+        while let Some(right) = node.right.take() {
+            path[depth] = Some(node);
+            depth += 1;
+            node = right;
+        }
+
+        let node = pool.release(node);
+        let mut subtree = node.left;
+
+        for mut parent in path[..depth].iter_mut().rev().filter_map(Option::take) {
+            parent.right = subtree;
+            parent.update_height();
+            subtree = Some(parent.rebalance_counting(rotations));
+        }
+
+        (node.value, subtree)
+    }
+
+    /// Returns the number of rotations performed by the tree over its lifetime.
+    ///
+    /// A double rotation (left-right or right-left case) counts as two rotations.
     ///
     /// # Complexity:
-    /// *O*(n) - visits all nodes.
+    /// *O*(1) - the counter is maintained by insertions and removals.
+    #[cfg(feature = "metrics")]
+    pub fn rotation_count(&self) -> u64 {
+        self.rotations.count()
+    }
+
+    /// Checks if the tree contains a `value`.
     ///
-    /// # Example:
+    /// Like the other lookups, it accepts any borrowed form of the element type.
     ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    /// Then the result of this traversal will be like this: `vec![&1, &3, &2, &6, &5, &4]`.
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
     ///
     /// The logic is the same as in `BST`.
-    pub fn post_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut stack = Vec::new();
-        let mut current = &self.root;
-        let mut last_visited: Option<&Box<AVLNode<T>>> = None;
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut cursor = &self.root;
 
-        while !stack.is_empty() || current.is_some() {
-            while let Some(node) = current {
-                stack.push(node);
-                current = &node.left;
+        while let Some(current_node) = cursor {
+            match value.partial_cmp(current_node.value.borrow()) {
+                Some(Ordering::Less) => cursor = &current_node.left,
+                Some(Ordering::Greater) => cursor = &current_node.right,
+                Some(Ordering::Equal) => return true,
+                None => return false,
             }
+        }
 
-            if let Some(node) = stack.last() {
-                let right_visited = match (&node.right, last_visited) {
-                    (Some(right), Some(last)) => std::ptr::eq(right.as_ref(), last.as_ref()),
-                    _ => false,
-                };
+        false
+    }
 
-                if node.right.is_none() || right_visited {
-                    result.push(&node.value);
-                    last_visited = stack.pop();
-                } else {
-                    current = &node.right;
-                }
+    /// Returns a reference to the stored element that compares equal to the `value`,
+    /// or `None` if there is no such element.
+    ///
+    /// Elements are identified by `partial_cmp` alone, so the stored representative may differ
+    /// from the `value` in everything that the ordering ignores (and even by `==`).
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn stored_equal_to<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            match value.partial_cmp(current_node.value.borrow())? {
+                Ordering::Less => cursor = &current_node.left,
+                Ordering::Greater => cursor = &current_node.right,
+                Ordering::Equal => return Some(&current_node.value),
             }
         }
 
-        result
+        None
     }
 
-    /// Returns references to the elements of the tree in the order of a level order traversal.
+    /// Returns a reference to the stored element that compares equal to the `value`
+    /// (an alias for `stored_equal_to`), for map-like usage where equality is by key.
     ///
     /// # Complexity:
-    /// *O*(n) - visits all nodes.
+    /// *O*(log n) - guaranteed due to AVL balancing.
     ///
-    /// # Example:
+    /// The logic is the same as in `BST`.
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.stored_equal_to(value)
+    }
+
+    /// Returns the rank of the `value` (the number of elements less than it) if the tree
+    /// contains it, or `None` otherwise.
     ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    /// Then the result of this traversal will be like this: `vec![&4, &2, &5, &1, &3, &6]`.
+    /// Presence and rank are found in a single descent using the subtree sizes of the nodes.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
     ///
     /// The logic is the same as in `BST`.
-    pub fn level_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut queue = VecDeque::new();
-
-        if let Some(root) = &self.root {
-            queue.push_back(root);
-        }
-
-        while let Some(node) = queue.pop_front() {
-            result.push(&node.value);
+    pub fn find(&self, value: &T) -> Option<usize> {
+        let mut rank = 0;
+        let mut cursor = &self.root;
 
-            if let Some(left) = &node.left {
-                queue.push_back(left);
-            }
-            if let Some(right) = &node.right {
-                queue.push_back(right);
+        while let Some(current_node) = cursor {
+            match value.partial_cmp(&current_node.value)? {
+                Ordering::Less => cursor = &current_node.left,
+                Ordering::Greater => {
+                    rank += AVLNode::size(&current_node.left) + 1;
+                    cursor = &current_node.right;
+                }
+                Ordering::Equal => return Some(rank + AVLNode::size(&current_node.left)),
             }
         }
 
-        result
+        None
     }
 
-    /// Returns the number of elements of the tree (the number of elements in the vector
-    /// for the preorder traversal).
+    /// Checks if at least one element `x` of the tree lies in the inclusive range `[lo, hi]`
+    /// (`lo <= x <= hi`), stopping at the first one found. If `lo > hi` the result is `false`.
     ///
     /// # Complexity:
-    /// *O*(n) - traverses entire tree.
+    /// *O*(log n) - guaranteed due to AVL balancing.
     ///
     /// The logic is the same as in `BST`.
-    pub fn number_of_elements(&self) -> usize {
-        self.pre_order().len()
+    pub fn has_any_in_range(&self, lo: &T, hi: &T) -> bool {
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            if &current_node.value < lo {
+                cursor = &current_node.right;
+            } else if &current_node.value > hi {
+                cursor = &current_node.left;
+            } else {
+                return true;
+            }
+        }
+
+        false
     }
 
-    /// Returns a value that is the rounded `value` to the nearest larger in the tree,
-    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
-    /// given `value`).
+    /// Returns the positions `start..end` in ascending order covered by the elements `x` of
+    /// the inclusive range `[lo, hi]` (`lo <= x <= hi`), so that they are exactly
+    /// `in_order()[start..end]`. An empty intersection (e.g. `lo > hi`) gives `start == end`.
     ///
     /// # Complexity:
-    /// *O*(log n) - guaranteed due to AVL balancing.
+    /// *O*(log n) - two descents, guaranteed due to AVL balancing.
     ///
     /// The logic is the same as in `BST`.
-    pub fn ceil(&self, value: &T) -> Option<&T> {
-        self.root.as_ref()?;
+    pub fn index_range(&self, lo: &T, hi: &T) -> Range<usize> {
+        let start = self.count_prefix(|value| value < lo);
+        let end = self.count_prefix(|value| value <= hi);
+        start..end.max(start)
+    }
 
-        let mut result = None;
+    /// Counts the elements satisfying `in_prefix`, which must hold for a prefix of the
+    /// elements in ascending order, descending with the subtree sizes of the nodes.
+    fn count_prefix(&self, in_prefix: impl Fn(&T) -> bool) -> usize {
+        let mut count = 0;
         let mut cursor = &self.root;
 
         while let Some(node) = cursor {
-            if &node.value == value {
-                return Some(&node.value);
-            }
-
-            if &node.value < value {
+            if in_prefix(&node.value) {
+                count += AVLNode::size(&node.left) + 1;
                 cursor = &node.right;
             } else {
-                result = Some(&node.value);
                 cursor = &node.left;
             }
         }
 
-        result
+        count
     }
 
-    /// Returns a value that is the rounded `value` to the nearest smaller in the tree,
-    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
-    /// given `value`).
+    /// Returns a reference to the `k`-th smallest element of the tree (1-based), or `None` if
+    /// `k == 0` or `k` exceeds the number of elements.
     ///
     /// # Complexity:
-    /// *O*(log n) - guaranteed due to AVL balancing.
-    ///
-    /// The logic is the same as in `BST`.
-    pub fn floor(&self, value: &T) -> Option<&T> {
-        self.root.as_ref()?;
+    /// *O*(log n) - descends with the subtree sizes, guaranteed due to AVL balancing.
+    pub fn kth_smallest(&self, k: usize) -> Option<&T> {
+        if k == 0 {
+            return None;
+        }
 
-        let mut result = None;
+        let mut rank = k - 1;
         let mut cursor = &self.root;
 
         while let Some(node) = cursor {
-            if &node.value == value {
-                return Some(&node.value);
+            let left_size = AVLNode::size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Less => cursor = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    rank -= left_size + 1;
+                    cursor = &node.right;
+                }
             }
+        }
+
+        None
+    }
+
+    /// Returns a reference to the `k`-th largest element of the tree (1-based), or `None` if
+    /// `k == 0` or `k` exceeds the number of elements.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn kth_largest(&self, k: usize) -> Option<&T> {
+        let len = self.len();
+        if k == 0 || k > len {
+            return None;
+        }
+        self.kth_smallest(len - k + 1)
+    }
+
+    /// Returns a reference to the minimum element of the tree or `None` if tree is empty.
+    ///
+    /// The minimum is the first element in the order defined by `T`, so for a tree of
+    /// `std::cmp::Reverse<U>` it wraps the largest `U`.
+    ///
+    /// # Complexity:
+    /// *O*(1) (due to storing the minimum element inside the tree structure).
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn min(&self) -> Option<&T> {
+        self.min_value.as_ref()
+    }
+
+    /// Returns a reference to the maximum element of the tree or `None` if tree is empty.
+    ///
+    /// The maximum is the last element in the order defined by `T`, so for a tree of
+    /// `std::cmp::Reverse<U>` it wraps the smallest `U`.
+    ///
+    /// # Complexity:
+    /// *O*(1) (due to storing the maximum element inside the tree structure).
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn max(&self) -> Option<&T> {
+        self.max_value.as_ref()
+    }
+
+    /// Returns a reference to the value of the root node or `None` if tree is empty.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn root_value(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
+    /// Each time the tree is updated, you need to re-search for the minimum.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    fn refind_min(&self) -> Option<T> {
+        #[cfg(test)]
+        REFINDS.with(|refinds| refinds.set(refinds.get() + 1));
+
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            match current_node.left {
+                Some(_) => cursor = &current_node.left,
+                None => return Some(current_node.value.clone()),
+            }
+        }
+        None
+    }
+
+    /// Each time the tree is updated, you need to re-search for the maximum.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    fn refind_max(&self) -> Option<T> {
+        #[cfg(test)]
+        REFINDS.with(|refinds| refinds.set(refinds.get() + 1));
+
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            if current_node.right.is_some() {
+                cursor = &current_node.right;
+            } else {
+                return Some(current_node.value.clone());
+            }
+        }
+        None
+    }
+
+    /// Returns the depth of the node holding the `value` (the number of edges from the root,
+    /// which is at depth 0), or `None` if the tree doesn't contain the `value`.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn depth(&self, value: &T) -> Option<usize> {
+        let mut cursor = &self.root;
+        let mut depth = 0;
+
+        while let Some(node) = cursor {
+            match value.partial_cmp(&node.value)? {
+                Ordering::Less => cursor = &node.left,
+                Ordering::Greater => cursor = &node.right,
+                Ordering::Equal => return Some(depth),
+            }
+            depth += 1;
+        }
+
+        None
+    }
+
+    /// Returns the balance factor (left subtree height minus right subtree height, see
+    /// `AVLNode::balance_factor`) of the node holding the `value`, or `None` if the tree
+    /// doesn't contain the `value`. It is always in `-1..=1` between operations.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn balance_factor_of(&self, value: &T) -> Option<i32> {
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            match value.partial_cmp(&node.value)? {
+                Ordering::Less => cursor = &node.left,
+                Ordering::Greater => cursor = &node.right,
+                Ordering::Equal => return Some(node.balance_factor()),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the height of the tree (longest path from root to leaf).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn height(&self) -> usize {
+        if self.root.is_none() {
+            return 0;
+        }
+
+        let mut height = 0;
+        let mut queue = VecDeque::new();
+
+        if let Some(root) = &self.root {
+            queue.push_back(root);
+        }
+
+        while !queue.is_empty() {
+            let level_size = queue.len();
+
+            for _ in 0..level_size {
+                let node = queue.pop_front().unwrap();
+
+                if let Some(left) = &node.left {
+                    queue.push_back(left);
+                }
+                if let Some(right) = &node.right {
+                    queue.push_back(right);
+                }
+            }
+
+            if !queue.is_empty() {
+                height += 1;
+            }
+        }
+
+        height
+    }
+
+    /// Returns the diameter of the tree: the number of edges on the longest path between
+    /// any two nodes (0 for an empty tree and for a single node).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn diameter(&self) -> usize {
+        let mut diameter = 0;
+        // The numbers of levels of the finished subtrees, the right one on top.
+        let mut levels = Vec::new();
+        let mut stack: Vec<_> = self.root.iter().map(|node| (node, false)).collect();
+
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                let right = node.right.as_ref().and_then(|_| levels.pop()).unwrap_or(0);
+                let left = node.left.as_ref().and_then(|_| levels.pop()).unwrap_or(0);
+                diameter = diameter.max(left + right);
+                levels.push(left.max(right) + 1);
+            } else {
+                stack.push((node, true));
+                stack.extend(node.right.iter().map(|right| (right, false)));
+                stack.extend(node.left.iter().map(|left| (left, false)));
+            }
+        }
+
+        diameter
+    }
+
+    /// Returns the number of leaves of the tree (nodes without children).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn count_leaves(&self) -> usize {
+        let mut leaves = 0;
+        let mut stack: Vec<_> = self.root.iter().collect();
+
+        while let Some(node) = stack.pop() {
+            if node.left.is_none() && node.right.is_none() {
+                leaves += 1;
+            }
+            stack.extend(&node.left);
+            stack.extend(&node.right);
+        }
+
+        leaves
+    }
+
+    /// Returns the number of internal nodes of the tree (nodes with at least one child).
+    /// Together with `count_leaves` it adds up to `len()`.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes (to count the leaves).
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn count_internal_nodes(&self) -> usize {
+        self.len() - self.count_leaves()
+    }
+
+    /// Returns references to the elements of the tree in the order of a preorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    ///
+    /// Then the result of this traversal will be like this: `vec![&4, &2, &1, &3, &5, &6]`.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn pre_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                result.push(&node.value);
+                stack.push(node);
+                current = &node.left;
+            }
+
+            current = &stack.pop().unwrap().right;
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in the order of a inorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    /// Then the result of this traversal will be like this: `vec![&1, &2, &3, &4, &5, &6]`.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn in_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+
+            if let Some(node) = stack.pop() {
+                result.push(&node.value);
+                current = &node.right;
+            }
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in descending order (the reverse of
+    /// `in_order`), walking the tree right-first instead of reversing a vector.
+    ///
+    /// `iter().rev()` yields the same elements lazily, without building a vector.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn reverse_in_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.right;
+            }
+
+            if let Some(node) = stack.pop() {
+                result.push(&node.value);
+                current = &node.left;
+            }
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in the order of a postorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    /// Then the result of this traversal will be like this: `vec![&1, &3, &2, &6, &5, &4]`.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn post_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+        let mut last_visited: Option<&Box<AVLNode<T>>> = None;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+
+            if let Some(node) = stack.last() {
+                let right_visited = match (&node.right, last_visited) {
+                    (Some(right), Some(last)) => std::ptr::eq(right.as_ref(), last.as_ref()),
+                    _ => false,
+                };
+
+                if node.right.is_none() || right_visited {
+                    result.push(&node.value);
+                    last_visited = stack.pop();
+                } else {
+                    current = &node.right;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in the order of a level order traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    /// Then the result of this traversal will be like this: `vec![&4, &2, &5, &1, &3, &6]`.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn level_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+
+        if let Some(root) = &self.root {
+            queue.push_back(root);
+        }
+
+        while let Some(node) = queue.pop_front() {
+            result.push(&node.value);
+
+            if let Some(left) = &node.left {
+                queue.push_back(left);
+            }
+            if let Some(right) = &node.right {
+                queue.push_back(right);
+            }
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree grouped by depth: the element `i` of
+    /// the result holds the elements at depth `i` (the root at depth 0) from left to right.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is
+    /// `vec![vec![&4], vec![&2, &5], vec![&1, &3, &6]]`.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn level_order_grouped(&self) -> Vec<Vec<&T>> {
+        let mut result = Vec::new();
+        let mut level: Vec<&Box<_>> = self.root.iter().collect();
+
+        while !level.is_empty() {
+            result.push(level.iter().map(|node| &node.value).collect());
+            level = level
+                .into_iter()
+                .flat_map(|node| [&node.left, &node.right])
+                .flatten()
+                .collect();
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in the order of a level order traversal,
+    /// with `None` markers for the absent children of the present nodes, so that the exact shape
+    /// of the tree can be recovered (the format used by LeetCode).
+    ///
+    /// Trailing `None` markers are omitted, an empty tree gives an empty vector.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    /// ```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    /// ```
+    /// Then the result of this traversal will be like this:
+    /// `vec![Some(&4), Some(&2), Some(&5), Some(&1), Some(&3), None, Some(&6)]`.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn level_order_with_nulls(&self) -> Vec<Option<&T>> {
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+
+        if self.root.is_some() {
+            queue.push_back(&self.root);
+        }
+
+        while let Some(link) = queue.pop_front() {
+            match link {
+                Some(node) => {
+                    result.push(Some(&node.value));
+                    queue.push_back(&node.left);
+                    queue.push_back(&node.right);
+                }
+                None => result.push(None),
+            }
+        }
+
+        while matches!(result.last(), Some(None)) {
+            result.pop();
+        }
+
+        result
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a preorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn pre_order_cloned(&self) -> Vec<T> {
+        self.pre_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a inorder traversal
+    /// (i.e. sorted in ascending order).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn in_order_cloned(&self) -> Vec<T> {
+        self.in_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a postorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn post_order_cloned(&self) -> Vec<T> {
+        self.post_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a level order traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn level_order_cloned(&self) -> Vec<T> {
+        self.level_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a level order traversal,
+    /// with `None` markers for absent children (see `level_order_with_nulls`).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn level_order_with_nulls_cloned(&self) -> Vec<Option<T>> {
+        self.level_order_with_nulls()
+            .into_iter()
+            .map(|value| value.cloned())
+            .collect()
+    }
+
+    /// Consumes the tree and moves its elements out in the order of a level order traversal,
+    /// without cloning them.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn into_level_order(self) -> Vec<T> {
+        let mut result = Vec::new();
+        let mut queue: VecDeque<Box<AVLNode<T>>> = self.root.into_iter().collect();
+
+        while let Some(node) = queue.pop_front() {
+            let node = *node;
+            queue.extend(node.left);
+            queue.extend(node.right);
+            result.push(node.value);
+        }
+
+        result
+    }
+
+    /// Consumes the tree and moves its elements out in ascending order, without cloning them.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        Self::drain_sorted(self.root, &mut self.pool)
+    }
+
+    /// Calls `f` on every element of the tree in the given traversal `order`, without
+    /// collecting the elements into a vector.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn walk<F: FnMut(&T)>(&self, order: TraversalOrder, mut f: F) {
+        let _ = self.try_walk(order, |value| {
+            f(value);
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    /// Calls `f` on every element of the tree in the given traversal `order` (an alias for
+    /// `walk`).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn for_each<F: FnMut(&T)>(&self, order: TraversalOrder, f: F) {
+        self.walk(order, f);
+    }
+
+    /// Calls `f` on the elements of the tree in the given traversal `order` until it returns
+    /// `ControlFlow::Break`, and returns the break value (or `ControlFlow::Continue(())` if
+    /// all elements have been visited).
+    ///
+    /// # Complexity:
+    /// *O*(k) (up to *O*(n)), where `k` is the number of visited elements (plus the nodes
+    /// on the path to the first of them).
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn try_walk<B, F: FnMut(&T) -> ControlFlow<B>>(
+        &self,
+        order: TraversalOrder,
+        mut f: F,
+    ) -> ControlFlow<B> {
+        match order {
+            TraversalOrder::PreOrder => {
+                let mut stack: Vec<_> = self.root.iter().collect();
+
+                while let Some(node) = stack.pop() {
+                    f(&node.value)?;
+                    stack.extend(&node.right);
+                    stack.extend(&node.left);
+                }
+            }
+            TraversalOrder::InOrder => {
+                let mut stack = Vec::new();
+                let mut current = &self.root;
+
+                while current.is_some() || !stack.is_empty() {
+                    while let Some(node) = current {
+                        stack.push(node);
+                        current = &node.left;
+                    }
+
+                    if let Some(node) = stack.pop() {
+                        f(&node.value)?;
+                        current = &node.right;
+                    }
+                }
+            }
+            TraversalOrder::PostOrder => {
+                // Each node is pushed twice: to expand its children, then to be visited.
+                let mut stack: Vec<_> = self.root.iter().map(|node| (node, false)).collect();
+
+                while let Some((node, expanded)) = stack.pop() {
+                    if expanded {
+                        f(&node.value)?;
+                    } else {
+                        stack.push((node, true));
+                        stack.extend(node.right.iter().map(|right| (right, false)));
+                        stack.extend(node.left.iter().map(|left| (left, false)));
+                    }
+                }
+            }
+            TraversalOrder::LevelOrder => {
+                let mut queue: VecDeque<_> = self.root.iter().collect();
+
+                while let Some(node) = queue.pop_front() {
+                    f(&node.value)?;
+                    queue.extend(&node.left);
+                    queue.extend(&node.right);
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Checks if the elements of the tree in ascending order are equal to the `other` slice,
+    /// which is expected to be sorted.
+    ///
+    /// A shorthand for comparing `in_order()` against a sorted reference in tests.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes (*O*(1) if the lengths differ).
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn same_elements_as(&self, other: &[T]) -> bool {
+        AVLNode::size(&self.root) == other.len() && self.in_order().into_iter().eq(other)
+    }
+
+    /// Checks that every element of the tree is comparable to itself and to its neighbors
+    /// in ascending order (`partial_cmp` returns `Some(_)`), e.g. that no `NaN` is stored.
+    ///
+    /// Incomparable values break the ordering that searches rely on, which the BST invariant
+    /// check alone may not notice.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn all_comparable(&self) -> bool {
+        let values = self.in_order();
+
+        values
+            .iter()
+            .all(|value| value.partial_cmp(value).is_some())
+            && values
+                .windows(2)
+                .all(|pair| pair[0].partial_cmp(pair[1]).is_some())
+    }
+
+    /// Walks this tree and the `other` one together in ascending order, yielding every element
+    /// as `DiffItem::Left` (only in `self`), `DiffItem::Right` (only in `other`) or
+    /// `DiffItem::Both` (in both trees).
+    ///
+    /// Elements that are incomparable to the current element of the other tree are yielded
+    /// as `DiffItem::Left` first.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - both trees are visited once.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn merge_diff<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = DiffItem<'a, T>> {
+        let mut left = self.in_order().into_iter().peekable();
+        let mut right = other.in_order().into_iter().peekable();
+
+        std::iter::from_fn(move || match (left.peek(), right.peek()) {
+            (Some(a), Some(b)) => match a.partial_cmp(b) {
+                Some(Ordering::Greater) => right.next().map(DiffItem::Right),
+                Some(Ordering::Equal) => Some(DiffItem::Both(left.next()?, right.next()?)),
+                _ => left.next().map(DiffItem::Left),
+            },
+            (Some(_), None) => left.next().map(DiffItem::Left),
+            (None, _) => right.next().map(DiffItem::Right),
+        })
+    }
+
+    /// Returns a lazy iterator over the elements of the tree in ascending order, which also
+    /// iterates in descending order from the back (`iter().rev()`).
+    ///
+    /// Unlike `in_order`, no vector of all elements is built, so stopping early is cheap.
+    /// To move the elements out instead, consume the tree with `into_iter`.
+    ///
+    /// # Complexity:
+    /// *O*(log n) to create, and amortized *O*(1) per element.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+
+    /// Returns a `Cursor` positioned before the smallest element, which reads the elements
+    /// in ascending order page by page (see `Cursor::next_page`).
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor::new(self)
+    }
+
+    /// Returns the number of elements of the tree.
+    ///
+    /// The count is read from the subtree size cached in the root, so duplicates rejected by
+    /// `insert` and failed removals never affect it.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn len(&self) -> usize {
+        AVLNode::size(&self.root)
+    }
+
+    /// Returns the number of elements of the tree.
+    ///
+    /// Kept for compatibility, delegates to [`len`](Self::len).
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn number_of_elements(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns an estimate of the memory used by the tree in bytes: the tree itself plus one
+    /// boxed node allocation per element (and per pooled allocation with the `pool` feature).
+    ///
+    /// The node size includes the child links, the subtree size and the cached `height` of every node.
+    ///
+    /// Allocator padding and any heap memory owned by the values themselves are not counted.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn approx_memory_bytes(&self) -> usize {
+        let nodes = AVLNode::size(&self.root) + self.pool.len();
+        size_of::<Self>() + nodes * size_of::<AVLNode<T>>()
+    }
+
+    /// Returns a value that is the rounded `value` to the nearest larger in the tree,
+    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
+    /// given `value`).
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn ceil<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.root.as_ref()?;
+
+        let mut result = None;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            if node.value.borrow() == value {
+                return Some(&node.value);
+            }
+
+            if node.value.borrow() < value {
+                cursor = &node.right;
+            } else {
+                result = Some(&node.value);
+                cursor = &node.left;
+            }
+        }
+
+        result
+    }
+
+    /// Returns a value that is the rounded `value` to the nearest smaller in the tree,
+    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
+    /// given `value`).
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn floor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.root.as_ref()?;
+
+        let mut result = None;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            if node.value.borrow() == value {
+                return Some(&node.value);
+            }
+
+            if node.value.borrow() > value {
+                cursor = &node.left;
+            } else {
+                result = Some(&node.value);
+                cursor = &node.right;
+            }
+        }
+
+        result
+    }
+
+    /// Returns the smallest element of the tree that is greater than or equal to `value`
+    /// (an alias for `ceil`).
+    ///
+    /// # Complexity:
+    /// The same as for `ceil`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::avl_tree::AVLTree;
+    ///
+    /// let mut avl = AVLTree::new();
+    /// for value in [10, 20, 30] {
+    ///     avl.insert(value);
+    /// }
+    ///
+    /// assert_eq!(avl.successor_or_equal(&15), Some(&20));
+    /// assert_eq!(avl.successor_or_equal(&20), Some(&20));
+    /// assert_eq!(avl.successor_or_equal(&31), None);
+    /// ```
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn successor_or_equal(&self, value: &T) -> Option<&T> {
+        self.ceil(value)
+    }
+
+    /// Returns the largest element of the tree that is less than or equal to `value`
+    /// (an alias for `floor`).
+    ///
+    /// # Complexity:
+    /// The same as for `floor`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::avl_tree::AVLTree;
+    ///
+    /// let mut avl = AVLTree::new();
+    /// for value in [10, 20, 30] {
+    ///     avl.insert(value);
+    /// }
+    ///
+    /// assert_eq!(avl.predecessor_or_equal(&15), Some(&10));
+    /// assert_eq!(avl.predecessor_or_equal(&20), Some(&20));
+    /// assert_eq!(avl.predecessor_or_equal(&9), None);
+    /// ```
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn predecessor_or_equal(&self, value: &T) -> Option<&T> {
+        self.floor(value)
+    }
+
+    /// Snaps a `value` to the nearest element of the tree at or below it (the `floor`), falling
+    /// back to the minimum when there is no such element (the `value` is below the range).
+    /// Returns `None` only if the tree is empty.
+    ///
+    /// # Example:
+    ///
+    /// For a tree of `10, 20, 30`: `clamp(&5)` is `Some(&10)`, `clamp(&25)` is `Some(&20)`,
+    /// `clamp(&20)` is `Some(&20)` and `clamp(&99)` is `Some(&30)`.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn clamp(&self, value: &T) -> Option<&T> {
+        self.floor(value).or_else(|| self.min())
+    }
+
+    /// Returns the (up to) `k` elements closest to `value`, ordered by their distance to it
+    /// (the difference of the larger and the smaller one). Of two equally distant elements
+    /// the smaller one comes first.
+    ///
+    /// The elements below and above `value` are walked outward simultaneously, starting from
+    /// its floor and ceil, so only the returned elements are visited after the first descent.
+    ///
+    /// # Complexity:
+    /// *O*(log n + k) - guaranteed due to AVL balancing.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn nearest_k(&self, value: &T, k: usize) -> Vec<&T>
+    where
+        T: Sub<Output = T>,
+    {
+        let distance = |other: &T| {
+            if other > value {
+                other.clone() - value.clone()
+            } else {
+                value.clone() - other.clone()
+            }
+        };
+
+        // The nodes still to be visited on each side, the nearest to `value` on top.
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+        let mut cursor = &self.root;
+        while let Some(node) = cursor {
+            if &node.value < value {
+                below.push(node);
+                cursor = &node.right;
+            } else {
+                above.push(node);
+                cursor = &node.left;
+            }
+        }
+
+        let mut result = Vec::new();
+        while result.len() < k {
+            let take_below = match (below.last(), above.last()) {
+                (Some(b), Some(a)) => distance(&b.value) <= distance(&a.value),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+
+            if take_below {
+                let node = below.pop().unwrap();
+                result.push(&node.value);
+
+                let mut cursor = &node.left;
+                while let Some(next) = cursor {
+                    below.push(next);
+                    cursor = &next.right;
+                }
+            } else {
+                let node = above.pop().unwrap();
+                result.push(&node.value);
+
+                let mut cursor = &node.right;
+                while let Some(next) = cursor {
+                    above.push(next);
+                    cursor = &next.left;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Performs a tree traversal and returns all pairs of connections between nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn find_connections(&self) -> Vec<(&T, &T)> {
+        let mut result = Vec::new();
+        let mut queue = std::collections::VecDeque::new();
+
+        if let Some(root) = &self.root {
+            queue.push_back(root);
+        }
+
+        while let Some(node) = queue.pop_front() {
+            if let Some(left) = &node.left {
+                queue.push_back(left);
+                result.push((&node.value, &left.value));
+            }
+            if let Some(right) = &node.right {
+                queue.push_back(right);
+                result.push((&node.value, &right.value));
+            }
+        }
+
+        result
+    }
+
+    /// Lazily yields all pairs of connections between nodes (parent, child), in the same order
+    /// as `find_connections` (level order, the left child before the right one).
+    ///
+    /// Only the nodes of at most two levels are buffered, so the whole edge list
+    /// is never allocated at once.
+    ///
+    /// # Complexity:
+    /// *O*(n) for the full iteration, amortized *O*(1) per edge.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T)> {
+        let mut queue: VecDeque<(&T, &AVLNode<T>)> = VecDeque::new();
+        if let Some(root) = &self.root {
+            for child in [&root.left, &root.right].into_iter().flatten() {
+                queue.push_back((&root.value, child));
+            }
+        }
+
+        std::iter::from_fn(move || {
+            let (parent, node) = queue.pop_front()?;
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back((&node.value, child));
+            }
+            Some((parent, &node.value))
+        })
+    }
+}
+
+impl<T: PartialOrd + Clone> Default for AVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two trees are equal if they hold the same elements, regardless of their shape
+/// (and so of the order in which the elements were inserted).
+impl<T: PartialOrd + Clone> PartialEq for AVLTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.in_order() == other.in_order()
+    }
+}
+
+impl<T: PartialOrd + Clone + Eq> Eq for AVLTree<T> {}
+
+#[cfg(feature = "rayon")]
+impl<T: PartialOrd + Clone + Send> AVLTree<T> {
+    /// Builds the same tree as `from_unsorted`, sorting the values in parallel.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) work, dominated by the parallel sort.
+    pub fn par_from_unsorted(iter: impl IntoParallelIterator<Item = T>) -> Self {
+        let mut values: Vec<T> = iter
+            .into_par_iter()
+            .filter(|value| value.partial_cmp(value).is_some())
+            .collect();
+        values.par_sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        values.dedup_by(|a, b| (*a).partial_cmp(b) == Some(Ordering::Equal));
+
+        let mut tree = Self::new();
+        tree.rebuild_from_sorted(values);
+        tree
+    }
+}
+
+/// Collects a parallel iterator with `par_from_unsorted`.
+#[cfg(feature = "rayon")]
+impl<T: PartialOrd + Clone + Send> FromParallelIterator<T> for AVLTree<T> {
+    fn from_par_iter<I: IntoParallelIterator<Item = T>>(par_iter: I) -> Self {
+        Self::par_from_unsorted(par_iter)
+    }
+}
+
+/// Builds the tree out of `values` inserted in order, failing on the first value that is
+/// already present instead of silently skipping it.
+impl<T: PartialOrd + Clone> TryFrom<Vec<T>> for AVLTree<T> {
+    type Error = DuplicateError<T>;
+
+    fn try_from(values: Vec<T>) -> Result<Self, Self::Error> {
+        let mut tree = Self::new();
+
+        for value in values {
+            if tree.contains(&value) {
+                return Err(DuplicateError { value });
+            }
+            tree.insert(value);
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<T: PartialOrd + Clone + LeBytes> AVLTree<T> {
+    /// Serializes the tree as the number of its elements (a little-endian `u64`) followed by
+    /// the little-endian bytes of the elements in ascending order.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytes::encode(self.in_order().into_iter())
+    }
+
+    /// Deserializes a tree serialized with `to_bytes`, rebuilding it balanced.
+    ///
+    /// Fails if the length prefix is missing or does not match the data, or if the elements
+    /// are not strictly ascending.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is built bottom-up from the sorted sequence.
+    ///
+    /// The logic is the same as in `BST`.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DecodeError> {
+        let values = bytes::decode_ascending(data, &Order::Natural)?;
+
+        let mut tree = Self::new();
+        tree.rebuild_from_sorted(values);
+        Ok(tree)
+    }
+}
+
+/// Formats the tree as a set of its elements in ascending order, e.g. `{1, 2, 3}`.
+///
+/// The alternate form (`{:#}`) draws the tree sideways instead, for debugging: one node per
+/// line with the root at the left margin, every level indented by four more spaces and the
+/// right subtree above its parent, or `<empty>` for an empty tree.
+///
+/// # Example:
+///
+/// A tree of `2, 1, 3` is formatted by `{}` and `{:#}` like this:
+/// ```text
+/// {1, 2, 3}
+///
+///     3
+/// 2
+///     1
+/// ```
+impl<T: PartialOrd + Clone + fmt::Display> fmt::Display for AVLTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_sideways(f);
+        }
+
+        write!(f, "{{")?;
+        for (i, value) in self.in_order().into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value}")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<T: PartialOrd + Clone + fmt::Display> AVLTree<T> {
+    /// Draws the tree sideways (the alternate form of `Display`).
+    fn fmt_sideways(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.root.is_none() {
+            return write!(f, "<empty>");
+        }
+
+        // A reverse in-order traversal puts the right subtree above its parent.
+        let mut stack = Vec::new();
+        let mut current = (&self.root, 0);
+        let mut separator = "";
+
+        loop {
+            while let (Some(node), depth) = current {
+                stack.push((node, depth));
+                current = (&node.right, depth + 1);
+            }
+
+            let Some((node, depth)) = stack.pop() else {
+                break;
+            };
+            let indent = 4 * depth;
+            write!(f, "{separator}{:indent$}{}", "", node.value)?;
+            separator = "\n";
+            current = (&node.left, depth + 1);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+
+    #[test]
+    fn is_and_isnt_empty_tree() {
+        let avl_1 = AVLTree::<i32>::new();
+        assert!(avl_1.is_empty());
+
+        let mut avl_2 = AVLTree::<i32>::new();
+        avl_2.insert(42);
+        assert!(!avl_2.is_empty());
+    }
+
+    #[test]
+    fn contains_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert!(!avl.contains(&42));
+    }
+
+    #[test]
+    fn contains_in_single_node_tree() {
+        let mut avl = AVLTree::new();
+        avl.insert(1);
+
+        assert!(avl.contains(&1));
+    }
+
+    #[test]
+    fn contains_basic() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            avl_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            avl_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            avl_diff_heights_two.insert(value);
+        }
+
+        for value in &values_1 {
+            assert!(avl_diff_heights_null.contains(&value));
+        }
+        for value in &values_2 {
+            assert!(avl_diff_heights_one.contains(&value));
+        }
+        for value in &values_3 {
+            assert!(avl_diff_heights_two.contains(&value));
+        }
+    }
+
+    #[test]
+    fn remove_from_empty_tree() {
+        let mut avl = AVLTree::<i32>::new();
+
+        avl.remove(&42);
+
+        assert!(!avl.contains(&42));
+        assert_eq!(avl.min(), None);
+        assert_eq!(avl.max(), None);
+    }
+
+    #[test]
+    fn remove_from_single_node_tree_check_min_max_updating() {
+        let mut avl = AVLTree::new();
+        assert!(avl.max() == avl.min() && avl.max().is_none());
+
+        avl.insert(1);
+        assert!(avl.max() == avl.min() && avl.max() == Some(&1));
+        assert!(avl.contains(&1));
+
+        avl.remove(&1);
+        assert!(avl.max() == avl.min() && avl.max().is_none());
+        assert!(!avl.contains(&1));
+    }
+
+    #[test]
+    fn remove_basic_check_min_max_updating() {
+        let mut avl1 = AVLTree::new();
+        let mut avl2 = AVLTree::new();
+        let mut avl3 = AVLTree::new();
+
+        let values1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values2 = vec![4, 2, 6, 1, 3, 5];
+        let values3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+
+        for &v in &values1 {
+            avl1.insert(v);
+        }
+        for &v in &values2 {
+            avl2.insert(v);
+        }
+        for &v in &values3 {
+            avl3.insert(v);
+        }
+
+        let mut remaining1 = values1.clone();
+        let mut remaining2 = values2.clone();
+        let mut remaining3 = values3.clone();
+
+        for &v in &values1 {
+            avl1.remove(&v);
+            remaining1.retain(|&x| x != v);
+
+            assert!(!avl1.contains(&v));
+            assert_eq!(avl1.min(), remaining1.iter().min());
+            assert_eq!(avl1.max(), remaining1.iter().max());
+        }
+
+        for &v in &values2 {
+            avl2.remove(&v);
+            remaining2.retain(|&x| x != v);
+
+            assert!(!avl2.contains(&v));
+            assert_eq!(avl2.min(), remaining2.iter().min());
+            assert_eq!(avl2.max(), remaining2.iter().max());
+        }
+
+        for &v in &values3 {
+            avl3.remove(&v);
+            remaining3.retain(|&x| x != v);
+
+            assert!(!avl3.contains(&v));
+            assert_eq!(avl3.min(), remaining3.iter().min());
+            assert_eq!(avl3.max(), remaining3.iter().max());
+        }
+    }
+
+    #[test]
+    fn min_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.min(), None);
+    }
+
+    #[test]
+    fn min_basic_check_updating() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+
+        let mut current_min_1 = None;
+        let mut current_min_2 = None;
+        let mut current_min_3 = None;
+
+        for value in &values_1 {
+            avl_diff_heights_null.insert(*value);
+            current_min_1 = Some(*value)
+                .filter(|&x| current_min_1.is_none_or(|min| x < min))
+                .or(current_min_1);
+            assert_eq!(avl_diff_heights_null.min(), current_min_1.as_ref());
+        }
+
+        for value in &values_2 {
+            avl_diff_heights_one.insert(*value);
+            current_min_2 = Some(*value)
+                .filter(|&x| current_min_2.is_none_or(|min| x < min))
+                .or(current_min_2);
+            assert_eq!(avl_diff_heights_one.min(), current_min_2.as_ref());
+        }
+
+        for value in &values_3 {
+            avl_diff_heights_two.insert(*value);
+            current_min_3 = Some(*value)
+                .filter(|&x| current_min_3.is_none_or(|min| x < min))
+                .or(current_min_3);
+            assert_eq!(avl_diff_heights_two.min(), current_min_3.as_ref());
+        }
+    }
+
+    #[test]
+    fn max_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.max(), None);
+    }
+
+    #[test]
+    fn max_basic_check_updating() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+
+        let mut current_max_1 = None;
+        let mut current_max_2 = None;
+        let mut current_max_3 = None;
+
+        for value in &values_1 {
+            avl_diff_heights_null.insert(*value);
+            current_max_1 = Some(*value)
+                .filter(|&x| current_max_1.is_none_or(|max| x > max))
+                .or(current_max_1);
+            assert_eq!(avl_diff_heights_null.max(), current_max_1.as_ref());
+        }
+
+        for value in &values_2 {
+            avl_diff_heights_one.insert(*value);
+            current_max_2 = Some(*value)
+                .filter(|&x| current_max_2.is_none_or(|max| x > max))
+                .or(current_max_2);
+            assert_eq!(avl_diff_heights_one.max(), current_max_2.as_ref());
+        }
+
+        for value in &values_3 {
+            avl_diff_heights_two.insert(*value);
+            current_max_3 = Some(*value)
+                .filter(|&x| current_max_3.is_none_or(|max| x > max))
+                .or(current_max_3);
+            assert_eq!(avl_diff_heights_two.max(), current_max_3.as_ref());
+        }
+    }
+
+    #[test]
+    fn max_min_are_similar_for_single_element_tree() {
+        let mut avl = AVLTree::new();
+        avl.insert(1);
+
+        assert!(avl.min() == avl.max() && avl.min() == Some(&1));
+    }
+
+    #[test]
+    fn height_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.height(), 0);
+    }
+
+    #[test]
+    fn height_basic() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            avl_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            avl_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            avl_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(avl_diff_heights_null.height(), 2);
+        assert_eq!(avl_diff_heights_one.height(), 2);
+        assert_eq!(avl_diff_heights_two.height(), 3);
+    }
+
+    #[test]
+    fn pre_order_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.pre_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn pre_order_basic() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            avl_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            avl_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            avl_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            avl_diff_heights_null.pre_order(),
+            vec![&&5, &&3, &&2, &&4, &&7, &&6, &&8]
+        );
+
+        assert_eq!(
+            avl_diff_heights_one.pre_order(),
+            vec![&&4, &&2, &&1, &&3, &&6, &&5]
+        );
+
+        assert_eq!(
+            avl_diff_heights_two.pre_order(),
+            vec![&&8, &&4, &&2, &&1, &&6, &&7, &&12, &&10]
+        );
+    }
+
+    #[test]
+    fn in_order_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.in_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn in_order_basic() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            avl_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            avl_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            avl_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            avl_diff_heights_null.in_order(),
+            vec![&&2, &&3, &&4, &&5, &&6, &&7, &&8]
+        );
+
+        assert_eq!(
+            avl_diff_heights_one.in_order(),
+            vec![&&1, &&2, &&3, &&4, &&5, &&6]
+        );
+
+        assert_eq!(
+            avl_diff_heights_two.in_order(),
+            vec![&&1, &&2, &&4, &&6, &&7, &&8, &&10, &&12]
+        );
+    }
+
+    #[test]
+    fn post_order_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.post_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn post_order_basic() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            avl_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            avl_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            avl_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            avl_diff_heights_null.post_order(),
+            vec![&&2, &&4, &&3, &&6, &&8, &&7, &&5]
+        );
+
+        assert_eq!(
+            avl_diff_heights_one.post_order(),
+            vec![&&1, &&3, &&2, &&5, &&6, &&4]
+        );
+
+        assert_eq!(
+            avl_diff_heights_two.post_order(),
+            vec![&&1, &&2, &&7, &&6, &&4, &&10, &&12, &&8]
+        );
+    }
+
+    #[test]
+    fn level_order_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.level_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn level_order_basic() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            avl_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            avl_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            avl_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            avl_diff_heights_null.level_order(),
+            vec![&&5, &&3, &&7, &&2, &&4, &&6, &&8]
+        );
+
+        assert_eq!(
+            avl_diff_heights_one.level_order(),
+            vec![&&4, &&2, &&6, &&1, &&3, &&5]
+        );
+
+        assert_eq!(
+            avl_diff_heights_two.level_order(),
+            vec![&&8, &&4, &&12, &&2, &&6, &&10, &&1, &&7]
+        );
+    }
+
+    #[test]
+    fn number_of_elements_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.number_of_elements(), 0);
+    }
+
+    #[test]
+    fn number_of_elements_basic() {
+        let mut avl_diff_heights_null = AVLTree::new();
+        let mut avl_diff_heights_one = AVLTree::new();
+        let mut avl_diff_heights_two = AVLTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            avl_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            avl_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            avl_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(avl_diff_heights_null.number_of_elements(), values_1.len());
+        assert_eq!(avl_diff_heights_one.number_of_elements(), values_2.len());
+        assert_eq!(avl_diff_heights_two.number_of_elements(), values_3.len());
+    }
+
+    #[test]
+    fn ceil_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.ceil(&0), None);
+    }
+
+    #[test]
+    fn ceil_basic() {
+        let mut avl = AVLTree::<i32>::new();
+
+        avl.insert(1);
+        avl.insert(2);
+        avl.insert(5);
+
+        assert_eq!(avl.ceil(&6), None);
+        assert_eq!(avl.ceil(&5), Some(&5));
+        assert_eq!(avl.ceil(&4), Some(&5));
+        assert_eq!(avl.ceil(&3), Some(&5));
+        assert_eq!(avl.ceil(&2), Some(&2));
+        assert_eq!(avl.ceil(&1), Some(&1));
+        assert_eq!(avl.ceil(&0), Some(&1));
+    }
+
+    #[test]
+    fn floor_in_empty_tree() {
+        let avl = AVLTree::<i32>::new();
+
+        assert_eq!(avl.floor(&0), None);
+    }
+
+    #[test]
+    fn floor_basic() {
+        let mut avl = AVLTree::<i32>::new();
+
+        avl.insert(1);
+        avl.insert(2);
+        avl.insert(5);
+
+        assert_eq!(avl.floor(&6), Some(&5));
+        assert_eq!(avl.floor(&5), Some(&5));
+        assert_eq!(avl.floor(&4), Some(&2));
+        assert_eq!(avl.floor(&3), Some(&2));
+        assert_eq!(avl.floor(&2), Some(&2));
+        assert_eq!(avl.floor(&1), Some(&1));
+        assert_eq!(avl.floor(&0), None);
+    }
+
+    /// Ordered and compared by `key` only, so that equal elements may carry different payloads.
+    #[derive(Debug, Clone)]
+    struct Keyed {
+        key: i32,
+        payload: &'static str,
+    }
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            self.key.partial_cmp(&other.key)
+        }
+    }
+
+    fn keyed(key: i32, payload: &'static str) -> Keyed {
+        Keyed { key, payload }
+    }
+
+    #[test]
+    fn insert_or_replace_basic() {
+        let mut avl = AVLTree::new();
+
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            assert_eq!(avl.insert_or_replace(keyed(key, "old")), None);
+        }
+
+        let replaced = avl.insert_or_replace(keyed(4, "new")).unwrap();
+        assert_eq!(replaced.payload, "old");
+        assert_eq!(avl.number_of_elements(), 7);
+
+        let payloads: Vec<_> = avl.in_order().iter().map(|k| k.payload).collect();
+        assert_eq!(
+            payloads,
+            vec!["old", "old", "old", "new", "old", "old", "old"]
+        );
+    }
+
+    #[test]
+    fn insert_or_replace_updates_min_max() {
+        let mut avl = AVLTree::new();
+
+        for key in [2, 1, 3] {
+            avl.insert(keyed(key, "old"));
+        }
+
+        assert_eq!(
+            avl.insert_or_replace(keyed(1, "new")).unwrap().payload,
+            "old"
+        );
+        assert_eq!(
+            avl.insert_or_replace(keyed(3, "new")).unwrap().payload,
+            "old"
+        );
+
+        assert_eq!(avl.min().unwrap().payload, "new");
+        assert_eq!(avl.max().unwrap().payload, "new");
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn rotation_count_ascending_inserts() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.rotation_count(), 0);
+
+        let n = 1000;
+        for i in 1..=n {
+            avl.insert(i);
+        }
+
+        // Ascending inserts trigger at most one single rotation per insertion
+        // and never leave more than a logarithmic number of insertions unrotated.
+        let rotations = avl.rotation_count();
+        assert!(rotations > 0);
+        assert!(rotations < n as u64);
+        assert!(rotations >= (n - 2 * (n as f64).log2().ceil() as i32) as u64);
+
+        avl.remove(&1);
+        avl.remove(&2);
+        assert!(avl.rotation_count() >= rotations);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn rotation_count_ignores_rotations_outside_the_tree() {
+        let mut avl = AVLTree::new();
+        for i in 1..=100 {
+            avl.insert(i);
+        }
+        let rotations = avl.rotation_count();
+
+        // Rebalancing a detached right-right chain rotates on the same thread.
+        let mut middle = Box::new(AVLNode::new(2));
+        middle.right = Some(Box::new(AVLNode::new(3)));
+        middle.update_height();
+        let mut chain = Box::new(AVLNode::new(1));
+        chain.right = Some(middle);
+        chain.update_height();
+        assert_eq!(chain.rebalance().value, 2);
+
+        let mut other = AVLTree::new();
+        for i in 1..=100 {
+            other.insert(i);
+        }
+
+        avl.insert(50);
+        assert_eq!(avl.rotation_count(), rotations);
+        assert_eq!(other.rotation_count(), rotations);
+    }
+
+    #[test]
+    fn from_unsorted_matches_sequential_inserts() {
+        let values = vec![8, 3, 12, 3, 1, 5, 8, 10, 14, 7, 1, 6];
+        let bulk = AVLTree::from_unsorted(values.clone());
+
+        let mut avl = AVLTree::new();
+        for &value in &values {
+            avl.insert(value);
+        }
+
+        assert_eq!(bulk.in_order(), avl.in_order());
+        assert_eq!(bulk.min(), Some(&1));
+        assert_eq!(bulk.max(), Some(&14));
+        assert!(bulk.is_balanced());
+        assert!(bulk.is_valid_bst());
+    }
+
+    #[test]
+    fn from_unsorted_on_sorted_input() {
+        for n in [0, 1, 2, 3, 10, 100, 1000] {
+            let bulk = AVLTree::from_unsorted(0..n);
+
+            let mut avl = AVLTree::new();
+            for value in 0..n {
+                avl.insert(value);
+            }
+
+            assert_eq!(bulk.number_of_elements(), n as usize);
+            assert!(bulk.is_balanced());
+            assert!(bulk.height() <= avl.height());
+        }
+    }
+
+    #[test]
+    fn from_unsorted_deduplicates_by_ordering() {
+        // Equal by ordering (same key) but not by `==` (different labels).
+        #[derive(Debug, Clone, PartialEq)]
+        struct Labeled(i32, &'static str);
+
+        impl PartialOrd for Labeled {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        let bulk = AVLTree::from_unsorted(vec![Labeled(1, "a"), Labeled(1, "b"), Labeled(2, "c")]);
+
+        assert_eq!(bulk.number_of_elements(), 2);
+        assert_eq!(bulk.in_order(), vec![&Labeled(1, "a"), &Labeled(2, "c")]);
+        assert!(bulk.is_balanced());
+        assert!(bulk.is_valid_bst());
+    }
+
+    #[test]
+    fn retain_range_keeps_middle_window() {
+        let mut avl = AVLTree::new();
+        for i in 0..100 {
+            avl.insert((i * 37) % 100);
+        }
+
+        avl.retain_range(&30, &60);
+
+        let expected: Vec<i32> = (30..=60).collect();
+        assert_eq!(avl.in_order(), expected.iter().collect::<Vec<_>>());
+        assert_eq!(avl.min(), Some(&30));
+        assert_eq!(avl.max(), Some(&60));
+        assert!(avl.is_balanced());
+        assert!(avl.is_valid_bst());
+
+        avl.retain_range(&-10, &35);
+        assert_eq!(avl.in_order(), vec![&30, &31, &32, &33, &34, &35]);
+
+        avl.retain_range(&5, &1);
+        assert!(avl.is_empty());
+        assert_eq!(avl.min(), None);
+        assert_eq!(avl.max(), None);
+    }
+
+    #[test]
+    fn display_as_set() {
+        let mut avl = AVLTree::new();
+        assert_eq!(format!("{avl}"), "{}");
+
+        avl.insert(2);
+        assert_eq!(format!("{avl}"), "{2}");
+
+        avl.insert(3);
+        avl.insert(1);
+        assert_eq!(format!("{avl}"), "{1, 2, 3}");
+    }
+
+    #[test]
+    fn traversals_cloned() {
+        let mut avl = AVLTree::new();
+        for value in [4, 2, 5, 1, 3, 6] {
+            avl.insert(value);
+        }
+
+        let mut sorted = vec![4, 2, 5, 1, 3, 6];
+        sorted.sort();
+        assert_eq!(avl.in_order_cloned(), sorted);
+
+        let owned = |refs: Vec<&i32>| refs.into_iter().copied().collect::<Vec<_>>();
+        assert_eq!(avl.pre_order_cloned(), owned(avl.pre_order()));
+        assert_eq!(avl.post_order_cloned(), owned(avl.post_order()));
+        assert_eq!(avl.level_order_cloned(), owned(avl.level_order()));
+        assert!(AVLTree::<i32>::new().in_order_cloned().is_empty());
+    }
+
+    #[test]
+    fn empty_and_with_value() {
+        let avl = AVLTree::<i32>::empty();
+        assert!(avl.is_empty());
+        assert_eq!(avl.min(), None);
+
+        let avl = AVLTree::with_value(5);
+        assert_eq!(avl.min(), Some(&5));
+        assert_eq!(avl.max(), Some(&5));
+        assert_eq!(avl.number_of_elements(), 1);
+        assert!(avl.contains(&5));
+    }
+
+    #[test]
+    fn find_returns_rank_of_present_values() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.find(&1), None);
+
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            avl.insert(value);
+        }
+
+        assert_eq!(avl.find(&20), Some(0));
+        assert_eq!(avl.find(&50), Some(3));
+        assert_eq!(avl.find(&80), Some(6));
+        assert_eq!(avl.find(&45), None);
+
+        avl.remove(&30);
+        assert_eq!(avl.find(&40), Some(1));
+        assert_eq!(avl.find(&80), Some(5));
+    }
+
+    #[test]
+    fn level_order_with_nulls() {
+        let mut avl = AVLTree::new();
+        assert!(avl.level_order_with_nulls().is_empty());
+
+        for value in [4, 2, 5, 1, 3, 6] {
+            avl.insert(value);
+        }
+
+        assert_eq!(
+            avl.level_order_with_nulls(),
+            vec![
+                Some(&4),
+                Some(&2),
+                Some(&5),
+                Some(&1),
+                Some(&3),
+                None,
+                Some(&6)
+            ]
+        );
+    }
+
+    #[test]
+    fn balance_factor_histogram() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.balance_factor_histogram(), [0, 0, 0]);
+
+        for value in 1..=7 {
+            avl.insert(value);
+        }
+        assert_eq!(avl.balance_factor_histogram(), [0, 7, 0]);
+
+        for value in 8..=100 {
+            avl.insert(value);
+        }
+        let histogram = avl.balance_factor_histogram();
+        assert_eq!(histogram.iter().sum::<usize>(), avl.number_of_elements());
+        assert!(histogram[0] > 0);
+    }
+
+    #[test]
+    fn walk_matches_traversals() {
+        let mut avl = AVLTree::new();
+        for value in [4, 2, 5, 1, 3, 6, 8, 7] {
+            avl.insert(value);
+        }
+
+        let walked = |order| {
+            let mut values = Vec::new();
+            avl.walk(order, |value| values.push(*value));
+            values
+        };
+        let owned = |refs: Vec<&i32>| refs.into_iter().copied().collect::<Vec<_>>();
+
+        assert_eq!(walked(TraversalOrder::PreOrder), owned(avl.pre_order()));
+        assert_eq!(walked(TraversalOrder::InOrder), owned(avl.in_order()));
+        assert_eq!(walked(TraversalOrder::PostOrder), owned(avl.post_order()));
+        assert_eq!(walked(TraversalOrder::LevelOrder), owned(avl.level_order()));
+
+        let mut calls = 0;
+        AVLTree::<i32>::new().walk(TraversalOrder::InOrder, |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn try_from_vec() {
+        let avl = AVLTree::try_from(vec![3, 1, 2]).unwrap();
+        assert_eq!(avl.in_order(), vec![&1, &2, &3]);
+        assert_eq!(avl.min(), Some(&1));
+        assert_eq!(avl.max(), Some(&3));
+
+        let empty = AVLTree::<i32>::try_from(vec![]).unwrap();
+        assert!(empty.is_empty());
+
+        let err = AVLTree::try_from(vec![3, 1, 2, 1, 3]).unwrap_err();
+        assert_eq!(err, DuplicateError { value: 1 });
+        assert_eq!(err.to_string(), "duplicate value: 1");
+    }
+
+    #[test]
+    fn stored_equal_to_returns_the_stored_representative() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.stored_equal_to(&keyed(1, "query")), None);
+
+        avl.insert(keyed(1, "first"));
+        avl.insert(keyed(2, "second"));
+        avl.insert(keyed(1, "ignored"));
+
+        let stored = avl.stored_equal_to(&keyed(1, "query")).unwrap();
+        assert_eq!(stored.payload, "first");
+        assert_eq!(avl.stored_equal_to(&keyed(3, "query")), None);
+
+        avl.insert_or_replace(keyed(1, "replaced"));
+        assert_eq!(
+            avl.stored_equal_to(&keyed(1, "query")).unwrap().payload,
+            "replaced"
+        );
+    }
+
+    #[test]
+    fn has_any_in_range() {
+        let mut avl = AVLTree::new();
+        assert!(!avl.has_any_in_range(&0, &100));
+
+        for value in [10, 5, 20, 1, 7, 30, 25] {
+            avl.insert(value);
+        }
+
+        assert!(!avl.has_any_in_range(&11, &19));
+        assert!(!avl.has_any_in_range(&31, &40));
+        assert!(!avl.has_any_in_range(&-5, &0));
+        assert!(avl.has_any_in_range(&21, &26));
+        assert!(avl.has_any_in_range(&7, &7));
+        assert!(avl.has_any_in_range(&-100, &100));
+        assert!(!avl.has_any_in_range(&26, &21));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_from_unsorted_matches_sequential() {
+        let values: Vec<i32> = (0..10_000).map(|i| (i * 7919) % 5000 - 2500).collect();
+
+        let sequential = AVLTree::from_unsorted(values.clone());
+        let parallel = AVLTree::par_from_unsorted(values.clone());
+        let collected: AVLTree<i32> = values.into_par_iter().collect();
+
+        for avl in [&parallel, &collected] {
+            assert!(avl.is_balanced());
+            assert!(avl.is_valid_bst());
+            assert_eq!(avl.in_order(), sequential.in_order());
+            assert_eq!(avl.level_order(), sequential.level_order());
+            assert_eq!(avl.min(), sequential.min());
+            assert_eq!(avl.max(), sequential.max());
+        }
+
+        assert!(AVLTree::<f64>::par_from_unsorted(vec![f64::NAN]).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_from_unsorted_deduplicates_by_ordering() {
+        // Equal by ordering (same key) but not by `==` (different labels).
+        #[derive(Debug, Clone, PartialEq)]
+        struct Labeled(i32, &'static str);
+
+        impl PartialOrd for Labeled {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        let values = vec![Labeled(1, "a"), Labeled(1, "b"), Labeled(2, "c")];
+        let bulk = AVLTree::par_from_unsorted(values);
+
+        assert_eq!(bulk.number_of_elements(), 2);
+        assert_eq!(bulk.in_order(), vec![&Labeled(1, "a"), &Labeled(2, "c")]);
+        assert!(bulk.is_balanced());
+        assert!(bulk.is_valid_bst());
+    }
+
+    #[test]
+    fn same_elements_as() {
+        let mut avl = AVLTree::new();
+        assert!(avl.same_elements_as(&[]));
+
+        for value in [5, 3, 8, 1, 4] {
+            avl.insert(value);
+        }
+
+        assert!(avl.same_elements_as(&[1, 3, 4, 5, 8]));
+        assert!(!avl.same_elements_as(&[5, 3, 8, 1, 4]));
+        assert!(!avl.same_elements_as(&[1, 3, 4, 5]));
+        assert!(!avl.same_elements_as(&[1, 3, 4, 5, 9]));
+    }
+
+    #[test]
+    fn reverse_ordering() {
+        let mut avl = AVLTree::new();
+        for value in [5, 3, 8, 1, 4, 9] {
+            avl.insert(Reverse(value));
+        }
+
+        let descending: Vec<i32> = avl.in_order().into_iter().map(|value| value.0).collect();
+        assert_eq!(descending, vec![9, 8, 5, 4, 3, 1]);
+        assert_eq!(avl.min(), Some(&Reverse(9)));
+        assert_eq!(avl.max(), Some(&Reverse(1)));
+        assert_eq!(avl.ceil(&Reverse(6)), Some(&Reverse(5)));
+        assert_eq!(avl.floor(&Reverse(6)), Some(&Reverse(8)));
+
+        avl.remove(&Reverse(9));
+        avl.remove(&Reverse(1));
+        assert_eq!(avl.min(), Some(&Reverse(8)));
+        assert_eq!(avl.max(), Some(&Reverse(3)));
+        assert_eq!(avl.find(&Reverse(8)), Some(0));
+    }
+
+    #[test]
+    fn is_valid_bst_multiset_allows_equal_values_on_the_right() {
+        let mut avl = AVLTree::try_from(vec![1, 2, 3]).unwrap();
+        assert!(avl.is_valid_bst());
+        assert!(avl.is_valid_bst_multiset());
+
+        let mut root = Box::new(AVLNode::new(2));
+        root.left = Some(Box::new(AVLNode::new(1)));
+        root.right = Some(Box::new(AVLNode::new(2)));
+        avl.root = Some(root);
+        assert!(!avl.is_valid_bst());
+        assert!(avl.is_valid_bst_multiset());
+
+        let mut root = Box::new(AVLNode::new(2));
+        root.left = Some(Box::new(AVLNode::new(2)));
+        root.right = Some(Box::new(AVLNode::new(3)));
+        avl.root = Some(root);
+        assert!(!avl.is_valid_bst());
+        assert!(!avl.is_valid_bst_multiset());
+
+        let mut root = Box::new(AVLNode::new(2));
+        let mut right = Box::new(AVLNode::new(3));
+        right.left = Some(Box::new(AVLNode::new(1)));
+        root.right = Some(right);
+        avl.root = Some(root);
+        assert!(!avl.is_valid_bst_multiset());
+    }
+
+    #[test]
+    fn split_off_partitions_at_key() {
+        let values = [50, 30, 70, 20, 40, 60, 80, 10, 35, 65];
+
+        for key in [0, 10, 35, 36, 50, 80, 81] {
+            let mut avl = AVLTree::new();
+            for value in values {
+                avl.insert(value);
+            }
+
+            let other = avl.split_off(&key);
+
+            let mut sorted = values.to_vec();
+            sorted.sort();
+            let (less, rest): (Vec<i32>, Vec<i32>) = sorted.iter().partition(|&&v| v < key);
+            assert_eq!(avl.in_order_cloned(), less);
+            assert_eq!(other.in_order_cloned(), rest);
+
+            assert_eq!(avl.min(), less.first());
+            assert_eq!(avl.max(), less.last());
+            assert_eq!(other.min(), rest.first());
+            assert_eq!(other.max(), rest.last());
+            assert!(avl.is_balanced() && avl.is_valid_bst());
+            assert!(other.is_balanced() && other.is_valid_bst());
+            assert_eq!(
+                avl.find(&less.last().copied().unwrap_or(key)),
+                less.len().checked_sub(1)
+            );
+            assert_eq!(
+                other.find(&rest.last().copied().unwrap_or(key)),
+                rest.len().checked_sub(1)
+            );
+        }
+    }
+
+    #[test]
+    fn append_overlapping_trees() {
+        let mut avl = AVLTree::new();
+        for value in [5, 1, 9, 3, 7] {
+            avl.insert(value);
+        }
+        let mut other = AVLTree::new();
+        for value in [6, 3, 12, 0, 9] {
+            other.insert(value);
+        }
+
+        avl.append(&mut other);
+
+        assert_eq!(avl.in_order_cloned(), vec![0, 1, 3, 5, 6, 7, 9, 12]);
+        assert_eq!(avl.min(), Some(&0));
+        assert_eq!(avl.max(), Some(&12));
+        assert_eq!(avl.find(&12), Some(7));
+        assert!(avl.is_balanced() && avl.is_valid_bst());
+        assert!(other.is_empty());
+        assert_eq!(other.min(), None);
+        assert_eq!(other.max(), None);
+
+        avl.append(&mut other);
+        assert_eq!(avl.number_of_elements(), 8);
+    }
+
+    #[test]
+    fn append_keeps_own_copy_of_equal_values() {
+        let mut avl = AVLTree::new();
+        avl.insert(keyed(1, "self"));
+        let mut other = AVLTree::new();
+        other.insert(keyed(1, "other"));
+        other.insert(keyed(2, "other"));
+
+        avl.append(&mut other);
+
+        assert_eq!(
+            avl.stored_equal_to(&keyed(1, "")).map(|k| k.payload),
+            Some("self")
+        );
+        assert_eq!(avl.number_of_elements(), 2);
+    }
+
+    #[test]
+    fn drain_range_moves_middle_window() {
+        let mut avl = AVLTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80, 10, 35, 65, 75] {
+            avl.insert(value);
+        }
+
+        let drained = avl.drain_range(&33, &65);
+
+        assert_eq!(drained.in_order_cloned(), vec![35, 40, 50, 60, 65]);
+        assert_eq!(drained.min(), Some(&35));
+        assert_eq!(drained.max(), Some(&65));
+        assert_eq!(drained.find(&65), Some(4));
+        assert_eq!(avl.in_order_cloned(), vec![10, 20, 30, 70, 75, 80]);
+        assert_eq!(avl.min(), Some(&10));
+        assert_eq!(avl.max(), Some(&80));
+        assert_eq!(avl.find(&80), Some(5));
+
+        assert!(avl.is_balanced() && drained.is_balanced());
+        assert!(avl.drain_range(&31, &69).is_empty());
+        assert!(avl.drain_range(&80, &10).is_empty());
+        assert_eq!(avl.number_of_elements(), 6);
+
+        let drained = avl.drain_range(&0, &100);
+        assert_eq!(drained.in_order_cloned(), vec![10, 20, 30, 70, 75, 80]);
+        assert!(avl.is_empty());
+        assert_eq!(avl.min(), None);
+    }
+
+    #[test]
+    fn approx_memory_bytes_scales_linearly() {
+        let mut avl = AVLTree::new();
+        let empty = avl.approx_memory_bytes();
+        assert_eq!(empty, size_of::<AVLTree<i32>>());
+
+        for value in 0..100 {
+            avl.insert(value);
+        }
+        let hundred = avl.approx_memory_bytes();
+        for value in 100..200 {
+            avl.insert(value);
+        }
+        let two_hundred = avl.approx_memory_bytes();
+
+        assert!(hundred > empty);
+        assert_eq!(two_hundred - hundred, hundred - empty);
+        assert_eq!(hundred - empty, 100 * size_of::<AVLNode<i32>>());
+    }
+
+    #[test]
+    fn from_insertion_log_reproduces_the_tree() {
+        let log = [
+            Op::Insert(5),
+            Op::Insert(3),
+            Op::Insert(8),
+            Op::Insert(1),
+            Op::Remove(3),
+            Op::Insert(9),
+            Op::Remove(42),
+            Op::Insert(4),
+            Op::Insert(5),
+            Op::Remove(5),
+        ];
+
+        let mut avl = AVLTree::new();
+        avl.insert(5);
+        avl.insert(3);
+        avl.insert(8);
+        avl.insert(1);
+        avl.remove(&3);
+        avl.insert(9);
+        avl.remove(&42);
+        avl.insert(4);
+        avl.insert(5);
+        avl.remove(&5);
+
+        let replayed = AVLTree::from_insertion_log(&log);
+        assert_eq!(
+            replayed.level_order_with_nulls(),
+            avl.level_order_with_nulls()
+        );
+        assert_eq!(replayed.in_order(), vec![&1, &4, &8, &9]);
+        assert_eq!(replayed.min(), Some(&1));
+        assert_eq!(replayed.max(), Some(&9));
+
+        assert!(AVLTree::<i32>::from_insertion_log(&[]).is_empty());
+    }
+
+    #[test]
+    fn root_value() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.root_value(), None);
 
-            if &node.value > value {
-                cursor = &node.left;
-            } else {
-                result = Some(&node.value);
-                cursor = &node.right;
-            }
+        for value in 1..=7 {
+            avl.insert(value);
         }
+        assert_eq!(avl.root_value(), Some(&4));
+        assert_eq!(avl.root_value(), avl.level_order().first().copied());
 
-        result
+        avl.remove(&4);
+        assert_eq!(avl.root_value(), Some(&5));
     }
 
-    /// Performs a tree traversal and returns all pairs of connections between nodes.
-    ///
-    /// The logic is the same as in `BST`.
-    pub fn find_connections(&self) -> Vec<(&T, &T)> {
-        let mut result = Vec::new();
-        let mut queue = std::collections::VecDeque::new();
+    #[test]
+    fn edges_match_find_connections() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.edges().next(), None);
 
-        if let Some(root) = &self.root {
-            queue.push_back(root);
-        }
+        avl.insert(4);
+        assert_eq!(avl.edges().next(), None);
 
-        while let Some(node) = queue.pop_front() {
-            if let Some(left) = &node.left {
-                queue.push_back(left);
-                result.push((&node.value, &left.value));
-            }
-            if let Some(right) = &node.right {
-                queue.push_back(right);
-                result.push((&node.value, &right.value));
-            }
+        for value in [2, 6, 1, 3, 5, 7, 8, 0] {
+            avl.insert(value);
+            assert_eq!(avl.edges().collect::<Vec<_>>(), avl.find_connections());
         }
-
-        result
+        assert_eq!(avl.edges().count(), avl.number_of_elements() - 1);
     }
-}
 
-impl<T: PartialOrd + Clone> Default for AVLTree<T> {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+    #[test]
+    fn clamp_prefers_floor_then_min() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.clamp(&5), None);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        for value in [20, 10, 30] {
+            avl.insert(value);
+        }
+
+        assert_eq!(avl.clamp(&5), Some(&10));
+        assert_eq!(avl.clamp(&10), Some(&10));
+        assert_eq!(avl.clamp(&25), Some(&20));
+        assert_eq!(avl.clamp(&20), Some(&20));
+        assert_eq!(avl.clamp(&30), Some(&30));
+        assert_eq!(avl.clamp(&99), Some(&30));
+    }
 
     #[test]
-    fn is_and_isnt_empty_tree() {
-        let avl_1 = AVLTree::<i32>::new();
-        assert!(avl_1.is_empty());
+    fn bytes_round_trip() {
+        let mut avl = AVLTree::new();
+        for value in 0..100 {
+            avl.insert(value * 3);
+        }
 
-        let mut avl_2 = AVLTree::<i32>::new();
-        avl_2.insert(42);
-        assert!(!avl_2.is_empty());
+        let decoded = AVLTree::<i64>::from_bytes(&avl.to_bytes()).unwrap();
+        assert_eq!(decoded.in_order(), avl.in_order());
+        assert_eq!(decoded.min(), Some(&0));
+        assert_eq!(decoded.max(), Some(&297));
+        assert!(decoded.is_balanced() && decoded.is_valid_bst());
+        assert!(matches!(
+            AVLTree::<i64>::from_bytes(&[0; 4]),
+            Err(DecodeError::MissingLength)
+        ));
     }
 
     #[test]
-    fn contains_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn into_level_order_matches_level_order() {
+        assert!(AVLTree::<String>::new().into_level_order().is_empty());
 
-        assert!(!avl.contains(&42));
+        let mut avl = AVLTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80, 10, 65] {
+            avl.insert(value.to_string());
+        }
+
+        let expected = avl.level_order_cloned();
+        assert_eq!(avl.into_level_order(), expected);
     }
 
     #[test]
-    fn contains_in_single_node_tree() {
+    fn all_comparable_detects_nan() {
         let mut avl = AVLTree::new();
-        avl.insert(1);
+        assert!(avl.all_comparable());
 
-        assert!(avl.contains(&1));
+        for value in [2.0, 1.0, 3.0, f64::INFINITY] {
+            avl.insert(value);
+        }
+        assert!(avl.all_comparable());
+
+        avl.root.as_mut().unwrap().value = f64::NAN;
+        assert!(!avl.all_comparable());
+
+        let mut single = AVLTree::new();
+        single.root = Some(Box::new(AVLNode::new(f64::NAN)));
+        assert!(!single.all_comparable());
     }
 
     #[test]
-    fn contains_basic() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
+    fn merge_diff_classifies_elements() {
+        let mut avl = AVLTree::new();
+        let mut other = AVLTree::new();
+        assert_eq!(avl.merge_diff(&other).next(), None);
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            avl_diff_heights_null.insert(value);
+        for value in [5, 1, 3, 7, 9] {
+            avl.insert(value);
         }
-        for value in &values_2 {
-            avl_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            avl_diff_heights_two.insert(value);
+        for value in [4, 3, 9, 10, 0] {
+            other.insert(value);
         }
 
-        for value in &values_1 {
-            assert!(avl_diff_heights_null.contains(&value));
-        }
-        for value in &values_2 {
-            assert!(avl_diff_heights_one.contains(&value));
-        }
-        for value in &values_3 {
-            assert!(avl_diff_heights_two.contains(&value));
-        }
+        assert_eq!(
+            avl.merge_diff(&other).collect::<Vec<_>>(),
+            vec![
+                DiffItem::Right(&0),
+                DiffItem::Left(&1),
+                DiffItem::Both(&3, &3),
+                DiffItem::Right(&4),
+                DiffItem::Left(&5),
+                DiffItem::Left(&7),
+                DiffItem::Both(&9, &9),
+                DiffItem::Right(&10),
+            ]
+        );
+        assert!(
+            avl.merge_diff(&avl)
+                .all(|item| matches!(item, DiffItem::Both(a, b) if a == b))
+        );
+        assert_eq!(
+            avl.merge_diff(&AVLTree::new()).count(),
+            avl.number_of_elements()
+        );
     }
 
     #[test]
-    fn remove_from_empty_tree() {
-        let mut avl = AVLTree::<i32>::new();
-
-        avl.remove(&42);
+    fn with_capacity_behaves_like_new() {
+        let values: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+
+        let mut avl = AVLTree::with_capacity(1000);
+        assert!(avl.is_empty());
+        let mut expected = AVLTree::new();
+        for &value in &values {
+            avl.insert(value);
+            expected.insert(value);
+        }
 
-        assert!(!avl.contains(&42));
-        assert_eq!(avl.min(), None);
-        assert_eq!(avl.max(), None);
+        assert_eq!(
+            avl.level_order_with_nulls(),
+            expected.level_order_with_nulls()
+        );
+        assert_eq!(avl.min(), expected.min());
+        assert_eq!(avl.max(), expected.max());
     }
 
     #[test]
-    fn remove_from_single_node_tree_check_min_max_updating() {
+    fn filter_map_halves_evens_and_drops_odds() {
         let mut avl = AVLTree::new();
-        assert!(avl.max() == avl.min() && avl.max().is_none());
+        for value in [8, 3, 12, 1, 6, 10, 14, 7] {
+            avl.insert(value);
+        }
 
-        avl.insert(1);
-        assert!(avl.max() == avl.min() && avl.max() == Some(&1));
-        assert!(avl.contains(&1));
+        let halves = avl.filter_map(|&v| (v % 2 == 0).then_some(v / 2));
+        assert_eq!(halves.in_order(), vec![&3, &4, &5, &6, &7]);
+        assert_eq!(halves.min(), Some(&3));
+        assert_eq!(halves.max(), Some(&7));
+        assert!(halves.is_balanced());
 
-        avl.remove(&1);
-        assert!(avl.max() == avl.min() && avl.max().is_none());
-        assert!(!avl.contains(&1));
+        let collapsed = avl.filter_map(|&v| Some(v / 4));
+        assert_eq!(collapsed.in_order(), vec![&0, &1, &2, &3]);
+
+        let labels = avl.filter_map(|&v| (v > 10).then(|| v.to_string()));
+        assert_eq!(labels.in_order_cloned(), vec!["12", "14"]);
+
+        assert!(avl.filter_map(|_| None::<i32>).is_empty());
     }
 
     #[test]
-    fn remove_basic_check_min_max_updating() {
-        let mut avl1 = AVLTree::new();
-        let mut avl2 = AVLTree::new();
-        let mut avl3 = AVLTree::new();
-
-        let values1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values2 = vec![4, 2, 6, 1, 3, 5];
-        let values3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+    fn nearest_k_expands_symmetrically() {
+        let mut avl = AVLTree::new();
+        assert!(avl.nearest_k(&5, 3).is_empty());
 
-        for &v in &values1 {
-            avl1.insert(v);
-        }
-        for &v in &values2 {
-            avl2.insert(v);
+        for value in [50, 25, 75, 12, 37, 62, 87] {
+            avl.insert(value);
         }
-        for &v in &values3 {
-            avl3.insert(v);
+        for value in 0..100 {
+            avl.insert(value);
         }
 
-        let mut remaining1 = values1.clone();
-        let mut remaining2 = values2.clone();
-        let mut remaining3 = values3.clone();
+        assert_eq!(avl.nearest_k(&50, 5), vec![&50, &49, &51, &48, &52]);
+        assert!(avl.nearest_k(&50, 0).is_empty());
+        assert_eq!(avl.nearest_k(&-10, 2), vec![&0, &1]);
+        assert_eq!(avl.nearest_k(&200, 2), vec![&99, &98]);
 
-        for &v in &values1 {
-            avl1.remove(&v);
-            remaining1.retain(|&x| x != v);
+        let all = avl.nearest_k(&0, 1000);
+        assert_eq!(all.len(), 100);
+        assert_eq!(all, avl.in_order());
 
-            assert!(!avl1.contains(&v));
-            assert_eq!(avl1.min(), remaining1.iter().min());
-            assert_eq!(avl1.max(), remaining1.iter().max());
+        let mut evens = AVLTree::new();
+        for value in [10u32, 4, 16, 2, 8, 14, 18, 6, 12] {
+            evens.insert(value);
         }
+        assert_eq!(evens.nearest_k(&11, 4), vec![&10, &12, &8, &14]);
+        assert_eq!(evens.nearest_k(&1, 3), vec![&2, &4, &6]);
+    }
 
-        for &v in &values2 {
-            avl2.remove(&v);
-            remaining2.retain(|&x| x != v);
+    #[test]
+    fn remove_min_and_remove_max_empty_the_tree_in_order() {
+        let mut avl = AVLTree::new();
+        assert!(!avl.remove_min());
+        assert!(!avl.remove_max());
 
-            assert!(!avl2.contains(&v));
-            assert_eq!(avl2.min(), remaining2.iter().min());
-            assert_eq!(avl2.max(), remaining2.iter().max());
+        let values: Vec<i32> = (0..200).map(|i| (i * 7919) % 200).collect();
+        for &value in &values {
+            avl.insert(value);
         }
 
-        for &v in &values3 {
-            avl3.remove(&v);
-            remaining3.retain(|&x| x != v);
+        for expected in 0..100 {
+            assert_eq!(avl.min(), Some(&expected));
+            assert!(avl.remove_min());
+            assert_eq!(avl.find(&199), Some(198 - expected as usize));
+            assert!(avl.is_balanced() && avl.is_valid_bst());
+        }
+        for expected in (100..200).rev() {
+            assert_eq!(avl.max(), Some(&expected));
+            assert_eq!(avl.min(), Some(&100));
+            assert!(avl.remove_max());
+            assert!(avl.is_balanced() && avl.is_valid_bst());
+        }
 
-            assert!(!avl3.contains(&v));
-            assert_eq!(avl3.min(), remaining3.iter().min());
-            assert_eq!(avl3.max(), remaining3.iter().max());
+        assert!(avl.is_empty());
+        assert_eq!(avl.min(), None);
+        assert_eq!(avl.max(), None);
+        assert!(!avl.remove_min());
+    }
+
+    #[test]
+    fn insert_recording_left_right_case() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.insert_recording(30), vec![vec![Some(30)]]);
+        assert_eq!(avl.insert_recording(10).len(), 1);
+
+        let snapshots = avl.insert_recording(20);
+        assert_eq!(
+            snapshots,
+            vec![
+                vec![Some(30), Some(10), None, None, Some(20)],
+                vec![Some(30), Some(20), None, Some(10)],
+                vec![Some(20), Some(10), Some(30)],
+            ]
+        );
+        assert_eq!(snapshots.last(), Some(&avl.level_order_with_nulls_cloned()));
+        assert!(avl.is_balanced());
+        assert!(avl.insert_recording(20).is_empty());
+
+        let mut expected = AVLTree::new();
+        let mut recorded = AVLTree::new();
+        for value in [50, 20, 80, 10, 30, 25, 27, 90, 95, 5, 1] {
+            expected.insert(value);
+            recorded.insert_recording(value);
+            assert_eq!(
+                recorded.level_order_with_nulls(),
+                expected.level_order_with_nulls()
+            );
+            assert!(recorded.is_balanced());
+            assert_eq!(recorded.min(), expected.min());
+            assert_eq!(recorded.max(), expected.max());
         }
+        assert_eq!(recorded.find(&95), Some(10));
     }
 
     #[test]
-    fn min_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn try_walk_stops_at_first_match() {
+        let mut avl = AVLTree::new();
+        for value in [50, 20, 80, 10, 30, 60, 90, 25, 35] {
+            avl.insert(value);
+        }
 
-        assert_eq!(avl.min(), None);
+        let mut visited = 0;
+        let found = avl.try_walk(TraversalOrder::InOrder, |&value| {
+            visited += 1;
+            if value > 22 {
+                ControlFlow::Break(value)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(found, ControlFlow::Break(25));
+        assert_eq!(visited, 3);
+
+        let mut pre_order = Vec::new();
+        let result = avl.try_walk(TraversalOrder::PreOrder, |&value| {
+            pre_order.push(value);
+            ControlFlow::<i32>::Continue(())
+        });
+        assert_eq!(result, ControlFlow::Continue(()));
+        assert_eq!(pre_order, avl.pre_order_cloned());
+
+        let first = avl.try_walk(TraversalOrder::LevelOrder, |&value| {
+            ControlFlow::Break(value)
+        });
+        assert_eq!(first, ControlFlow::Break(*avl.root_value().unwrap()));
     }
 
+    #[cfg(feature = "pool")]
     #[test]
-    fn min_basic_check_updating() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
+    fn rebuilds_recycle_pooled_nodes() {
+        let mut avl = AVLTree::new();
+        for value in 0..100 {
+            avl.insert(value);
+        }
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        avl.retain(|value| value % 2 == 0);
+        assert_eq!(avl.pooled_nodes(), 50);
 
-        let mut current_min_1 = None;
-        let mut current_min_2 = None;
-        let mut current_min_3 = None;
+        // The split off tree takes the allocations for its nodes along.
+        let mut other = avl.split_off(&50);
+        assert_eq!((avl.pooled_nodes(), other.pooled_nodes()), (50, 0));
 
-        for value in &values_1 {
-            avl_diff_heights_null.insert(*value);
-            current_min_1 = Some(*value)
-                .filter(|&x| current_min_1.is_none_or(|min| x < min))
-                .or(current_min_1);
-            assert_eq!(avl_diff_heights_null.min(), current_min_1.as_ref());
-        }
+        avl.append(&mut other);
+        assert_eq!((avl.pooled_nodes(), other.pooled_nodes()), (50, 0));
+        assert_eq!(avl.number_of_elements(), 50);
+    }
 
-        for value in &values_2 {
-            avl_diff_heights_one.insert(*value);
-            current_min_2 = Some(*value)
-                .filter(|&x| current_min_2.is_none_or(|min| x < min))
-                .or(current_min_2);
-            assert_eq!(avl_diff_heights_one.min(), current_min_2.as_ref());
+    #[test]
+    fn clear_and_reuse() {
+        let mut avl = AVLTree::new();
+        avl.clear();
+        assert!(avl.is_empty());
+
+        for value in [5, 3, 8, 1, 4] {
+            avl.insert(value);
         }
+        avl.clear();
 
-        for value in &values_3 {
-            avl_diff_heights_two.insert(*value);
-            current_min_3 = Some(*value)
-                .filter(|&x| current_min_3.is_none_or(|min| x < min))
-                .or(current_min_3);
-            assert_eq!(avl_diff_heights_two.min(), current_min_3.as_ref());
+        assert!(avl.is_empty());
+        assert_eq!(avl.min(), None);
+        assert_eq!(avl.max(), None);
+        assert_eq!(avl.number_of_elements(), 0);
+        assert!(!avl.contains(&5));
+
+        for value in [7, 2, 9] {
+            avl.insert(value);
         }
+        assert_eq!(avl.in_order(), vec![&2, &7, &9]);
+        assert_eq!(avl.min(), Some(&2));
+        assert_eq!(avl.max(), Some(&9));
+        assert_eq!(avl.number_of_elements(), 3);
+        assert!(avl.is_balanced());
     }
 
     #[test]
-    fn max_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn len_ignores_duplicates_and_absent_removals() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.len(), 0);
 
-        assert_eq!(avl.max(), None);
+        for value in [5, 3, 8, 5, 3, 1, 8, 9] {
+            avl.insert(value);
+        }
+        assert_eq!(avl.len(), 5);
+
+        avl.remove(&42);
+        avl.remove(&3);
+        avl.remove(&3);
+        avl.insert(1);
+        avl.insert(4);
+        assert_eq!(avl.len(), 5);
+        assert_eq!(avl.len(), avl.in_order().len());
+        assert_eq!(avl.number_of_elements(), avl.len());
+
+        for value in [1, 4, 5, 8, 9, 0] {
+            avl.remove(&value);
+        }
+        assert_eq!(avl.len(), 0);
+        assert!(avl.is_empty());
     }
 
     #[test]
-    fn max_basic_check_updating() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
-
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+    fn kth_smallest_and_largest() {
+        let avl = AVLTree::try_from(vec![50, 20, 80, 10, 30, 70, 90]).unwrap();
+
+        assert_eq!(avl.kth_smallest(0), None);
+        assert_eq!(avl.kth_smallest(1), Some(&10));
+        assert_eq!(avl.kth_smallest(4), Some(&50));
+        assert_eq!(avl.kth_smallest(7), Some(&90));
+        assert_eq!(avl.kth_smallest(8), None);
+
+        assert_eq!(avl.kth_largest(0), None);
+        assert_eq!(avl.kth_largest(1), Some(&90));
+        assert_eq!(avl.kth_largest(3), Some(&70));
+        assert_eq!(avl.kth_largest(7), Some(&10));
+        assert_eq!(avl.kth_largest(8), None);
+
+        let empty = AVLTree::<i32>::new();
+        assert_eq!(empty.kth_smallest(1), None);
+        assert_eq!(empty.kth_largest(1), None);
+    }
 
-        let mut current_max_1 = None;
-        let mut current_max_2 = None;
-        let mut current_max_3 = None;
+    #[test]
+    fn kth_smallest_after_rotations() {
+        let mut avl = AVLTree::new();
 
-        for value in &values_1 {
-            avl_diff_heights_null.insert(*value);
-            current_max_1 = Some(*value)
-                .filter(|&x| current_max_1.is_none_or(|max| x > max))
-                .or(current_max_1);
-            assert_eq!(avl_diff_heights_null.max(), current_max_1.as_ref());
+        // Ascending insertions force a left rotation on almost every step.
+        for i in 1..=64 {
+            avl.insert(i);
+        }
+        for i in (2..=64).step_by(3) {
+            avl.remove(&i);
         }
 
-        for value in &values_2 {
-            avl_diff_heights_one.insert(*value);
-            current_max_2 = Some(*value)
-                .filter(|&x| current_max_2.is_none_or(|max| x > max))
-                .or(current_max_2);
-            assert_eq!(avl_diff_heights_one.max(), current_max_2.as_ref());
+        let sorted: Vec<i32> = avl.in_order_cloned();
+        for (k, expected) in sorted.iter().enumerate() {
+            assert_eq!(avl.kth_smallest(k + 1), Some(expected));
+            assert_eq!(avl.kth_largest(sorted.len() - k), Some(expected));
         }
+        assert_eq!(avl.kth_smallest(sorted.len() + 1), None);
+    }
 
-        for value in &values_3 {
-            avl_diff_heights_two.insert(*value);
-            current_max_3 = Some(*value)
-                .filter(|&x| current_max_3.is_none_or(|max| x > max))
-                .or(current_max_3);
-            assert_eq!(avl_diff_heights_two.max(), current_max_3.as_ref());
+    #[test]
+    fn pop_min_and_pop_max() {
+        let mut avl = AVLTree::try_from(vec![4, 2, 6, 1, 3, 5, 7]).unwrap();
+
+        assert_eq!(avl.pop_min(), Some(1));
+        assert_eq!(avl.pop_max(), Some(7));
+        assert_eq!(avl.min(), Some(&2));
+        assert_eq!(avl.max(), Some(&6));
+        assert_eq!(avl.in_order(), vec![&2, &3, &4, &5, &6]);
+        assert!(avl.is_balanced());
+
+        for expected in [2, 3, 4, 5, 6] {
+            assert_eq!(avl.pop_min(), Some(expected));
+            assert!(avl.is_balanced());
         }
+        assert_eq!(avl.pop_min(), None);
+        assert_eq!(avl.pop_max(), None);
+        assert_eq!(avl.min(), None);
+        assert_eq!(avl.max(), None);
     }
 
     #[test]
-    fn max_min_are_similar_for_single_element_tree() {
+    fn take_returns_removed_value() {
         let mut avl = AVLTree::new();
-        avl.insert(1);
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            avl.insert(value);
+        }
 
-        assert!(avl.min() == avl.max() && avl.min() == Some(&1));
+        assert_eq!(avl.take(&45), None);
+        assert_eq!(avl.take(&50), Some(50));
+        assert_eq!(avl.take(&20), Some(20));
+        assert_eq!(avl.take(&80), Some(80));
+        assert_eq!(avl.take(&80), None);
+        assert_eq!(avl.in_order(), vec![&30, &40, &60, &70]);
+        assert_eq!(avl.min(), Some(&30));
+        assert_eq!(avl.max(), Some(&70));
+        assert_eq!(avl.len(), 4);
+
+        let mut empty = AVLTree::<i32>::new();
+        assert_eq!(empty.take(&1), None);
     }
 
     #[test]
-    fn height_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn try_insert_reports_new_values() {
+        let mut avl = AVLTree::new();
 
-        assert_eq!(avl.height(), 0);
+        assert!(avl.try_insert(5));
+        assert!(avl.try_insert(3));
+        assert!(!avl.try_insert(5));
+        assert!(!avl.try_insert(3));
+        assert_eq!(avl.len(), 2);
+
+        assert!(avl.try_insert(8));
+        assert!(!avl.try_insert(8));
+        assert_eq!(avl.len(), 3);
+        assert_eq!(avl.in_order(), vec![&3, &5, &8]);
     }
 
     #[test]
-    fn height_basic() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
+    fn get_returns_the_stored_payload() {
+        let mut avl = AVLTree::new();
+        assert!(avl.get(&keyed(1, "query")).is_none());
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            avl_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            avl_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            avl_diff_heights_two.insert(value);
-        }
+        avl.insert(keyed(2, "two"));
+        avl.insert(keyed(1, "one"));
+        avl.insert(keyed(3, "three"));
 
-        assert_eq!(avl_diff_heights_null.height(), 2);
-        assert_eq!(avl_diff_heights_one.height(), 2);
-        assert_eq!(avl_diff_heights_two.height(), 3);
+        assert_eq!(avl.get(&keyed(1, "query")).unwrap().payload, "one");
+        assert_eq!(avl.get(&keyed(3, "query")).unwrap().payload, "three");
+        assert!(avl.get(&keyed(4, "query")).is_none());
     }
 
     #[test]
-    fn pre_order_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn clone_is_independent() {
+        let mut avl = AVLTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            avl.insert(value);
+        }
 
-        assert_eq!(avl.pre_order(), Vec::<&i32>::new());
+        let mut copy = avl.clone();
+        copy.remove(&50);
+        copy.remove(&20);
+        copy.insert(90);
+
+        assert_eq!(avl.in_order(), vec![&20, &30, &40, &50, &60, &70, &80]);
+        assert_eq!(avl.min(), Some(&20));
+        assert_eq!(avl.max(), Some(&80));
+        assert_eq!(avl.len(), 7);
+
+        assert_eq!(copy.in_order(), vec![&30, &40, &60, &70, &80, &90]);
+        assert_eq!(copy.min(), Some(&30));
+        assert_eq!(copy.max(), Some(&90));
+        assert_eq!(copy.len(), 6);
     }
 
     #[test]
-    fn pre_order_basic() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
-
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            avl_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            avl_diff_heights_one.insert(value);
+    fn equality_ignores_insertion_order() {
+        let mut first = AVLTree::new();
+        let mut second = AVLTree::new();
+        for value in [1, 2, 3, 4, 5] {
+            first.insert(value);
         }
-        for value in &values_3 {
-            avl_diff_heights_two.insert(value);
+        for value in [3, 5, 1, 4, 2, 5] {
+            second.insert(value);
         }
+        assert_eq!(first, second);
+        assert_eq!(AVLTree::<i32>::new(), AVLTree::new());
 
-        assert_eq!(
-            avl_diff_heights_null.pre_order(),
-            vec![&&5, &&3, &&2, &&4, &&7, &&6, &&8]
-        );
+        second.remove(&5);
+        second.insert(6);
+        assert_ne!(first, second);
 
-        assert_eq!(
-            avl_diff_heights_one.pre_order(),
-            vec![&&4, &&2, &&1, &&3, &&6, &&5]
-        );
+        second.remove(&6);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn into_sorted_vec_moves_values_out() {
+        let mut avl = AVLTree::new();
+        for value in [5, 2, 8, 1, 9, 3, 2] {
+            avl.insert(Box::new(value));
+        }
 
+        let sorted = avl.into_sorted_vec();
+        assert_eq!(sorted.len(), 6);
+        assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
         assert_eq!(
-            avl_diff_heights_two.pre_order(),
-            vec![&&8, &&4, &&2, &&1, &&6, &&7, &&12, &&10]
+            sorted.into_iter().map(|value| *value).collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 8, 9]
         );
+
+        assert!(AVLTree::<i32>::new().into_sorted_vec().is_empty());
     }
 
     #[test]
-    fn in_order_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn level_order_grouped_by_depth() {
+        let mut avl = AVLTree::new();
+        assert!(avl.level_order_grouped().is_empty());
 
-        assert_eq!(avl.in_order(), Vec::<&i32>::new());
+        for value in 1..=10 {
+            avl.insert(value);
+        }
+
+        let levels = avl.level_order_grouped();
+        assert_eq!(levels.len(), avl.height() + 1);
+        assert_eq!(levels[0], vec![avl.root_value().unwrap()]);
+        assert_eq!(levels.concat(), avl.level_order());
     }
 
     #[test]
-    fn in_order_basic() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
-
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            avl_diff_heights_null.insert(value);
+    fn lookups_accept_borrowed_forms() {
+        let mut avl = AVLTree::new();
+        for word in ["bar", "foo", "qux"] {
+            avl.insert(word.to_string());
         }
-        for value in &values_2 {
-            avl_diff_heights_one.insert(value);
+
+        assert!(avl.contains("foo"));
+        assert!(!avl.contains("baz"));
+        assert_eq!(avl.get("qux"), Some(&"qux".to_string()));
+        assert_eq!(avl.ceil("baz"), Some(&"foo".to_string()));
+        assert_eq!(avl.floor("baz"), Some(&"bar".to_string()));
+
+        assert_eq!(avl.take("bar"), Some("bar".to_string()));
+        avl.remove("foo");
+        assert!(!avl.contains("foo"));
+        assert_eq!(avl.len(), 1);
+    }
+
+    #[test]
+    fn merge_builds_balanced_union() {
+        let mut avl: AVLTree<i32> = AVLTree::new();
+        for value in (0..100).step_by(2) {
+            avl.insert(value);
         }
-        for value in &values_3 {
-            avl_diff_heights_two.insert(value);
+        let mut other = AVLTree::new();
+        for value in (0..150).step_by(3) {
+            other.insert(value);
         }
 
-        assert_eq!(
-            avl_diff_heights_null.in_order(),
-            vec![&&2, &&3, &&4, &&5, &&6, &&7, &&8]
-        );
-
-        assert_eq!(
-            avl_diff_heights_one.in_order(),
-            vec![&&1, &&2, &&3, &&4, &&5, &&6]
-        );
+        avl.merge(other);
 
-        assert_eq!(
-            avl_diff_heights_two.in_order(),
-            vec![&&1, &&2, &&4, &&6, &&7, &&8, &&10, &&12]
-        );
+        let expected: Vec<i32> = (0..150)
+            .filter(|v| v % 3 == 0 || (v % 2 == 0 && *v < 100))
+            .collect();
+        assert_eq!(avl.clone().into_sorted_vec(), expected);
+        assert_eq!(avl.len(), expected.len());
+        assert_eq!(avl.min(), Some(&0));
+        assert_eq!(avl.max(), Some(&147));
+        assert!(avl.is_balanced());
     }
 
     #[test]
-    fn post_order_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn merge_with_empty_trees() {
+        let mut avl: AVLTree<i32> = AVLTree::new();
+        avl.merge(AVLTree::new());
+        assert!(avl.is_empty());
+        assert_eq!(avl.min(), None);
 
-        assert_eq!(avl.post_order(), Vec::<&i32>::new());
+        let mut other = AVLTree::new();
+        other.insert(7);
+        other.insert(3);
+        avl.merge(other);
+        assert_eq!(avl.in_order(), vec![&3, &7]);
+        assert_eq!(avl.min(), Some(&3));
+        assert_eq!(avl.max(), Some(&7));
+        assert!(avl.is_balanced());
     }
 
     #[test]
-    fn post_order_basic() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
-
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            avl_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            avl_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            avl_diff_heights_two.insert(value);
+    fn retain_keeps_matching_values_balanced() {
+        let mut avl = AVLTree::new();
+        for value in 1..=100 {
+            avl.insert(value);
         }
 
-        assert_eq!(
-            avl_diff_heights_null.post_order(),
-            vec![&&2, &&4, &&3, &&6, &&8, &&7, &&5]
-        );
+        avl.retain(|value| value % 2 == 0);
 
-        assert_eq!(
-            avl_diff_heights_one.post_order(),
-            vec![&&1, &&3, &&2, &&5, &&6, &&4]
-        );
+        let evens: Vec<i32> = (2..=100).step_by(2).collect();
+        assert_eq!(avl.clone().into_sorted_vec(), evens);
+        assert_eq!(avl.len(), 50);
+        assert_eq!((avl.min(), avl.max()), (Some(&2), Some(&100)));
+        assert!(avl.is_balanced());
 
-        assert_eq!(
-            avl_diff_heights_two.post_order(),
-            vec![&&1, &&2, &&7, &&6, &&4, &&10, &&12, &&8]
-        );
+        avl.retain(|_| false);
+        assert!(avl.is_empty());
+        assert_eq!((avl.min(), avl.max()), (None, None));
     }
 
     #[test]
-    fn level_order_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn for_each_sums_in_order() {
+        let mut avl = AVLTree::new();
+        for value in [8, 3, 10, 1, 6, 14, 4, 7, 13] {
+            avl.insert(value);
+        }
 
-        assert_eq!(avl.level_order(), Vec::<&i32>::new());
+        let mut sum = 0;
+        avl.for_each(TraversalOrder::InOrder, |value| sum += value);
+
+        assert_eq!(sum, avl.in_order().into_iter().sum::<i32>());
     }
 
     #[test]
-    fn level_order_basic() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
+    fn depth_of_values() {
+        let mut avl = AVLTree::new();
+        for value in 1..=7 {
+            avl.insert(value);
+        }
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            avl_diff_heights_null.insert(value);
+        // Sequential insertions produce the perfect tree rooted at 4.
+        assert_eq!(avl.depth(&4), Some(0));
+        assert_eq!(avl.depth(&2), Some(1));
+        assert_eq!(avl.depth(&6), Some(1));
+        for leaf in [1, 3, 5, 7] {
+            assert_eq!(avl.depth(&leaf), Some(2));
         }
-        for value in &values_2 {
-            avl_diff_heights_one.insert(value);
+        assert_eq!(avl.depth(&8), None);
+    }
+
+    #[test]
+    fn diameter_of_balanced_tree() {
+        assert_eq!(AVLTree::<i32>::new().diameter(), 0);
+
+        let mut avl = AVLTree::new();
+        avl.insert(1);
+        assert_eq!(avl.diameter(), 0);
+
+        // Sequential insertions produce the perfect tree of 3 levels.
+        for value in 2..=7 {
+            avl.insert(value);
         }
-        for value in &values_3 {
-            avl_diff_heights_two.insert(value);
+        assert_eq!(avl.diameter(), 4);
+
+        for value in 8..=1000 {
+            avl.insert(value);
         }
+        assert!(avl.diameter() <= 2 * avl.height());
+    }
 
-        assert_eq!(
-            avl_diff_heights_null.level_order(),
-            vec![&&5, &&3, &&7, &&2, &&4, &&6, &&8]
-        );
+    #[test]
+    fn count_leaves_and_internal_nodes() {
+        let mut avl = AVLTree::new();
+        assert_eq!((avl.count_leaves(), avl.count_internal_nodes()), (0, 0));
 
-        assert_eq!(
-            avl_diff_heights_one.level_order(),
-            vec![&&4, &&2, &&6, &&1, &&3, &&5]
-        );
+        // Sequential insertions produce the perfect tree of 3 levels.
+        for value in 1..=7 {
+            avl.insert(value);
+        }
+        assert_eq!((avl.count_leaves(), avl.count_internal_nodes()), (4, 3));
 
-        assert_eq!(
-            avl_diff_heights_two.level_order(),
-            vec![&&8, &&4, &&12, &&2, &&6, &&10, &&1, &&7]
-        );
+        for value in 8..=100 {
+            avl.insert(value);
+        }
+        assert_eq!(avl.count_leaves() + avl.count_internal_nodes(), avl.len());
     }
 
     #[test]
-    fn number_of_elements_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn balance_factor_of_values() {
+        //        5
+        //       / \
+        //      3   8
+        //     /
+        //    1
+        let mut avl = AVLTree::new();
+        for value in [5, 3, 8, 1] {
+            avl.insert(value);
+        }
 
-        assert_eq!(avl.number_of_elements(), 0);
+        assert_eq!(avl.balance_factor_of(&5), Some(1));
+        assert_eq!(avl.balance_factor_of(&3), Some(1));
+        assert_eq!(avl.balance_factor_of(&8), Some(0));
+        assert_eq!(avl.balance_factor_of(&1), Some(0));
+        assert_eq!(avl.balance_factor_of(&4), None);
+
+        avl.insert(9);
+        avl.insert(10);
+        for value in [1, 3, 5, 8, 9, 10] {
+            let factor = avl.balance_factor_of(&value).unwrap();
+            assert!((-1..=1).contains(&factor));
+        }
     }
 
     #[test]
-    fn number_of_elements_basic() {
-        let mut avl_diff_heights_null = AVLTree::new();
-        let mut avl_diff_heights_one = AVLTree::new();
-        let mut avl_diff_heights_two = AVLTree::new();
+    fn display_alternate_draws_tree_sideways() {
+        let mut avl = AVLTree::new();
+        assert_eq!(format!("{avl:#}"), "<empty>");
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            avl_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            avl_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            avl_diff_heights_two.insert(value);
+        for value in [4, 2, 6, 1, 3, 7] {
+            avl.insert(value);
         }
 
-        assert_eq!(avl_diff_heights_null.number_of_elements(), values_1.len());
-        assert_eq!(avl_diff_heights_one.number_of_elements(), values_2.len());
-        assert_eq!(avl_diff_heights_two.number_of_elements(), values_3.len());
+        let expected = "        7\n    6\n4\n        3\n    2\n        1";
+        assert_eq!(format!("{avl:#}"), expected);
+        assert_eq!(format!("{avl}"), "{1, 2, 3, 4, 6, 7}");
     }
 
     #[test]
-    fn ceil_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn reverse_in_order_descends() {
+        let mut avl = AVLTree::new();
+        assert!(avl.reverse_in_order().is_empty());
 
-        assert_eq!(avl.ceil(&0), None);
+        for i in 0..100 {
+            avl.insert((i * 37) % 100);
+        }
+
+        let descending: Vec<i32> = (0..100).rev().collect();
+        let expected: Vec<&i32> = descending.iter().collect();
+        assert_eq!(avl.reverse_in_order(), expected);
+        assert_eq!(avl.iter().rev().collect::<Vec<_>>(), avl.reverse_in_order());
     }
 
     #[test]
-    fn ceil_basic() {
-        let mut avl = AVLTree::<i32>::new();
+    fn extract_if_returns_odd_values() {
+        let mut avl = AVLTree::new();
+        for value in [8, 3, 10, 1, 6, 14, 4, 7, 13] {
+            avl.insert(value);
+        }
 
-        avl.insert(1);
-        avl.insert(2);
-        avl.insert(5);
+        let odd = avl.extract_if(|value| value % 2 == 1);
 
-        assert_eq!(avl.ceil(&6), None);
-        assert_eq!(avl.ceil(&5), Some(&5));
-        assert_eq!(avl.ceil(&4), Some(&5));
-        assert_eq!(avl.ceil(&3), Some(&5));
-        assert_eq!(avl.ceil(&2), Some(&2));
-        assert_eq!(avl.ceil(&1), Some(&1));
-        assert_eq!(avl.ceil(&0), Some(&1));
+        assert_eq!(odd, vec![1, 3, 7, 13]);
+        assert_eq!(avl.in_order_cloned(), vec![4, 6, 8, 10, 14]);
+        assert_eq!((avl.min(), avl.max()), (Some(&4), Some(&14)));
+        assert!(avl.is_balanced() && avl.is_valid_bst());
+
+        assert!(avl.extract_if(|value| value % 2 == 1).is_empty());
+        assert_eq!(avl.extract_if(|_| true), vec![4, 6, 8, 10, 14]);
+        assert!(avl.is_empty());
+        assert_eq!((avl.min(), avl.max()), (None, None));
     }
 
     #[test]
-    fn floor_in_empty_tree() {
-        let avl = AVLTree::<i32>::new();
+    fn remove_roots_of_large_tree_stays_balanced() {
+        let mut avl = AVLTree::new();
+        for value in 0..50_000 {
+            avl.insert(value);
+        }
 
-        assert_eq!(avl.floor(&0), None);
+        // Removing the root always detaches the minimum of its right subtree.
+        let mut expected = 50_000;
+        while let Some(&root) = avl.root_value() {
+            avl.remove(&root);
+            expected -= 1;
+
+            assert!(!avl.contains(&root));
+            assert_eq!(avl.len(), expected);
+            if expected % 1_000 == 0 {
+                assert!(avl.is_balanced() && avl.is_valid_bst());
+            }
+        }
+
+        assert!(avl.is_empty());
+        assert_eq!((avl.min(), avl.max()), (None, None));
     }
 
     #[test]
-    fn floor_basic() {
-        let mut avl = AVLTree::<i32>::new();
-
-        avl.insert(1);
-        avl.insert(2);
-        avl.insert(5);
+    fn remove_rescans_only_for_extreme_values() {
+        let mut avl = AVLTree::new();
+        for value in 0..1_000 {
+            avl.insert(value);
+        }
+        let refinds = || REFINDS.with(|refinds| refinds.get());
+        let before = refinds();
 
-        assert_eq!(avl.floor(&6), Some(&5));
-        assert_eq!(avl.floor(&5), Some(&5));
-        assert_eq!(avl.floor(&4), Some(&2));
-        assert_eq!(avl.floor(&3), Some(&2));
-        assert_eq!(avl.floor(&2), Some(&2));
-        assert_eq!(avl.floor(&1), Some(&1));
-        assert_eq!(avl.floor(&0), None);
+        for value in (1..999).step_by(3) {
+            avl.remove(&value);
+        }
+        avl.remove(&5_000);
+        assert_eq!(refinds(), before);
+        assert_eq!((avl.min(), avl.max()), (Some(&0), Some(&999)));
+
+        avl.remove(&999);
+        assert_eq!(refinds(), before + 1);
+        assert_eq!(avl.max(), Some(&998));
+
+        avl.remove(&0);
+        assert_eq!(refinds(), before + 2);
+        assert_eq!(avl.min(), Some(&2));
+        assert_eq!(avl.in_order().first(), Some(&&2));
+        assert_eq!(avl.in_order().last(), Some(&&998));
     }
 }
@@ -0,0 +1,226 @@
+use super::AVLTree;
+use super::node::AVLNode;
+
+/// A lazy iterator over the elements of an `AVLTree` in ascending order
+/// (descending from the back), created by `AVLTree::iter`.
+///
+/// Only the nodes on the paths to the next elements from both ends are kept on the stacks,
+/// so no vector of all elements is built.
+#[derive(Debug)]
+pub struct Iter<'a, T: PartialOrd> {
+    /// Ancestors of the next element from the front, the next one on top.
+    front: Vec<&'a AVLNode<T>>,
+
+    /// Ancestors of the next element from the back, the next one on top.
+    back: Vec<&'a AVLNode<T>>,
+
+    /// The number of elements not yet returned from either end.
+    remaining: usize,
+}
+
+impl<'a, T: PartialOrd + Clone> Iter<'a, T> {
+    /// Creates an iterator over all elements of the `tree`.
+    pub(super) fn new(tree: &'a AVLTree<T>) -> Self {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            remaining: AVLNode::size(&tree.root),
+        };
+        iter.push_left_spine(&tree.root);
+        iter.push_right_spine(&tree.root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut current: &'a Option<Box<AVLNode<T>>>) {
+        while let Some(node) = current {
+            self.front.push(node);
+            current = &node.left;
+        }
+    }
+
+    fn push_right_spine(&mut self, mut current: &'a Option<Box<AVLNode<T>>>) {
+        while let Some(node) = current {
+            self.back.push(node);
+            current = &node.right;
+        }
+    }
+}
+
+impl<'a, T: PartialOrd + Clone> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.front.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: PartialOrd + Clone> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.back.pop()?;
+        self.push_right_spine(&node.left);
+        Some(&node.value)
+    }
+}
+
+impl<T: PartialOrd + Clone> ExactSizeIterator for Iter<'_, T> {}
+
+/// An iterator moving the elements out of an `AVLTree` in ascending order, created by
+/// `into_iter` (so `for value in tree` consumes the tree).
+///
+/// The nodes are taken apart as the iteration goes, so the values are never cloned.
+#[derive(Debug)]
+pub struct IntoIter<T: PartialOrd> {
+    /// Ancestors of the next element with their left subtrees detached, the next one on top.
+    stack: Vec<Box<AVLNode<T>>>,
+
+    /// The number of elements not yet returned.
+    remaining: usize,
+}
+
+impl<T: PartialOrd> IntoIter<T> {
+    /// Creates an iterator over all elements of a tree with the given `root`.
+    pub(super) fn new(root: Option<Box<AVLNode<T>>>) -> Self {
+        let mut iter = IntoIter {
+            stack: Vec::new(),
+            remaining: AVLNode::size(&root),
+        };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut current: Option<Box<AVLNode<T>>>) {
+        while let Some(mut node) = current {
+            current = node.left.take();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<T: PartialOrd> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.remaining -= 1;
+        self.push_left_spine(node.right.take());
+        Some(node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: PartialOrd> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: PartialOrd + Clone> IntoIterator for AVLTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.root)
+    }
+}
+
+impl<'a, T: PartialOrd + Clone> IntoIterator for &'a AVLTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterates_in_both_directions() {
+        let mut avl = AVLTree::new();
+        assert_eq!(avl.iter().next(), None);
+        assert_eq!(avl.iter().next_back(), None);
+
+        for value in [50, 20, 80, 10, 30, 60, 90, 25, 35] {
+            avl.insert(value);
+        }
+
+        assert_eq!(avl.iter().collect::<Vec<_>>(), avl.in_order());
+        assert_eq!(
+            avl.iter().rev().collect::<Vec<_>>(),
+            vec![&90, &80, &60, &50, &35, &30, &25, &20, &10]
+        );
+        assert_eq!(avl.iter().len(), 9);
+    }
+
+    #[test]
+    fn ends_meet_without_overlap() {
+        let mut avl = AVLTree::new();
+        for value in [4, 2, 6, 1, 3, 5, 7] {
+            avl.insert(value);
+        }
+
+        let mut iter = avl.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&7));
+        assert_eq!(iter.next_back(), Some(&6));
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&4));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        assert!(avl.iter().take_while(|&&value| value < 3).eq([&1, &2]));
+    }
+
+    #[test]
+    fn into_iter_moves_values_out_in_order() {
+        let words = ["pear", "apple", "fig", "kiwi", "banana", "cherry", "date"];
+
+        let mut avl = AVLTree::new();
+        for word in words {
+            avl.insert(word.to_string());
+        }
+
+        let borrowed: Vec<&String> = (&avl).into_iter().collect();
+        assert_eq!(borrowed, avl.in_order());
+
+        let mut sorted: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+        sorted.sort();
+
+        let mut owned = Vec::new();
+        for value in avl {
+            owned.push(value);
+        }
+        assert_eq!(owned, sorted);
+
+        let mut avl = AVLTree::new();
+        for value in 0..10 {
+            avl.insert(value);
+        }
+        let mut iter = avl.into_iter();
+        assert_eq!(iter.len(), 10);
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.len(), 9);
+        assert!(iter.eq(1..10));
+        assert_eq!(AVLTree::<String>::new().into_iter().next(), None);
+    }
+}
@@ -3,9 +3,17 @@ mod avl_operations;
 /// Internal implementation of `AVLTree` nodes.
 pub mod node;
 
+/// Paginated reading in ascending order.
+pub mod cursor;
+
+/// Lazy iteration in ascending order, borrowing and owning.
+pub mod iter;
+
 /// For visualizing (Graphviz, DOT format).
 pub mod visualization;
 
+use crate::metrics::Rotations;
+use crate::pool::NodePool;
 use node::AVLNode;
 
 /// A self-balancing AVL tree implementation.
@@ -16,16 +24,22 @@ use node::AVLNode;
 ///   - All values in the left subtree are less than the node's value
 ///   - All values in the right subtree are greater than the node's value
 ///   - Duplicate values are not allowed
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AVLTree<T: PartialOrd + Clone> {
     /// Root node of the tree (private to maintain invariants)
     root: Option<Box<AVLNode<T>>>,
 
-    /// Cached minimum value (None if tree is empty)
+    /// Cached minimum value in the order of `T` (None if tree is empty)
     min_value: Option<T>,
 
-    /// Cached maximum value (None if tree is empty)
+    /// Cached maximum value in the order of `T` (None if tree is empty)
     max_value: Option<T>,
+
+    /// Node allocations kept for reuse (only populated with the `pool` feature)
+    pool: NodePool<AVLNode<T>>,
+
+    /// Number of rotations performed over the lifetime of the tree
+    rotations: Rotations,
 }
 
 impl<T: PartialOrd + Clone> AVLTree<T> {
@@ -43,6 +57,31 @@ impl<T: PartialOrd + Clone> AVLTree<T> {
         check_balance(&self.root)
     }
 
+    /// Returns how many nodes have a balance factor of -1, 0 and +1 respectively.
+    ///
+    /// Nodes with any other balance factor violate the AVL invariant and are not counted, so the
+    /// counts summing to less than `number_of_elements()` indicates a broken tree.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn balance_factor_histogram(&self) -> [usize; 3] {
+        let mut histogram = [0; 3];
+        let mut stack: Vec<&AVLNode<T>> = self.root.as_deref().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            match node.balance_factor() {
+                -1 => histogram[0] += 1,
+                0 => histogram[1] += 1,
+                1 => histogram[2] += 1,
+                _ => {}
+            }
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+
+        histogram
+    }
+
     /// Check BST invariant for AVL Tree.
     pub fn is_valid_bst(&self) -> bool {
         fn check<T: PartialOrd>(
@@ -68,4 +107,35 @@ impl<T: PartialOrd + Clone> AVLTree<T> {
         }
         check(&self.root, None, None)
     }
+
+    /// Check the multiset variant of the BST invariant for AVL Tree.
+    ///
+    /// Unlike `is_valid_bst`, values equal to a node are allowed in its right subtree
+    /// (left subtree values must still be strictly less than the node's value).
+    pub fn is_valid_bst_multiset(&self) -> bool {
+        fn check<T: PartialOrd>(
+            node: &Option<Box<AVLNode<T>>>,
+            min: Option<&T>,
+            max: Option<&T>,
+        ) -> bool {
+            match node {
+                Some(node) => {
+                    if let Some(min_val) = min
+                        && &node.value < min_val
+                    {
+                        return false;
+                    }
+                    if let Some(max_val) = max
+                        && &node.value >= max_val
+                    {
+                        return false;
+                    }
+                    check(&node.left, min, Some(&node.value))
+                        && check(&node.right, Some(&node.value), max)
+                }
+                None => true,
+            }
+        }
+        check(&self.root, None, None)
+    }
 }
@@ -1,9 +1,12 @@
+use crate::metrics::Rotations;
+
 /// A node in an AVL tree structure with height tracking.
 ///
 /// Each node contains:
 /// - A `value` of generic type `T`
 /// - Optional left/right child nodes (wrapped in `Box`)
 /// - Height information for balancing
+/// - The size of its subtree (for rank queries)
 ///
 /// Maintains the AVL invariant: balance factor ∈ [-1, 0, 1]
 #[derive(Debug, Clone)]
@@ -19,6 +22,9 @@ pub struct AVLNode<T: PartialOrd> {
 
     /// Height of this node's subtree (leaf nodes have height 1).
     pub height: usize,
+
+    /// Number of nodes in this node's subtree (leaf nodes have size 1).
+    pub size: usize,
 }
 
 impl<T: PartialOrd> AVLNode<T> {
@@ -29,12 +35,14 @@ impl<T: PartialOrd> AVLNode<T> {
             left: None,
             right: None,
             height: 1,
+            size: 1,
         }
     }
 
-    /// Updates this node's height based on children's heights.
+    /// Updates this node's height and subtree size based on children's heights and sizes.
     pub fn update_height(&mut self) {
         self.height = 1 + std::cmp::max(Self::height(&self.left), Self::height(&self.right));
+        self.size = 1 + Self::size(&self.left) + Self::size(&self.right);
     }
 
     /// Returns the height of a node (0 for `None`).
@@ -42,6 +50,11 @@ impl<T: PartialOrd> AVLNode<T> {
         node.as_ref().map_or(0, |n| n.height)
     }
 
+    /// Returns the size of a node's subtree (0 for `None`).
+    pub fn size(node: &Option<Box<Self>>) -> usize {
+        node.as_ref().map_or(0, |n| n.size)
+    }
+
     /// Calculates the balance factor (left_height - right_height).
     ///
     /// Returns:
@@ -62,27 +75,72 @@ impl<T: PartialOrd> AVLNode<T> {
     /// - Left-Right (double rotation)
     /// - Right-Left (double rotation)
     pub fn rebalance(self: Box<Self>) -> Box<Self> {
+        self.rebalance_counting(&mut Rotations::new())
+    }
+
+    /// Rebalances the node like `rebalance`, adding the rotations made to `rotations`.
+    pub(crate) fn rebalance_counting(self: Box<Self>, rotations: &mut Rotations) -> Box<Self> {
         match self.balance_factor() {
             bf if bf > 1 => {
                 if self.left.as_ref().unwrap().balance_factor() >= 0 {
-                    self.ll_rotation()
+                    self.ll_rotation(rotations)
                 } else {
-                    self.lr_rotation()
+                    self.lr_rotation(rotations)
                 }
             }
             bf if bf < -1 => {
                 if self.right.as_ref().unwrap().balance_factor() <= 0 {
-                    self.rr_rotation()
+                    self.rr_rotation(rotations)
                 } else {
-                    self.rl_rotation()
+                    self.rl_rotation(rotations)
                 }
             }
             _ => self,
         }
     }
 
+    /// Performs the first single rotation that `rebalance` would perform, returning the new
+    /// subtree root and whether a rotation has been made.
+    ///
+    /// Calling it until it returns `false` rebalances the node like `rebalance`, which allows
+    /// observing the double rotation cases step by step.
+    pub fn rebalance_step(self: Box<Self>) -> (Box<Self>, bool) {
+        self.rebalance_step_counting(&mut Rotations::new())
+    }
+
+    /// Performs a step of rebalancing like `rebalance_step`, adding the rotation made to
+    /// `rotations`.
+    pub(crate) fn rebalance_step_counting(
+        mut self: Box<Self>,
+        rotations: &mut Rotations,
+    ) -> (Box<Self>, bool) {
+        match self.balance_factor() {
+            bf if bf > 1 => {
+                if self.left.as_ref().unwrap().balance_factor() >= 0 {
+                    (self.ll_rotation(rotations), true)
+                } else {
+                    let left = self.left.take().unwrap();
+                    self.left = Some(left.rr_rotation(rotations));
+                    (self, true)
+                }
+            }
+            bf if bf < -1 => {
+                if self.right.as_ref().unwrap().balance_factor() <= 0 {
+                    (self.rr_rotation(rotations), true)
+                } else {
+                    let right = self.right.take().unwrap();
+                    self.right = Some(right.ll_rotation(rotations));
+                    (self, true)
+                }
+            }
+            _ => (self, false),
+        }
+    }
+
     /// Performs a left-left case rotation.
-    fn ll_rotation(mut self: Box<Self>) -> Box<Self> {
+    fn ll_rotation(mut self: Box<Self>, rotations: &mut Rotations) -> Box<Self> {
+        rotations.record();
+
         let mut new_root = self.left.take().unwrap();
         self.left = new_root.right.take();
         self.update_height();
@@ -92,7 +150,9 @@ impl<T: PartialOrd> AVLNode<T> {
     }
 
     /// Performs a right-right case rotation.
-    fn rr_rotation(mut self: Box<Self>) -> Box<Self> {
+    fn rr_rotation(mut self: Box<Self>, rotations: &mut Rotations) -> Box<Self> {
+        rotations.record();
+
         let mut new_root = self.right.take().unwrap();
         self.right = new_root.left.take();
         self.update_height();
@@ -102,16 +162,16 @@ impl<T: PartialOrd> AVLNode<T> {
     }
 
     /// Performs a right-left case rotation.
-    fn rl_rotation(mut self: Box<Self>) -> Box<Self> {
+    fn rl_rotation(mut self: Box<Self>, rotations: &mut Rotations) -> Box<Self> {
         let right = self.right.take().unwrap();
-        self.right = Some(right.ll_rotation());
-        self.rr_rotation()
+        self.right = Some(right.ll_rotation(rotations));
+        self.rr_rotation(rotations)
     }
 
     /// Performs a left-right case rotation.
-    fn lr_rotation(mut self: Box<Self>) -> Box<Self> {
+    fn lr_rotation(mut self: Box<Self>, rotations: &mut Rotations) -> Box<Self> {
         let left = self.left.take().unwrap();
-        self.left = Some(left.rr_rotation());
-        self.ll_rotation()
+        self.left = Some(left.rr_rotation(rotations));
+        self.ll_rotation(rotations)
     }
 }
@@ -1,6 +1,24 @@
 use super::*;
+use crate::avl_tree::AVLTree;
+use crate::bytes::{self, LeBytes};
+use crate::diff::DiffItem;
+use crate::error::{DecodeError, DuplicateError, IncomparableError};
+use crate::operation::Op;
+use crate::traversal::TraversalOrder;
+use cursor::Cursor;
+use entry::{Entry, VacantEntry};
+use iter::Iter;
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::ops::{ControlFlow, Range, Sub};
+
+#[cfg(test)]
+thread_local! {
+    /// Scans for the minimum or maximum performed on this thread (observed by the tests).
+    static REFINDS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
 
 impl<T: PartialOrd + Clone> BinarySearchTree<T> {
     /// Creates a new empty `BinarySearchTree`.
@@ -9,73 +27,223 @@ impl<T: PartialOrd + Clone> BinarySearchTree<T> {
             root: None,
             min_value: None,
             max_value: None,
+            pool: NodePool::new(),
+            order: Order::Natural,
+            kind: PhantomData,
         }
     }
 
-    /// Checks if the tree is empty.
+    /// Creates a new empty `BinarySearchTree` ordering its elements with `cmp` instead of the
+    /// `PartialOrd` implementation of `T` (e.g. by a derived key).
+    ///
+    /// Every comparison of the tree goes through the comparator: "less", "ascending" and
+    /// ranges in the documentation of the other methods refer to its order, the traversals
+    /// return the elements in it and the trees split or drained off this one keep it.
+    /// The lookups by a borrowed key (`get`, `contains`, `ceil`, ...) compare the key by its own
+    /// `PartialOrd`, so such a tree is searched with `get_by`, `ceil_by`, `floor_by` and
+    /// `take_by` instead.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::with_comparator(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+    /// for word in ["three", "a", "four", "to"] {
+    ///     bst.insert(word);
+    /// }
+    ///
+    /// assert_eq!(bst.in_order(), vec![&"a", &"to", &"four", &"three"]);
+    /// // Words of the same length compare equal.
+    /// assert_eq!(bst.get_by(|word| word.len().cmp(&"five".len())), Some(&"four"));
+    /// ```
+    pub fn with_comparator<F>(cmp: F) -> Self
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    {
+        let mut tree = Self::new();
+        tree.order = Order::custom(cmp);
+        tree
+    }
+
+    /// Creates a new empty `BinarySearchTree` prepared for `capacity` insertions.
+    ///
+    /// With the `pool` feature enabled, `capacity` node allocations are made upfront and
+    /// handed out by the following insertions (see `pooled_nodes`). Without the feature the
+    /// nodes are always allocated on insertion, so this is the same as `new`.
     ///
     /// # Complexity:
-    /// *O*(1) - checks if root is `None`.
-    pub fn is_empty(&self) -> bool {
-        self.root.is_none()
+    /// *O*(capacity) with the `pool` feature, *O*(1) otherwise.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut tree = Self::new();
+        tree.pool = NodePool::with_capacity(capacity);
+        tree
     }
 
-    /// Inserts a `value` into the tree while maintaining tree properties (min/max values).
+    /// Creates a new empty `BinarySearchTree` (an alias for `new`).
+    pub fn empty() -> Self {
+        Self::new()
+    }
+
+    /// Creates a `BinarySearchTree` containing exactly one element, the `value`.
     ///
     /// # Complexity:
-    /// - Average: *O*(log n)
-    /// - Worst: *O*(n) (degenerate/unbalanced trees)
-    /// - Best: *O*(1) (empty tree)
-    pub fn insert(&mut self, value: T) {
-        match (&self.min_value, &self.max_value) {
-            (None, None) => {
-                self.min_value = Some(value.clone());
-                self.max_value = Some(value.clone());
-            }
-            (Some(min), Some(max)) => {
-                if &value < min {
-                    self.min_value = Some(value.clone());
-                }
-                if &value > max {
-                    self.max_value = Some(value.clone());
-                }
+    /// *O*(1)
+    pub fn with_value(value: T) -> Self {
+        let mut tree = Self::new();
+        tree.insert(value);
+        tree
+    }
+
+    /// Creates a `BinarySearchTree` by replaying the recorded operations of the `log` in order,
+    /// reproducing exactly the tree (including its shape) that they have produced.
+    ///
+    /// # Complexity:
+    /// The sum of the complexities of the replayed `insert`s and `remove`s.
+    pub fn from_insertion_log(log: &[Op<T>]) -> Self {
+        let mut tree = Self::new();
+        for op in log {
+            match op {
+                Op::Insert(value) => tree.insert(value.clone()),
+                Op::Remove(value) => tree.remove(value),
             }
-            _ => unreachable!(),
         }
+        tree
+    }
 
-        let mut cursor = &mut self.root;
+    /// Creates a perfectly balanced `BinarySearchTree` out of a slice, taking the middle
+    /// element of every subslice as the root of the corresponding subtree.
+    ///
+    /// The `data` is assumed to be sorted in ascending order and free of duplicates: this is
+    /// not checked, and other input results in a tree that doesn't satisfy its invariants.
+    ///
+    /// # Complexity:
+    /// *O*(n)
+    pub fn from_sorted_slice(data: &[T]) -> Self {
+        let mut tree = Self::new();
+        tree.root = Self::build_balanced(&mut data.iter().cloned(), data.len(), &mut tree.pool);
+        tree.min_value = data.first().cloned();
+        tree.max_value = data.last().cloned();
+        tree
+    }
 
-        while let Some(current_node) = cursor {
-            match value.partial_cmp(&current_node.value) {
-                Some(Ordering::Less) => cursor = &mut current_node.left,
-                Some(Ordering::Greater) => cursor = &mut current_node.right,
-                Some(Ordering::Equal) => return,
-                None => return,
-            }
-        }
+    /// Creates a perfectly balanced `BinarySearchTree` ordered by `cmp` (see `with_comparator`)
+    /// out of a slice, like `from_sorted_slice`.
+    ///
+    /// The `data` is assumed to be sorted in ascending order of `cmp` and free of elements it
+    /// finds equal: this is not checked, and other input results in a tree that doesn't satisfy
+    /// its invariants.
+    ///
+    /// # Complexity:
+    /// *O*(n)
+    pub fn from_sorted_slice_by<F>(data: &[T], cmp: F) -> Self
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    {
+        let mut tree = Self::from_sorted_slice(data);
+        tree.order = Order::custom(cmp);
+        tree
+    }
 
-        *cursor = Some(Box::new(BinaryNode::new(value)));
+    /// Creates a perfectly balanced `BinarySearchTree` ordered by `cmp` (see `with_comparator`)
+    /// out of values in any order. Of the values that `cmp` finds equal only the first one
+    /// is kept.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) - the values are sorted by `cmp`, then the tree is built bottom-up.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// let bst = BinarySearchTree::from_unsorted_by([2, 7, 1, 7, 4], |a: &i32, b: &i32| b.cmp(a));
+    ///
+    /// assert_eq!(bst.in_order(), vec![&7, &4, &2, &1]);
+    /// assert_eq!(bst.successor(&4), Some(&2));
+    /// ```
+    pub fn from_unsorted_by<F>(iter: impl IntoIterator<Item = T>, cmp: F) -> Self
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    {
+        let mut values: Vec<T> = iter.into_iter().collect();
+        // The sort is stable and `dedup_by` drops the later of two neighbors.
+        values.sort_by(&cmp);
+        values.dedup_by(|a, b| cmp(a, b) == Ordering::Equal);
+
+        let mut tree = Self::with_comparator(cmp);
+        tree.min_value = values.first().cloned();
+        tree.max_value = values.last().cloned();
+
+        let len = values.len();
+        tree.root = Self::build_balanced(&mut values.into_iter(), len, &mut tree.pool);
+        tree
     }
 
-    fn pass_and_detach_local_minimum(root: &mut Option<Box<BinaryNode<T>>>) -> Option<T> {
-        if root.is_none() {
-            return None;
+    /// Rebuilds a `BinarySearchTree` of exactly the shape described by a level order sequence
+    /// with `None` markers for absent children (as returned by `level_order_with_nulls_cloned`).
+    ///
+    /// The children of the present nodes are attached in queue order. The sequence is taken
+    /// literally: values are not re-sorted, and a sequence that doesn't describe a valid
+    /// `BinarySearchTree` results in a tree that doesn't satisfy its invariants.
+    /// Values that are not reachable from the root (e.g. the whole sequence if it starts
+    /// with `None`) are dropped.
+    ///
+    /// # Complexity:
+    /// *O*(n)
+    ///
+    /// # Example:
+    ///
+    /// `vec![Some(4), Some(2), Some(5), Some(1), Some(3), None, Some(6)]` gives such a tree
+    /// ```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    /// ```
+    pub fn from_level_order_with_nulls(seq: Vec<Option<T>>) -> Self {
+        let mut nodes: Vec<Option<Box<BinaryNode<T>>>> = seq
+            .into_iter()
+            .map(|value| value.map(|value| Box::new(BinaryNode::new(value))))
+            .collect();
+
+        // Indices of every present node and of its children, in level order.
+        let mut links = Vec::new();
+        let mut queue = VecDeque::new();
+        let mut next = 1;
+
+        if matches!(nodes.first(), Some(Some(_))) {
+            queue.push_back(0);
         }
 
-        if root.as_mut().unwrap().left.is_none() {
-            let node = root.take().unwrap();
-            *root = node.right;
-            return Some(node.value);
+        while let Some(parent) = queue.pop_front() {
+            let present = |index: usize| nodes.get(index).is_some_and(Option::is_some);
+            let left = present(next).then_some(next);
+            let right = present(next + 1).then_some(next + 1);
+            next += 2;
+
+            queue.extend(left);
+            queue.extend(right);
+            links.push((parent, left, right));
         }
 
-        let mut parent = root.as_mut().unwrap();
-        while parent.left.as_ref().unwrap().left.is_some() {
-            parent = parent.left.as_mut().unwrap();
+        // Children always come after their parents, so the tree is assembled bottom-up.
+        for (parent, left, right) in links.into_iter().rev() {
+            let left = left.and_then(|index| nodes[index].take());
+            let right = right.and_then(|index| nodes[index].take());
+
+            let node = nodes[parent].as_mut().unwrap();
+            node.left = left;
+            node.right = right;
+            node.update_size();
         }
 
-        let leftmost = parent.left.take().unwrap();
-        parent.left = leftmost.right;
-        Some(leftmost.value)
+        let mut tree = Self::new();
+        tree.root = nodes.into_iter().next().flatten();
+        tree.min_value = tree.refind_min();
+        tree.max_value = tree.refind_max();
+        tree
     }
 
     /// Removes a `value` from the tree while maintaining tree properties (min/max values).
@@ -84,264 +252,310 @@ impl<T: PartialOrd + Clone> BinarySearchTree<T> {
     /// - Average: *O*(log n)
     /// - Worst: *O*(n) (degenerate/unbalanced trees)
     /// - Best: *O*(1) (leaf node)
-    pub fn remove(&mut self, value: &T)
+    pub fn remove<Q>(&mut self, value: &Q)
     where
-        T: PartialOrd + Clone,
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
     {
-        let mut cursor = &mut self.root;
-
-        while let Some(current) = cursor {
-            match value.partial_cmp(&current.value) {
-                Some(Ordering::Less) => {
-                    cursor = &mut cursor.as_mut().unwrap().left;
-                }
-                Some(Ordering::Greater) => {
-                    cursor = &mut cursor.as_mut().unwrap().right;
-                }
-                Some(Ordering::Equal) => {
-                    match (current.left.as_mut(), current.right.as_mut()) {
-                        (None, None) => *cursor = None,
-                        (Some(_), None) => *cursor = current.left.take(),
-                        (None, Some(_)) => *cursor = current.right.take(),
-                        (Some(_), Some(_)) => {
-                            cursor.as_mut().unwrap().value =
-                                Self::pass_and_detach_local_minimum(&mut current.right).unwrap();
-                        }
-                    }
-                    break;
-                }
-                None => {
-                    break;
-                }
-            }
-        }
-
-        self.min_value = self.refind_min();
-        self.max_value = self.refind_max();
+        self.take(value);
     }
 
-    /// Checks if the tree contains a `value`.
+    /// Removes a `value` from the tree like `remove` and returns the stored element equal to
+    /// it, or `None` if there was no such element.
     ///
     /// # Complexity:
     /// - Average: *O*(log n)
     /// - Worst: *O*(n) (degenerate/unbalanced trees)
-    /// - Best: *O*(1) (root match)
-    pub fn contains(&self, value: &T) -> bool {
-        let mut cursor = &self.root;
+    /// - Best: *O*(1) (leaf node)
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // The subtree sizes along the path may only shrink once the value is known to be present.
+        if !self.contains(value) {
+            return None;
+        }
 
-        while let Some(current_node) = cursor {
-            match value.partial_cmp(&current_node.value) {
-                Some(Ordering::Less) => cursor = &current_node.left,
-                Some(Ordering::Greater) => cursor = &current_node.right,
-                Some(Ordering::Equal) => return true,
-                None => return false,
-            }
+        let removed = Self::unlink(&mut self.root, &mut self.pool, |element| {
+            value.partial_cmp(element.borrow())
+        });
+
+        if let Some(element) = &removed {
+            self.refresh_bounds_after_removal(element);
         }
 
-        false
+        removed
     }
 
-    /// Returns a reference to the minimum element of the tree or `None` if tree is empty.
+    /// Builds a new tree out of the results of `f` applied to the elements (dropping the
+    /// duplicates among them).
+    ///
+    /// Like in `filter_map`, the results are inserted in preorder, so an order-preserving `f`
+    /// reproduces the shape of this tree (inserting them in ascending order would degenerate
+    /// it into a list). Any other `f` still gives a valid BST, but its elements no longer
+    /// correspond to the ones of this tree by position.
     ///
     /// # Complexity:
-    /// *O*(1) (due to storing the minimum element inside the tree structure).
-    pub fn min(&self) -> Option<&T> {
-        self.min_value.as_ref()
+    /// The sum of *O*(n) calls of `f` and the complexities of the `insert`s.
+    pub fn map<U, F>(&self, f: F) -> BinarySearchTree<U>
+    where
+        U: PartialOrd + Clone,
+        F: Fn(&T) -> U,
+    {
+        self.filter_map(|value| Some(f(value)))
     }
 
-    /// Returns a reference to the maximum element of the tree or `None` if tree is empty.
+    /// Builds a new tree out of the `Some` results of `f` applied to the elements, dropping
+    /// the `None`s (and the duplicates among the results).
+    ///
+    /// The results are inserted in the order of a preorder traversal, so an `f` that preserves
+    /// the order of the values and drops none of them reproduces the shape of this tree.
     ///
     /// # Complexity:
-    /// *O*(1) (due to storing the maximum element inside the tree structure).
-    pub fn max(&self) -> Option<&T> {
-        self.max_value.as_ref()
+    /// The sum of *O*(n) calls of `f` and the complexities of the `insert`s.
+    pub fn filter_map<U, F>(&self, f: F) -> BinarySearchTree<U>
+    where
+        U: PartialOrd + Clone,
+        F: Fn(&T) -> Option<U>,
+    {
+        let mut tree = BinarySearchTree::new();
+        self.walk(TraversalOrder::PreOrder, |value| {
+            if let Some(mapped) = f(value) {
+                tree.insert(mapped);
+            }
+        });
+        tree
     }
 
-    /// Each time the tree is updated, you need to re-search for the minimum.
+    /// Checks if the tree contains a `value`.
     ///
-    /// # Complexity
+    /// The `value` may be any borrowed form of the element type (e.g. a `&str` in a tree of
+    /// `String`s), as long as it is ordered the same way as the elements. It is compared by its
+    /// own `PartialOrd`, so a tree built `with_comparator` is searched with `get_by` instead.
+    /// The same holds for `get`, `stored_equal_to`, `ceil`, `floor`, `take` and `remove`
+    /// (see `ceil_by`, `floor_by` and `take_by`).
+    ///
+    /// # Complexity:
     /// - Average: *O*(log n)
     /// - Worst: *O*(n) (degenerate/unbalanced trees)
     /// - Best: *O*(1) (root match)
-    fn refind_min(&self) -> Option<T> {
-        let mut cursor = &self.root;
-
-        while let Some(current_node) = cursor {
-            match current_node.left {
-                Some(_) => cursor = &current_node.left,
-                None => return Some(current_node.value.clone()),
-            }
-        }
-        None
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.stored_equal_to(value).is_some()
     }
 
-    /// Each time the tree is updated, you need to re-search for the maximum.
+    /// Returns a reference to the stored element that compares equal to the `value`,
+    /// or `None` if there is no such element.
+    ///
+    /// Elements are identified by `partial_cmp` alone, so the stored representative may differ
+    /// from the `value` in everything that the ordering ignores (and even by `==`).
     ///
     /// # Complexity:
     /// - Average: *O*(log n)
     /// - Worst: *O*(n) (degenerate/unbalanced trees)
     /// - Best: *O*(1) (root match)
-    fn refind_max(&self) -> Option<T> {
-        let mut cursor = &self.root;
-
-        while let Some(current_node) = cursor {
-            if current_node.right.is_some() {
-                cursor = &current_node.right;
-            } else {
-                return Some(current_node.value.clone());
-            }
-        }
-        None
+    pub fn stored_equal_to<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.find_where(|element| value.partial_cmp(element.borrow()))
     }
 
-    /// Returns the height of the tree (longest path from root to leaf).
+    /// Returns a reference to the stored element that compares equal to the `value`
+    /// (an alias for `stored_equal_to`), for map-like usage where equality is by key.
     ///
     /// # Complexity:
-    /// *O*(n) - visits all nodes.
-    pub fn height(&self) -> usize {
-        if self.root.is_none() {
-            return 0;
-        }
-
-        let mut height = 0;
-        let mut queue = VecDeque::new();
-
-        if let Some(root) = &self.root {
-            queue.push_back(root);
-        }
-
-        while !queue.is_empty() {
-            let level_size = queue.len();
-
-            for _ in 0..level_size {
-                let node = queue.pop_front().unwrap();
-
-                if let Some(left) = &node.left {
-                    queue.push_back(left);
-                }
-                if let Some(right) = &node.right {
-                    queue.push_back(right);
-                }
-            }
-
-            if !queue.is_empty() {
-                height += 1;
-            }
-        }
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.stored_equal_to(value)
+    }
 
-        height
+    /// Recomputes the cached minimum and maximum from the nodes of the tree.
+    ///
+    /// The caches are kept up to date by all operations of the tree, this is a recovery path
+    /// for the cases where the nodes have been changed directly.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn refresh_bounds(&mut self) {
+        self.min_value = self.refind_min();
+        self.max_value = self.refind_max();
     }
 
-    /// Returns references to the elements of the tree in the order of a preorder traversal.
+    /// Returns owned copies of the elements of the tree in the order of a preorder traversal.
     ///
     /// # Complexity:
     /// *O*(n) - visits all nodes.
-    ///
-    /// # Example:
-    ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    ///
-    /// Then the result of this traversal will be like this: `vec![&4, &2, &1, &3, &5, &6]`.
-    pub fn pre_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut stack = Vec::new();
-        let mut current = &self.root;
+    pub fn pre_order_cloned(&self) -> Vec<T> {
+        self.pre_order().into_iter().cloned().collect()
+    }
 
-        while !stack.is_empty() || current.is_some() {
-            while let Some(node) = current {
-                result.push(&node.value);
-                stack.push(node);
-                current = &node.left;
-            }
+    /// Returns owned copies of the elements of the tree in the order of a inorder traversal
+    /// (i.e. sorted in ascending order).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn in_order_cloned(&self) -> Vec<T> {
+        self.in_order().into_iter().cloned().collect()
+    }
 
-            current = &stack.pop().unwrap().right;
-        }
+    /// Returns owned copies of the elements of the tree in the order of a postorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn post_order_cloned(&self) -> Vec<T> {
+        self.post_order().into_iter().cloned().collect()
+    }
 
-        result
+    /// Returns owned copies of the elements of the tree in the order of a level order traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn level_order_cloned(&self) -> Vec<T> {
+        self.level_order().into_iter().cloned().collect()
     }
 
-    /// Returns references to the elements of the tree in the order of a inorder traversal.
+    /// Returns owned copies of the elements of the tree in the order of a level order traversal,
+    /// with `None` markers for absent children (see `level_order_with_nulls`).
     ///
     /// # Complexity:
     /// *O*(n) - visits all nodes.
+    pub fn level_order_with_nulls_cloned(&self) -> Vec<Option<T>> {
+        self.level_order_with_nulls()
+            .into_iter()
+            .map(|value| value.cloned())
+            .collect()
+    }
+
+    /// Builds a balanced `AVLTree` with the elements of the tree (cloned).
     ///
-    /// # Example:
+    /// The elements are already free of duplicates and come out of the tree in ascending order,
+    /// so the sort in `AVLTree::from_unsorted` finishes in a single pass and the AVL tree is
+    /// built bottom-up instead of by `n` insertions. A tree built `with_comparator` is sorted
+    /// again, as the AVL tree orders its elements by `PartialOrd`.
     ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    /// Then the result of this traversal will be like this: `vec![&1, &2, &3, &4, &5, &6]`.
-    pub fn in_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut stack = Vec::new();
-        let mut current = &self.root;
-
-        while !stack.is_empty() || current.is_some() {
-            while let Some(node) = current {
-                stack.push(node);
-                current = &node.left;
-            }
-
-            if let Some(node) = stack.pop() {
-                result.push(&node.value);
-                current = &node.right;
-            }
-        }
+    /// # Complexity:
+    /// *O*(n) (*O*(n log n) for a custom order).
+    pub fn to_avl(&self) -> AVLTree<T> {
+        AVLTree::from_unsorted(self.iter().cloned())
+    }
 
-        result
+    /// Returns a value that is the rounded `value` to the nearest larger in the tree,
+    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
+    /// given `value`).
+    ///
+    /// # Complexity:
+    /// - Best case: *O*(1) - when the value matches the root node
+    /// - Average case: *O*(log n) - for balanced trees
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees
+    pub fn ceil<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.ceil_where(|element| value.partial_cmp(element.borrow()))
     }
 
-    /// Returns references to the elements of the tree in the order of a postorder traversal.
+    /// Returns a value that is the rounded `value` to the nearest smaller in the tree,
+    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
+    /// given `value`).
     ///
     /// # Complexity:
-    /// *O*(n) - visits all nodes.
+    /// - Best case: *O*(1) - when the value matches the root node  
+    /// - Average case: *O*(log n) - for balanced trees  
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees  
+    pub fn floor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.floor_where(|element| value.partial_cmp(element.borrow()))
+    }
+
+    /// Returns a mutable reference to the smallest element `>= value`, like `ceil`, e.g. to update a payload
+    /// that the ordering of the elements ignores. Like in `ceil`, the `value` may be a borrowed
+    /// form of the elements and is compared by its own `PartialOrd`.
     ///
-    /// # Example:
+    /// Changing the element in a way that changes its ordering relative to the other elements
+    /// breaks the BST invariant. The cached `min` and `max` are copies of the extreme
+    /// elements, so they don't reflect changes made through the returned reference.
     ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    /// Then the result of this traversal will be like this: `vec![&1, &3, &2, &6, &5, &4]`.
-    pub fn post_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut stack = Vec::new();
-        let mut current = &self.root;
-        let mut last_visited: Option<&Box<BinaryNode<T>>> = None;
+    /// # Complexity:
+    /// - Average case: *O*(log n) - for balanced trees
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees
+    pub fn ceil_mut<Q>(&mut self, value: &Q) -> Option<&mut T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut result = None;
+        let mut cursor = &mut self.root;
 
-        while !stack.is_empty() || current.is_some() {
-            while let Some(node) = current {
-                stack.push(node);
-                current = &node.left;
+        while let Some(node) = cursor {
+            let BinaryNode {
+                value: element,
+                left,
+                right,
+                ..
+            } = &mut **node;
+
+            match value.partial_cmp((*element).borrow()) {
+                Some(Ordering::Equal) => return Some(element),
+                Some(Ordering::Greater) => cursor = right,
+                _ => {
+                    result = Some(element);
+                    cursor = left;
+                }
             }
+        }
 
-            if let Some(node) = stack.last() {
-                let right_visited = match (&node.right, last_visited) {
-                    (Some(right), Some(last)) => std::ptr::eq(right.as_ref(), last.as_ref()),
-                    _ => false,
-                };
+        result
+    }
 
-                if node.right.is_none() || right_visited {
-                    result.push(&node.value);
-                    last_visited = stack.pop();
-                } else {
-                    current = &node.right;
+    /// Returns a mutable reference to the largest element `<= value`, like `floor`, e.g. to update a payload
+    /// that the ordering of the elements ignores. Like in `floor`, the `value` may be a borrowed
+    /// form of the elements and is compared by its own `PartialOrd`.
+    ///
+    /// Changing the element in a way that changes its ordering relative to the other elements
+    /// breaks the BST invariant. The cached `min` and `max` are copies of the extreme
+    /// elements, so they don't reflect changes made through the returned reference.
+    ///
+    /// # Complexity:
+    /// - Average case: *O*(log n) - for balanced trees
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees
+    pub fn floor_mut<Q>(&mut self, value: &Q) -> Option<&mut T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut result = None;
+        let mut cursor = &mut self.root;
+
+        while let Some(node) = cursor {
+            let BinaryNode {
+                value: element,
+                left,
+                right,
+                ..
+            } = &mut **node;
+
+            match value.partial_cmp((*element).borrow()) {
+                Some(Ordering::Equal) => return Some(element),
+                Some(Ordering::Less) => cursor = left,
+                _ => {
+                    result = Some(element);
+                    cursor = right;
                 }
             }
         }
@@ -349,915 +563,5261 @@ impl<T: PartialOrd + Clone> BinarySearchTree<T> {
         result
     }
 
-    /// Returns references to the elements of the tree in the order of a level order traversal.
+    /// Returns the smallest element of the tree that is greater than or equal to `value`
+    /// (an alias for `ceil`).
+    ///
+    /// The neighbor queries relate to each other like this:
+    /// - `successor_or_equal` / `ceil`: the smallest element `>= value`
+    /// - `predecessor_or_equal` / `floor`: the largest element `<= value`
+    /// - `successor`: the smallest element `> value`
+    /// - `predecessor`: the largest element `< value`
+    ///
+    /// The first two return `value`'s own stored copy if it is present in the tree, the strict
+    /// ones skip it.
     ///
     /// # Complexity:
-    /// *O*(n) - visits all nodes.
+    /// The same as for `ceil`.
     ///
     /// # Example:
     ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
-    ///    2   5
-    ///   / \   \
-    ///  1   3   6
-    ///```
-    /// Then the result of this traversal will be like this: `vec![&4, &2, &5, &1, &3, &6]`.
-    pub fn level_order(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        let mut queue = VecDeque::new();
-
-        if let Some(root) = &self.root {
-            queue.push_back(root);
-        }
-
-        while let Some(node) = queue.pop_front() {
-            result.push(&node.value);
-
-            if let Some(left) = &node.left {
-                queue.push_back(left);
-            }
-            if let Some(right) = &node.right {
-                queue.push_back(right);
-            }
-        }
-
-        result
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// for value in [10, 20, 30] {
+    ///     bst.insert(value);
+    /// }
+    ///
+    /// assert_eq!(bst.successor_or_equal(&15), Some(&20));
+    /// assert_eq!(bst.successor_or_equal(&20), Some(&20));
+    /// assert_eq!(bst.successor_or_equal(&31), None);
+    /// assert_eq!(bst.successor_or_equal(&15), bst.ceil(&15));
+    /// ```
+    pub fn successor_or_equal(&self, value: &T) -> Option<&T> {
+        self.ceil_where(|element| self.order.compare(value, element))
     }
 
-    /// Returns the number of elements of the tree (the number of elements in the vector
-    /// for the preorder traversal).
+    /// Returns the largest element of the tree that is less than or equal to `value`
+    /// (an alias for `floor`, see `successor_or_equal` for the related queries).
     ///
     /// # Complexity:
-    /// *O*(n) - traverses entire tree.
-    pub fn number_of_elements(&self) -> usize {
-        self.pre_order().len()
+    /// The same as for `floor`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// for value in [10, 20, 30] {
+    ///     bst.insert(value);
+    /// }
+    ///
+    /// assert_eq!(bst.predecessor_or_equal(&15), Some(&10));
+    /// assert_eq!(bst.predecessor_or_equal(&20), Some(&20));
+    /// assert_eq!(bst.predecessor_or_equal(&9), None);
+    /// assert_eq!(bst.predecessor_or_equal(&15), bst.floor(&15));
+    /// ```
+    pub fn predecessor_or_equal(&self, value: &T) -> Option<&T> {
+        self.floor_where(|element| self.order.compare(value, element))
     }
 
-    /// Returns a value that is the rounded `value` to the nearest larger in the tree,
-    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
-    /// given `value`).
+    /// Snaps a `value` to the nearest element of the tree at or below it (the `floor`), falling
+    /// back to the minimum when there is no such element (the `value` is below the range).
+    /// Returns `None` only if the tree is empty.
+    ///
+    /// # Example:
+    ///
+    /// For a tree of `10, 20, 30`: `clamp(&5)` is `Some(&10)`, `clamp(&25)` is `Some(&20)`,
+    /// `clamp(&20)` is `Some(&20)` and `clamp(&99)` is `Some(&30)`.
     ///
     /// # Complexity:
-    /// - Best case: *O*(1) - when the value matches the root node
     /// - Average case: *O*(log n) - for balanced trees
     /// - Worst case: *O*(n) - for degenerate/unbalanced trees
-    pub fn ceil(&self, value: &T) -> Option<&T> {
-        self.root.as_ref()?;
+    pub fn clamp(&self, value: &T) -> Option<&T> {
+        self.predecessor_or_equal(value).or_else(|| self.min())
+    }
 
-        let mut result = None;
-        let mut cursor = &self.root;
+    /// Returns the element closest to `value` (the stored `value` itself if present), or `None`
+    /// if the tree is empty. The distance is the difference of the larger and the smaller one;
+    /// if the floor and the ceil of `value` are equally distant, the smaller one (the floor)
+    /// is returned.
+    ///
+    /// # Example:
+    ///
+    /// For a tree of `10, 20, 30`: `closest(&14)` is `Some(&10)`, `closest(&15)` is `Some(&10)`,
+    /// `closest(&16)` is `Some(&20)` and `closest(&99)` is `Some(&30)`.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn closest(&self, value: &T) -> Option<&T>
+    where
+        T: Sub<Output = T>,
+    {
+        self.nearest_k(value, 1).pop()
+    }
 
-        while let Some(node) = cursor {
-            if &node.value == value {
-                return Some(&node.value);
+    /// Returns the (up to) `k` elements closest to `value`, ordered by their distance to it
+    /// (the difference of the larger and the smaller one). Of two equally distant elements
+    /// the smaller one comes first.
+    ///
+    /// The elements below and above `value` are walked outward simultaneously, starting from
+    /// its floor and ceil, so only the returned elements are visited after the first descent.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n + k)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn nearest_k(&self, value: &T, k: usize) -> Vec<&T>
+    where
+        T: Sub<Output = T>,
+    {
+        let distance = |other: &T| {
+            if other > value {
+                other.clone() - value.clone()
+            } else {
+                value.clone() - other.clone()
             }
+        };
 
-            if &node.value < value {
+        // The nodes still to be visited on each side, the nearest to `value` on top.
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+        let mut cursor = &self.root;
+        while let Some(node) = cursor {
+            if self.order.less(&node.value, value) {
+                below.push(node);
                 cursor = &node.right;
             } else {
-                result = Some(&node.value);
+                above.push(node);
                 cursor = &node.left;
             }
         }
 
+        let mut result = Vec::new();
+        while result.len() < k {
+            let take_below = match (below.last(), above.last()) {
+                (Some(b), Some(a)) => distance(&b.value) <= distance(&a.value),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+
+            if take_below {
+                let node = below.pop().unwrap();
+                result.push(&node.value);
+
+                let mut cursor = &node.left;
+                while let Some(next) = cursor {
+                    below.push(next);
+                    cursor = &next.right;
+                }
+            } else {
+                let node = above.pop().unwrap();
+                result.push(&node.value);
+
+                let mut cursor = &node.right;
+                while let Some(next) = cursor {
+                    above.push(next);
+                    cursor = &next.left;
+                }
+            }
+        }
+
         result
     }
+}
 
-    /// Returns a value that is the rounded `value` to the nearest smaller in the tree,
-    /// or returns `None` (if the tree is empty or if such rounding is not possible for this tree and
-    /// given `value`).
+impl<T: PartialOrd, C: Kind<T>> BinarySearchTree<T, C> {
+    /// Creates a new empty tree ordering its elements like this one.
+    fn empty_like(&self) -> Self {
+        BinarySearchTree {
+            root: None,
+            min_value: C::bound(|| None),
+            max_value: C::bound(|| None),
+            pool: NodePool::new(),
+            order: self.order.clone(),
+            kind: PhantomData,
+        }
+    }
+
+    /// Removes all elements from the tree.
+    ///
+    /// With the `pool` feature enabled, the node allocations are kept for reuse by the following
+    /// insertions instead of being returned to the allocator.
     ///
     /// # Complexity:
-    /// - Best case: *O*(1) - when the value matches the root node  
-    /// - Average case: *O*(log n) - for balanced trees  
-    /// - Worst case: *O*(n) - for degenerate/unbalanced trees  
-    pub fn floor(&self, value: &T) -> Option<&T> {
-        self.root.as_ref()?;
+    /// *O*(n) - visits all nodes.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
 
-        let mut result = None;
+        while let Some(node) = stack.pop() {
+            let node = self.pool.release(node);
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+
+        self.min_value = C::bound(|| None);
+        self.max_value = C::bound(|| None);
+    }
+
+    /// Returns the number of node allocations kept for reuse by the following insertions.
+    ///
+    /// The pool is filled by `with_capacity`, `clear` and `remove` and drained by `insert`.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    #[cfg(feature = "pool")]
+    pub fn pooled_nodes(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Inserts a `value` into the tree while maintaining tree properties (min/max values).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (empty tree)
+    pub fn insert(&mut self, value: T) {
+        // The subtree sizes along the path may only grow once the value is known to be new.
+        if self.is_vacant(&value) {
+            self.insert_vacant(value);
+        }
+    }
+
+    /// Returns the entry of the `value`: `Entry::Occupied` with the stored element equal to it,
+    /// or `Entry::Vacant` holding the `value`, which can then be inserted without searching
+    /// for duplicates again (like `HashMap::entry`).
+    ///
+    /// # Panics:
+    /// If the `value` is incomparable to an element on its search path (e.g. `NaN`), as such
+    /// a value can neither be found nor inserted. `try_entry` gives such a value back instead.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    /// use data_forest::binary_search_tree::entry::Entry;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// bst.insert(1.0);
+    ///
+    /// assert!(matches!(bst.entry(1.0), Entry::Occupied(&mut 1.0)));
+    /// assert_eq!(bst.entry(2.0).or_insert(), &mut 2.0);
+    /// ```
+    pub fn entry(&mut self, value: T) -> Entry<'_, T, C> {
+        match self.try_entry(value) {
+            Ok(entry) => entry,
+            Err(_) => panic!("the value is incomparable to an element of the tree"),
+        }
+    }
+
+    /// Returns the entry of the `value` like `entry`, or fails with the `value` given back if
+    /// it is incomparable to an element on its search path.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// bst.insert(1.0);
+    ///
+    /// assert_eq!(bst.try_entry(2.0).unwrap().or_insert(), &mut 2.0);
+    /// assert!(bst.try_entry(f64::NAN).unwrap_err().value.is_nan());
+    /// ```
+    pub fn try_entry(&mut self, value: T) -> Result<Entry<'_, T, C>, IncomparableError<T>> {
         let mut cursor = &self.root;
 
-        while let Some(node) = cursor {
-            if &node.value == value {
-                return Some(&node.value);
+        while let Some(current_node) = cursor {
+            match self.order.compare(&value, &current_node.value) {
+                Some(Ordering::Less) => cursor = &current_node.left,
+                Some(Ordering::Greater) => cursor = &current_node.right,
+                Some(Ordering::Equal) => break,
+                None => return Err(IncomparableError { value }),
             }
+        }
 
-            if &node.value > value {
-                cursor = &node.left;
+        // Like in `insert`, the subtree sizes along the path grow only once the vacant entry
+        // is filled (by linking the value in a second descent).
+        if cursor.is_none() {
+            return Ok(Entry::Vacant(VacantEntry { value, tree: self }));
+        }
+
+        let order = &self.order;
+        let mut cursor = &mut self.root;
+
+        // Only descending borrows the node for the lifetime of the entry.
+        while let Some(current_node) = cursor.as_deref() {
+            let ordering = order.compare(&value, &current_node.value);
+            let current_node = match ordering {
+                Some(Ordering::Less | Ordering::Greater) => cursor.as_mut().unwrap(),
+                _ => break,
+            };
+            if ordering == Some(Ordering::Less) {
+                cursor = &mut current_node.left;
             } else {
-                result = Some(&node.value);
-                cursor = &node.right;
+                cursor = &mut current_node.right;
             }
         }
 
-        result
+        Ok(Entry::Occupied(&mut cursor.as_mut().unwrap().value))
     }
 
-    /// Performs a tree traversal and returns all pairs of connections between nodes.
-    pub fn find_connections(&self) -> Vec<(&T, &T)> {
-        let mut result = Vec::new();
-        let mut queue = std::collections::VecDeque::new();
-
-        if let Some(root) = &self.root {
-            queue.push_back(root);
+    /// Inserts a `value` known to be absent (`is_vacant`) and returns a mutable reference
+    /// to it.
+    pub(super) fn insert_vacant(&mut self, value: T) -> &mut T {
+        // Nothing is cached for an empty tree, so the first value becomes both bounds.
+        if C::cached(&self.min_value).is_none_or(|min| self.order.less(&value, min)) {
+            self.min_value = C::bound(|| Some(&value));
+        }
+        if C::cached(&self.max_value).is_none_or(|max| self.order.less(max, &value)) {
+            self.max_value = C::bound(|| Some(&value));
         }
 
-        while let Some(node) = queue.pop_front() {
-            if let Some(left) = &node.left {
-                queue.push_back(left);
-                result.push((&node.value, &left.value));
+        self.link(value)
+    }
+
+    /// Inserts a `value` like `insert` and returns whether it has been added, i.e. `false` if
+    /// an equal element was already present (or the `value` is incomparable and was skipped).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn try_insert(&mut self, value: T) -> bool {
+        let len = self.len();
+        self.insert(value);
+        self.len() > len
+    }
+
+    /// Inserts a `value` into the tree, replacing the stored element that compares equal to it.
+    ///
+    /// Returns the replaced element, or `None` if the `value` was not present and has been
+    /// inserted (the tree-set analogue of `HashSet::replace`).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    pub fn insert_or_replace(&mut self, value: T) -> Option<T> {
+        let mut cursor = &mut self.root;
+
+        while let Some(current_node) = cursor {
+            match self.order.compare(&value, &current_node.value) {
+                Some(Ordering::Less) => cursor = &mut current_node.left,
+                Some(Ordering::Greater) => cursor = &mut current_node.right,
+                Some(Ordering::Equal) => {
+                    let replaced = std::mem::replace(&mut current_node.value, value);
+
+                    if C::cached(&self.min_value).is_some_and(|min| {
+                        self.order.compare(min, &replaced) == Some(Ordering::Equal)
+                    }) {
+                        self.min_value = C::bound(|| Some(&current_node.value));
+                    }
+                    if C::cached(&self.max_value).is_some_and(|max| {
+                        self.order.compare(max, &replaced) == Some(Ordering::Equal)
+                    }) {
+                        self.max_value = C::bound(|| Some(&current_node.value));
+                    }
+
+                    return Some(replaced);
+                }
+                None => return None,
             }
-            if let Some(right) = &node.right {
-                queue.push_back(right);
-                result.push((&node.value, &right.value));
+        }
+
+        self.insert(value);
+        None
+    }
+
+    /// Updates the cached minimum and maximum after the `removed` element has been taken out
+    /// of the tree: a cache is searched again only if it held that element.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) (extreme element), *O*(1) otherwise
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    fn refresh_bounds_after_removal(&mut self, removed: &T) {
+        if C::cached(&self.min_value)
+            .is_some_and(|min| self.order.compare(min, removed) == Some(Ordering::Equal))
+        {
+            self.min_value = self.refind_min();
+        }
+        if C::cached(&self.max_value)
+            .is_some_and(|max| self.order.compare(max, removed) == Some(Ordering::Equal))
+        {
+            self.max_value = self.refind_max();
+        }
+    }
+
+    /// Removes the minimum element of the tree without returning it.
+    /// Returns whether an element was removed (`false` if the tree is empty).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - only the left spine is visited
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn remove_min(&mut self) -> bool {
+        let removed = Self::pass_and_detach_local_minimum(&mut self.root, &mut self.pool).is_some();
+
+        self.min_value = self.refind_min();
+        if self.root.is_none() {
+            self.max_value = C::bound(|| None);
+        }
+
+        removed
+    }
+
+    /// Removes the maximum element of the tree without returning it.
+    /// Returns whether an element was removed (`false` if the tree is empty).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - only the right spine is visited
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn remove_max(&mut self) -> bool {
+        let removed = Self::pass_and_detach_local_maximum(&mut self.root, &mut self.pool).is_some();
+
+        self.max_value = self.refind_max();
+        if self.root.is_none() {
+            self.min_value = C::bound(|| None);
+        }
+
+        removed
+    }
+
+    /// Removes every value outside of the inclusive range `[lo, hi]`, keeping only the
+    /// values `x` with `lo <= x <= hi`.
+    ///
+    /// The tree is trimmed in place, so the relative shape of the remaining nodes is preserved.
+    /// If `lo > hi` the tree is emptied.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - only the boundary paths are visited
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn retain_range(&mut self, lo: &T, hi: &T) {
+        let order = &self.order;
+        if order.less(hi, lo) {
+            self.root = None;
+        }
+
+        // Descend to the first node inside the range, it becomes the new root.
+        loop {
+            match &mut self.root {
+                Some(node) if order.less(&node.value, lo) => self.root = node.right.take(),
+                Some(node) if order.less(hi, &node.value) => self.root = node.left.take(),
+                _ => break,
+            }
+        }
+
+        if let Some(root) = &mut self.root {
+            // Everything left of the root is <= hi, only the lower bound has to be enforced.
+            Self::trim_below(&mut root.left, lo, order);
+
+            // Everything right of the root is >= lo, only the upper bound has to be enforced.
+            Self::trim_above(&mut root.right, hi, order);
+
+            root.update_size();
+        }
+
+        self.min_value = self.refind_min();
+        self.max_value = self.refind_max();
+    }
+
+    /// Moves all values greater than or equal to `key` into a new tree, leaving the values less
+    /// than `key` in `self` (like `BTreeSet::split_off`).
+    ///
+    /// The tree is cut along the search path of `key`, so the relative shape of the nodes is
+    /// preserved in both parts.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - only the search path of `key` is visited
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn split_off(&mut self, key: &T) -> Self {
+        let order = &self.order;
+        let (less, rest) = Self::split_node(self.root.take(), &|value| order.less(value, key));
+
+        let mut other = self.empty_like();
+        other.root = rest;
+        other.min_value = other.refind_min();
+        other.max_value = other.refind_max();
+
+        self.root = less;
+        self.min_value = self.refind_min();
+        self.max_value = self.refind_max();
+
+        other
+    }
+
+    /// Splits a subtree into the values satisfying `goes_left` and the rest, where `goes_left`
+    /// must hold for a prefix of the in-order sequence.
+    #[allow(clippy::type_complexity)]
+    fn split_node(
+        node: Option<Box<BinaryNode<T>>>,
+        goes_left: &impl Fn(&T) -> bool,
+    ) -> (Option<Box<BinaryNode<T>>>, Option<Box<BinaryNode<T>>>) {
+        let Some(mut node) = node else {
+            return (None, None);
+        };
+
+        if goes_left(&node.value) {
+            let (left, rest) = Self::split_node(node.right.take(), goes_left);
+            node.right = left;
+            node.update_size();
+            (Some(node), rest)
+        } else {
+            let (left, rest) = Self::split_node(node.left.take(), goes_left);
+            node.left = rest;
+            node.update_size();
+            (left, Some(node))
+        }
+    }
+
+    /// Removes all values of the inclusive range `[lo, hi]` from the tree and returns them as
+    /// a new tree.
+    ///
+    /// The tree is cut along the search paths of `lo` and `hi`, and the values outside of the
+    /// range are joined back under the right spine of the lower part, so the relative shape of
+    /// the nodes is preserved. If `lo > hi` nothing is removed.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - only the search paths of `lo` and `hi` are visited
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn drain_range(&mut self, lo: &T, hi: &T) -> Self {
+        let mut drained = self.empty_like();
+        let order = &self.order;
+        if order.less(hi, lo) {
+            return drained;
+        }
+
+        let (less, rest) = Self::split_node(self.root.take(), &|value| order.less(value, lo));
+        let (range, greater) = Self::split_node(rest, &|value| order.less_or_equal(value, hi));
+
+        drained.root = range;
+        drained.min_value = drained.refind_min();
+        drained.max_value = drained.refind_max();
+
+        self.root = Self::join(less, greater);
+        self.min_value = self.refind_min();
+        self.max_value = self.refind_max();
+
+        drained
+    }
+
+    /// Joins two subtrees where every value of `less` is less than every value of `greater`.
+    fn join(
+        less: Option<Box<BinaryNode<T>>>,
+        greater: Option<Box<BinaryNode<T>>>,
+    ) -> Option<Box<BinaryNode<T>>> {
+        let Some(mut node) = less else {
+            return greater;
+        };
+
+        node.right = Self::join(node.right.take(), greater);
+        node.update_size();
+        Some(node)
+    }
+
+    /// Moves all values of `other` into `self`, leaving `other` empty (like `BTreeSet::append`).
+    ///
+    /// If a value is present in both trees, the copy from `self` is kept. The two in-order
+    /// sequences are merged and the result is rebuilt into a balanced tree.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - both trees are visited once.
+    pub fn append(&mut self, other: &mut Self) {
+        let values = Self::merge_sorted(
+            Self::drain_sorted(self.root.take(), &mut self.pool),
+            Self::drain_sorted(other.root.take(), &mut other.pool),
+            &self.order,
+        );
+        self.min_value = C::bound(|| values.first());
+        self.max_value = C::bound(|| values.last());
+
+        let len = values.len();
+        self.root = Self::build_balanced(&mut values.into_iter(), len, &mut self.pool);
+
+        other.min_value = C::bound(|| None);
+        other.max_value = C::bound(|| None);
+    }
+
+    /// Merges two sequences strictly ascending in the `order` into one, keeping the values of
+    /// `left` on ties.
+    fn merge_sorted(left: Vec<T>, right: Vec<T>, order: &Order<T>) -> Vec<T> {
+        let mut result = Vec::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            match order.compare(a, b) {
+                Some(Ordering::Greater) => result.extend(right.next()),
+                Some(Ordering::Equal) => {
+                    right.next();
+                    result.extend(left.next());
+                }
+                _ => result.extend(left.next()),
+            }
+        }
+
+        result.extend(left);
+        result.extend(right);
+        result
+    }
+
+    /// Cuts the values less than `lo` in the `order` off a subtree.
+    fn trim_below(link: &mut Option<Box<BinaryNode<T>>>, lo: &T, order: &Order<T>) {
+        // Every node kept on the boundary path loses everything cut off beneath it.
+        let mut removed = 0;
+        let mut cursor = &*link;
+        while let Some(node) = cursor {
+            if order.less(&node.value, lo) {
+                removed += 1 + BinaryNode::size(&node.left);
+                cursor = &node.right;
+            } else {
+                cursor = &node.left;
+            }
+        }
+
+        let mut cursor = link;
+        while let Some(node) = cursor {
+            if order.less(&node.value, lo) {
+                removed -= 1 + BinaryNode::size(&node.left);
+                *cursor = node.right.take();
+            } else {
+                node.size -= removed;
+                cursor = &mut cursor.as_mut().unwrap().left;
+            }
+        }
+    }
+
+    /// Cuts the values greater than `hi` in the `order` off a subtree.
+    fn trim_above(link: &mut Option<Box<BinaryNode<T>>>, hi: &T, order: &Order<T>) {
+        // Every node kept on the boundary path loses everything cut off beneath it.
+        let mut removed = 0;
+        let mut cursor = &*link;
+        while let Some(node) = cursor {
+            if order.less(hi, &node.value) {
+                removed += 1 + BinaryNode::size(&node.right);
+                cursor = &node.left;
+            } else {
+                cursor = &node.right;
+            }
+        }
+
+        let mut cursor = link;
+        while let Some(node) = cursor {
+            if order.less(hi, &node.value) {
+                removed -= 1 + BinaryNode::size(&node.right);
+                *cursor = node.left.take();
+            } else {
+                node.size -= removed;
+                cursor = &mut cursor.as_mut().unwrap().right;
+            }
+        }
+    }
+
+    /// Rebuilds the tree into a perfectly balanced shape with the same elements, so that its
+    /// height becomes `floor(log2(n))`.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn balance(&mut self) {
+        let values = Self::drain_sorted(self.root.take(), &mut self.pool);
+        let len = values.len();
+        self.root = Self::build_balanced(&mut values.into_iter(), len, &mut self.pool);
+    }
+
+    /// Rebalances the tree in place with the Day–Stout–Warren algorithm, so that its height
+    /// becomes `floor(log2(n))`.
+    ///
+    /// Unlike `balance`, the nodes are only relinked by rotations, never moved out of the tree:
+    /// the tree is first flattened into a "vine" (a right-leaning list) by right rotations and
+    /// then compressed into a complete tree by rounds of left rotations along the vine.
+    ///
+    /// # Complexity:
+    /// *O*(n) time and *O*(1) additional memory.
+    pub fn rebalance(&mut self) {
+        // Tree to vine: rotate right until the current node has no left child, then move on.
+        let mut link = &mut self.root;
+        loop {
+            if link.as_ref().is_some_and(|node| node.left.is_some()) {
+                Self::rotate_right_at(link);
+            } else if let Some(node) = link {
+                link = &mut node.right;
+            } else {
+                break;
+            }
+        }
+
+        // Vine to tree: the nodes beyond the largest complete tree become its bottom level
+        // first, then every round halves the spine.
+        let len = BinaryNode::size(&self.root);
+        let mut spine = (1usize << (usize::BITS - (len + 1).leading_zeros() - 1)) - 1;
+        self.compress(len - spine);
+        while spine > 1 {
+            spine /= 2;
+            self.compress(spine);
+        }
+    }
+
+    /// Performs `count` left rotations along the right spine, at every other node of it.
+    fn compress(&mut self, count: usize) {
+        let mut link = &mut self.root;
+        for _ in 0..count {
+            Self::rotate_left_at(link);
+            link = &mut link.as_mut().unwrap().right;
+        }
+    }
+
+    /// Rotates the subtree at `link` to the right (its left child becomes its root).
+    fn rotate_right_at(link: &mut Option<Box<BinaryNode<T>>>) {
+        let mut node = link.take().unwrap();
+        let mut left = node.left.take().unwrap();
+        node.left = left.right.take();
+        node.update_size();
+        left.right = Some(node);
+        left.update_size();
+        *link = Some(left);
+    }
+
+    /// Rotates the subtree at `link` to the left (its right child becomes its root).
+    fn rotate_left_at(link: &mut Option<Box<BinaryNode<T>>>) {
+        let mut node = link.take().unwrap();
+        let mut right = node.right.take().unwrap();
+        node.right = right.left.take();
+        node.update_size();
+        right.left = Some(node);
+        right.update_size();
+        *link = Some(right);
+    }
+
+    /// Inserts a `value` like `insert`, then rebuilds the tree with `balance` if its height
+    /// exceeds `max_height`. Returns whether the tree has been rebalanced.
+    ///
+    /// This bounds the depth of the tree without switching to a self-balancing one,
+    /// as long as `max_height` is at least `floor(log2(n))` (otherwise every call rebalances).
+    ///
+    /// # Complexity:
+    /// - *O*(n) - the height of the tree is measured after every insertion
+    pub fn insert_with_rebalance_threshold(&mut self, value: T, max_height: usize) -> bool {
+        self.insert(value);
+
+        if self.height() > max_height {
+            self.balance();
+            return true;
+        }
+
+        false
+    }
+
+    /// Builds a perfectly balanced subtree out of the next `len` values of an ascending sequence.
+    fn build_balanced(
+        values: &mut impl Iterator<Item = T>,
+        len: usize,
+        pool: &mut NodePool<BinaryNode<T>>,
+    ) -> Option<Box<BinaryNode<T>>> {
+        if len == 0 {
+            return None;
+        }
+
+        let left = Self::build_balanced(values, len / 2, pool);
+        let mut node = pool.alloc(BinaryNode::new(values.next()?));
+        node.left = left;
+        node.right = Self::build_balanced(values, len - len / 2 - 1, pool);
+        node.update_size();
+
+        Some(node)
+    }
+
+    /// Moves the values out of a subtree in ascending order.
+    fn drain_sorted(
+        root: Option<Box<BinaryNode<T>>>,
+        pool: &mut NodePool<BinaryNode<T>>,
+    ) -> Vec<T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = root;
+
+        while current.is_some() || !stack.is_empty() {
+            while let Some(mut node) = current {
+                current = node.left.take();
+                stack.push(node);
+            }
+
+            if let Some(node) = stack.pop() {
+                let node = pool.release(node);
+                current = node.right;
+                result.push(node.value);
+            }
+        }
+
+        result
+    }
+
+    /// Returns the rank of the `value` (the number of elements less than it) if the tree
+    /// contains it, or `None` otherwise.
+    ///
+    /// Presence and rank are found in a single descent using the subtree sizes of the nodes.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    pub fn find(&self, value: &T) -> Option<usize> {
+        let mut rank = 0;
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            match self.order.compare(value, &current_node.value)? {
+                Ordering::Less => cursor = &current_node.left,
+                Ordering::Greater => {
+                    rank += BinaryNode::size(&current_node.left) + 1;
+                    cursor = &current_node.right;
+                }
+                Ordering::Equal => return Some(rank + BinaryNode::size(&current_node.left)),
+            }
+        }
+
+        None
+    }
+
+    /// Checks if at least one element `x` of the tree lies in the inclusive range `[lo, hi]`
+    /// (`lo <= x <= hi`), stopping at the first one found. If `lo > hi` the result is `false`.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root inside the range)
+    pub fn has_any_in_range(&self, lo: &T, hi: &T) -> bool {
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            if self.order.less(&current_node.value, lo) {
+                cursor = &current_node.right;
+            } else if self.order.less(hi, &current_node.value) {
+                cursor = &current_node.left;
+            } else {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Returns the positions `start..end` in ascending order covered by the elements `x` of
+    /// the inclusive range `[lo, hi]` (`lo <= x <= hi`), so that they are exactly
+    /// `in_order()[start..end]`. An empty intersection (e.g. `lo > hi`) gives `start == end`.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - two descents
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn index_range(&self, lo: &T, hi: &T) -> Range<usize> {
+        let start = self.count_prefix(|value| self.order.less(value, lo));
+        let end = self.count_prefix(|value| self.order.less_or_equal(value, hi));
+        start..end.max(start)
+    }
+
+    /// Returns a lazy iterator over the elements `x` of the inclusive range `[lo, hi]`
+    /// (`lo <= x <= hi`) in ascending order. Subtrees lying entirely outside the bounds are
+    /// never visited. If `lo > hi` the iterator is empty.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n + k), where k is the number of yielded elements
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn range<'a>(&'a self, lo: &'a T, hi: &'a T) -> impl Iterator<Item = &'a T> {
+        // Pushes the left spine of `cursor`, skipping the nodes below `lo` together with their
+        // left subtrees.
+        fn push_spine<'a, T: PartialOrd>(
+            stack: &mut Vec<&'a BinaryNode<T>>,
+            mut cursor: &'a Option<Box<BinaryNode<T>>>,
+            lo: &T,
+            order: &Order<T>,
+        ) {
+            while let Some(node) = cursor {
+                if order.less(&node.value, lo) {
+                    cursor = &node.right;
+                } else {
+                    stack.push(node);
+                    cursor = &node.left;
+                }
+            }
+        }
+
+        let order = &self.order;
+        let mut stack = Vec::new();
+        if order.less_or_equal(lo, hi) {
+            push_spine(&mut stack, &self.root, lo, order);
+        }
+
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            if order.less(hi, &node.value) {
+                stack.clear();
+                return None;
+            }
+            push_spine(&mut stack, &node.right, lo, order);
+            Some(&node.value)
+        })
+    }
+
+    /// Counts the elements satisfying `in_prefix`, which must hold for a prefix of the
+    /// elements in ascending order, descending with the subtree sizes of the nodes.
+    fn count_prefix(&self, in_prefix: impl Fn(&T) -> bool) -> usize {
+        let mut count = 0;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            if in_prefix(&node.value) {
+                count += BinaryNode::size(&node.left) + 1;
+                cursor = &node.right;
+            } else {
+                cursor = &node.left;
+            }
+        }
+
+        count
+    }
+
+    /// Returns a reference to the minimum element of the tree or `None` if tree is empty.
+    ///
+    /// The minimum is the first element in the order defined by `T`, so for a tree of
+    /// `std::cmp::Reverse<U>` it wraps the largest `U`.
+    ///
+    /// # Complexity:
+    /// *O*(1) for a `Cached` tree (due to storing the minimum element inside the tree
+    /// structure). The left spine of an `Uncached` one is walked instead:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn min(&self) -> Option<&T> {
+        C::cached(&self.min_value).or_else(|| self.leftmost())
+    }
+
+    /// Returns a reference to the maximum element of the tree or `None` if tree is empty.
+    ///
+    /// The maximum is the last element in the order defined by `T`, so for a tree of
+    /// `std::cmp::Reverse<U>` it wraps the smallest `U`.
+    ///
+    /// # Complexity:
+    /// *O*(1) for a `Cached` tree (due to storing the maximum element inside the tree
+    /// structure). The right spine of an `Uncached` one is walked instead:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn max(&self) -> Option<&T> {
+        C::cached(&self.max_value).or_else(|| self.rightmost())
+    }
+
+    /// Returns a reference to the value of the root node or `None` if tree is empty.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn root_value(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
+    /// Each time the tree is updated, you need to re-search for the minimum.
+    ///
+    /// # Complexity
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    fn refind_min(&self) -> C::Bound {
+        C::bound(|| {
+            #[cfg(test)]
+            REFINDS.with(|refinds| refinds.set(refinds.get() + 1));
+
+            self.leftmost()
+        })
+    }
+
+    /// Each time the tree is updated, you need to re-search for the maximum.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    fn refind_max(&self) -> C::Bound {
+        C::bound(|| {
+            #[cfg(test)]
+            REFINDS.with(|refinds| refinds.set(refinds.get() + 1));
+
+            self.rightmost()
+        })
+    }
+
+    /// Returns the depth of the node holding the `value`: the number of edges from the root
+    /// to it (the root is at depth 0), or `None` if the tree doesn't contain the `value`.
+    ///
+    /// Unlike `height`, which is the depth of the deepest leaf, only the path to the `value`
+    /// is visited.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    pub fn depth(&self, value: &T) -> Option<usize> {
+        let mut cursor = &self.root;
+        let mut depth = 0;
+
+        while let Some(node) = cursor {
+            match self.order.compare(value, &node.value)? {
+                Ordering::Less => cursor = &node.left,
+                Ordering::Greater => cursor = &node.right,
+                Ordering::Equal => return Some(depth),
+            }
+            depth += 1;
+        }
+
+        None
+    }
+
+    /// Returns the lowest common ancestor of the nodes holding `a` and `b`: the deepest node
+    /// whose subtree contains both of them (one of the two values itself if it is an ancestor
+    /// of the other), or `None` if either value is absent.
+    ///
+    /// The search descends from the root while both values lie on the same side of the
+    /// current node and stops at the node where their paths split.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn lowest_common_ancestor(&self, a: &T, b: &T) -> Option<&T> {
+        self.find_where(|element| self.order.compare(a, element))?;
+        self.find_where(|element| self.order.compare(b, element))?;
+
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            match (
+                self.order.compare(a, &node.value)?,
+                self.order.compare(b, &node.value)?,
+            ) {
+                (Ordering::Less, Ordering::Less) => cursor = &node.left,
+                (Ordering::Greater, Ordering::Greater) => cursor = &node.right,
+                _ => return Some(&node.value),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the height of the tree (longest path from root to leaf).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn height(&self) -> usize {
+        if self.root.is_none() {
+            return 0;
+        }
+
+        let mut height = 0;
+        let mut queue = VecDeque::new();
+
+        if let Some(root) = &self.root {
+            queue.push_back(root);
+        }
+
+        while !queue.is_empty() {
+            let level_size = queue.len();
+
+            for _ in 0..level_size {
+                let node = queue.pop_front().unwrap();
+
+                if let Some(left) = &node.left {
+                    queue.push_back(left);
+                }
+                if let Some(right) = &node.right {
+                    queue.push_back(right);
+                }
+            }
+
+            if !queue.is_empty() {
+                height += 1;
+            }
+        }
+
+        height
+    }
+
+    /// Returns the diameter of the tree: the number of edges on the longest path between
+    /// any two nodes (0 for an empty tree and for a single node).
+    ///
+    /// The longest path passing through a node joins the deepest leaves of its two subtrees,
+    /// so the subtree heights are computed bottom-up in a single postorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn diameter(&self) -> usize {
+        let mut diameter = 0;
+        // The numbers of levels of the finished subtrees, the right one on top.
+        let mut levels = Vec::new();
+        let mut stack: Vec<_> = self.root.iter().map(|node| (node, false)).collect();
+
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                let right = node.right.as_ref().and_then(|_| levels.pop()).unwrap_or(0);
+                let left = node.left.as_ref().and_then(|_| levels.pop()).unwrap_or(0);
+                diameter = diameter.max(left + right);
+                levels.push(left.max(right) + 1);
+            } else {
+                stack.push((node, true));
+                stack.extend(node.right.iter().map(|right| (right, false)));
+                stack.extend(node.left.iter().map(|left| (left, false)));
+            }
+        }
+
+        diameter
+    }
+
+    /// Returns the number of leaves of the tree (nodes without children).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn count_leaves(&self) -> usize {
+        let mut leaves = 0;
+        let mut stack: Vec<_> = self.root.iter().collect();
+
+        while let Some(node) = stack.pop() {
+            if node.left.is_none() && node.right.is_none() {
+                leaves += 1;
+            }
+            stack.extend(&node.left);
+            stack.extend(&node.right);
+        }
+
+        leaves
+    }
+
+    /// Returns the number of internal nodes of the tree (nodes with at least one child).
+    /// Together with `count_leaves` it adds up to `len()`.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes (to count the leaves).
+    pub fn count_internal_nodes(&self) -> usize {
+        self.len() - self.count_leaves()
+    }
+
+    /// Returns how close the tree is to being balanced: the ratio of the minimal possible
+    /// number of levels for its elements, `⌈log2(n + 1)⌉`, to its actual number of levels
+    /// (`height() + 1`).
+    ///
+    /// `1.0` means the tree is as shallow as possible (and for an empty tree), and the score
+    /// approaches `0.0` as the tree degenerates into a chain, signalling that `balance` is due.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes to measure the height.
+    pub fn balance_quality(&self) -> f64 {
+        let len = BinaryNode::size(&self.root);
+        if len == 0 {
+            return 1.0;
+        }
+
+        let ideal_levels = usize::BITS - len.leading_zeros();
+        f64::from(ideal_levels) / (self.height() + 1) as f64
+    }
+
+    /// Returns references to the elements of the tree in the order of a preorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    ///
+    /// Then the result of this traversal will be like this: `vec![&4, &2, &1, &3, &5, &6]`.
+    pub fn pre_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                result.push(&node.value);
+                stack.push(node);
+                current = &node.left;
+            }
+
+            current = &stack.pop().unwrap().right;
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in the order of a inorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    /// Then the result of this traversal will be like this: `vec![&1, &2, &3, &4, &5, &6]`.
+    pub fn in_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+
+            if let Some(node) = stack.pop() {
+                result.push(&node.value);
+                current = &node.right;
+            }
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in descending order (the reverse of
+    /// `in_order`), walking the tree right-first instead of reversing a vector.
+    ///
+    /// `iter().rev()` yields the same elements lazily, without building a vector.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn reverse_in_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.right;
+            }
+
+            if let Some(node) = stack.pop() {
+                result.push(&node.value);
+                current = &node.left;
+            }
+        }
+
+        result
+    }
+
+    /// Calls `f` on the elements of the tree in ascending order using *O*(1) additional memory,
+    /// unlike `in_order` and `iter`, which keep a stack of up to `h` nodes.
+    ///
+    /// Like Morris traversal, this temporarily rewires the tree instead of remembering the path
+    /// to the current node, which is why it needs `&mut self`. A node can't be threaded back
+    /// to from its predecessor while every node owns its children, so the links along the path
+    /// are reversed to point at the parents instead (the Deutsch-Schorr-Waite technique). All
+    /// links are restored before returning, also if `f` panics.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits every node twice at most, and *O*(1) additional memory.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// for value in [2, 1, 3] {
+    ///     bst.insert(value);
+    /// }
+    ///
+    /// let mut walked = Vec::new();
+    /// bst.in_order_morris(|value| walked.push(*value));
+    /// assert_eq!(walked, vec![1, 2, 3]);
+    /// assert_eq!(bst.root_value(), Some(&2));
+    /// ```
+    pub fn in_order_morris<F: FnMut(&T)>(&mut self, mut f: F) {
+        let mut reversal = Reversal {
+            current: self.root.take(),
+            parent: None,
+            root: &mut self.root,
+            order: &self.order,
+        };
+        if reversal.current.is_none() {
+            return;
+        }
+
+        let mut left_visited = false;
+
+        loop {
+            if !left_visited {
+                while reversal.node().left.is_some() {
+                    reversal.descend(true);
+                }
+            }
+
+            f(&reversal.node().value);
+
+            if reversal.node().right.is_some() {
+                reversal.descend(false);
+                left_visited = false;
+                continue;
+            }
+
+            // Climb up to the first ancestor whose left subtree has just been visited.
+            loop {
+                match reversal.ascend() {
+                    Some(true) => break,
+                    Some(false) => {}
+                    None => return,
+                }
+            }
+            left_visited = true;
+        }
+    }
+
+    /// Returns references to the elements of the tree in the order of a postorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    /// Then the result of this traversal will be like this: `vec![&1, &3, &2, &6, &5, &4]`.
+    pub fn post_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+        let mut last_visited: Option<&Box<BinaryNode<T>>> = None;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+
+            if let Some(node) = stack.last() {
+                let right_visited = match (&node.right, last_visited) {
+                    (Some(right), Some(last)) => std::ptr::eq(right.as_ref(), last.as_ref()),
+                    _ => false,
+                };
+
+                if node.right.is_none() || right_visited {
+                    result.push(&node.value);
+                    last_visited = stack.pop();
+                } else {
+                    current = &node.right;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in the order of a level order traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    ///```
+    /// Then the result of this traversal will be like this: `vec![&4, &2, &5, &1, &3, &6]`.
+    pub fn level_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+
+        if let Some(root) = &self.root {
+            queue.push_back(root);
+        }
+
+        while let Some(node) = queue.pop_front() {
+            result.push(&node.value);
+
+            if let Some(left) = &node.left {
+                queue.push_back(left);
+            }
+            if let Some(right) = &node.right {
+                queue.push_back(right);
+            }
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree grouped by depth: the element `i` of
+    /// the result holds the elements at depth `i` (the root at depth 0) from left to right.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is
+    /// `vec![vec![&4], vec![&2, &5], vec![&1, &3, &6]]`.
+    pub fn level_order_grouped(&self) -> Vec<Vec<&T>> {
+        let mut result = Vec::new();
+        let mut level: Vec<&Box<_>> = self.root.iter().collect();
+
+        while !level.is_empty() {
+            result.push(level.iter().map(|node| &node.value).collect());
+            level = level
+                .into_iter()
+                .flat_map(|node| [&node.left, &node.right])
+                .flatten()
+                .collect();
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree grouped by depth like
+    /// `level_order_grouped`, but with every other level (depths 1, 3, ...) reversed,
+    /// i.e. read from right to left.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is
+    /// `vec![vec![&4], vec![&5, &2], vec![&1, &3, &6]]`.
+    pub fn zigzag_level_order(&self) -> Vec<Vec<&T>> {
+        let mut levels = self.level_order_grouped();
+        for level in levels.iter_mut().skip(1).step_by(2) {
+            level.reverse();
+        }
+        levels
+    }
+
+    /// Returns references to the elements of the tree grouped by their horizontal distance
+    /// from the root (a left child is one column to the left of its parent, a right child one
+    /// column to the right), from the leftmost column to the rightmost one. Within a column
+    /// the elements come in level order.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) - visits all nodes, grouping them by column.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is
+    /// `vec![vec![&1], vec![&2], vec![&4, &3], vec![&5], vec![&6]]`.
+    pub fn vertical_order(&self) -> Vec<Vec<&T>> {
+        let mut columns: BTreeMap<isize, Vec<&T>> = BTreeMap::new();
+        let mut queue: VecDeque<_> = self.root.iter().map(|node| (node, 0)).collect();
+
+        while let Some((node, column)) = queue.pop_front() {
+            columns.entry(column).or_default().push(&node.value);
+            queue.extend(node.left.iter().map(|left| (left, column - 1)));
+            queue.extend(node.right.iter().map(|right| (right, column + 1)));
+        }
+
+        columns.into_values().collect()
+    }
+
+    /// Returns the elements seen when looking at the tree from the left: the leftmost element
+    /// of every level, from the root down.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is `vec![&4, &2, &1]`.
+    pub fn left_view(&self) -> Vec<&T> {
+        self.level_order_grouped()
+            .into_iter()
+            .filter_map(|level| level.first().copied())
+            .collect()
+    }
+
+    /// Returns the elements seen when looking at the tree from the right: the rightmost
+    /// element of every level, from the root down.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is `vec![&4, &5, &6]`.
+    pub fn right_view(&self) -> Vec<&T> {
+        self.level_order_grouped()
+            .into_iter()
+            .filter_map(|level| level.last().copied())
+            .collect()
+    }
+
+    /// Returns the elements seen when looking at the tree from above: the topmost element of
+    /// every column of `vertical_order`, from the leftmost column to the rightmost one.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) - see `vertical_order`.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is
+    /// `vec![&1, &2, &4, &5, &6]`.
+    pub fn top_view(&self) -> Vec<&T> {
+        self.vertical_order()
+            .into_iter()
+            .filter_map(|column| column.first().copied())
+            .collect()
+    }
+
+    /// Returns the elements seen when looking at the tree from below: the bottommost element
+    /// of every column of `vertical_order` (the rightmost one if several are equally deep),
+    /// from the leftmost column to the rightmost one.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) - see `vertical_order`.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is
+    /// `vec![&1, &2, &3, &5, &6]`.
+    pub fn bottom_view(&self) -> Vec<&T> {
+        self.vertical_order()
+            .into_iter()
+            .filter_map(|column| column.last().copied())
+            .collect()
+    }
+
+    /// Returns references to the elements of the tree in the order of a level order traversal,
+    /// with `None` markers for the absent children of the present nodes, so that the exact shape
+    /// of the tree can be recovered (the format used by LeetCode).
+    ///
+    /// Trailing `None` markers are omitted, an empty tree gives an empty vector.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    /// ```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    /// ```
+    /// Then the result of this traversal will be like this:
+    /// `vec![Some(&4), Some(&2), Some(&5), Some(&1), Some(&3), None, Some(&6)]`.
+    pub fn level_order_with_nulls(&self) -> Vec<Option<&T>> {
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+
+        if self.root.is_some() {
+            queue.push_back(&self.root);
+        }
+
+        while let Some(link) = queue.pop_front() {
+            match link {
+                Some(node) => {
+                    result.push(Some(&node.value));
+                    queue.push_back(&node.left);
+                    queue.push_back(&node.right);
+                }
+                None => result.push(None),
+            }
+        }
+
+        while matches!(result.last(), Some(None)) {
+            result.pop();
+        }
+
+        result
+    }
+
+    /// Consumes the tree and moves its elements out in the order of a level order traversal,
+    /// without cloning them.
+    ///
+    /// Together with `from_level_order_with_nulls` (using the shape of `level_order_with_nulls`)
+    /// this gives a shape-preserving round trip of owned values.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn into_level_order(self) -> Vec<T> {
+        let mut result = Vec::new();
+        let mut queue: VecDeque<Box<BinaryNode<T>>> = self.root.into_iter().collect();
+
+        while let Some(node) = queue.pop_front() {
+            let node = *node;
+            queue.extend(node.left);
+            queue.extend(node.right);
+            result.push(node.value);
+        }
+
+        result
+    }
+
+    /// Consumes the tree and moves its elements out in ascending order, without cloning them.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        Self::drain_sorted(self.root, &mut self.pool)
+    }
+
+    /// Calls `f` on every element of the tree in the given traversal `order`, without
+    /// collecting the elements into a vector.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn walk<F: FnMut(&T)>(&self, order: TraversalOrder, mut f: F) {
+        let _ = self.try_walk(order, |value| {
+            f(value);
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    /// Calls `f` on every element of the tree in the given traversal `order` (an alias for
+    /// `walk`).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn for_each<F: FnMut(&T)>(&self, order: TraversalOrder, f: F) {
+        self.walk(order, f);
+    }
+
+    /// Calls `f` on the elements of the tree in the given traversal `order` until it returns
+    /// `ControlFlow::Break`, and returns the break value (or `ControlFlow::Continue(())` if
+    /// all elements have been visited).
+    ///
+    /// # Complexity:
+    /// *O*(k) (up to *O*(n)), where `k` is the number of visited elements (plus the nodes
+    /// on the path to the first of them).
+    pub fn try_walk<B, F: FnMut(&T) -> ControlFlow<B>>(
+        &self,
+        order: TraversalOrder,
+        mut f: F,
+    ) -> ControlFlow<B> {
+        match order {
+            TraversalOrder::PreOrder => {
+                let mut stack: Vec<_> = self.root.iter().collect();
+
+                while let Some(node) = stack.pop() {
+                    f(&node.value)?;
+                    stack.extend(&node.right);
+                    stack.extend(&node.left);
+                }
+            }
+            TraversalOrder::InOrder => {
+                let mut stack = Vec::new();
+                let mut current = &self.root;
+
+                while current.is_some() || !stack.is_empty() {
+                    while let Some(node) = current {
+                        stack.push(node);
+                        current = &node.left;
+                    }
+
+                    if let Some(node) = stack.pop() {
+                        f(&node.value)?;
+                        current = &node.right;
+                    }
+                }
+            }
+            TraversalOrder::PostOrder => {
+                // Each node is pushed twice: to expand its children, then to be visited.
+                let mut stack: Vec<_> = self.root.iter().map(|node| (node, false)).collect();
+
+                while let Some((node, expanded)) = stack.pop() {
+                    if expanded {
+                        f(&node.value)?;
+                    } else {
+                        stack.push((node, true));
+                        stack.extend(node.right.iter().map(|right| (right, false)));
+                        stack.extend(node.left.iter().map(|left| (left, false)));
+                    }
+                }
+            }
+            TraversalOrder::LevelOrder => {
+                let mut queue: VecDeque<_> = self.root.iter().collect();
+
+                while let Some(node) = queue.pop_front() {
+                    f(&node.value)?;
+                    queue.extend(&node.left);
+                    queue.extend(&node.right);
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Checks if the elements of the tree in ascending order are equal to the `other` slice,
+    /// which is expected to be sorted.
+    ///
+    /// A shorthand for comparing `in_order()` against a sorted reference in tests.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes (*O*(1) if the lengths differ).
+    pub fn same_elements_as(&self, other: &[T]) -> bool {
+        BinaryNode::size(&self.root) == other.len() && self.in_order().into_iter().eq(other)
+    }
+
+    /// Checks that every element of the tree is comparable to itself and to its neighbors
+    /// in ascending order (`partial_cmp` returns `Some(_)`), e.g. that no `NaN` is stored.
+    ///
+    /// Incomparable values break the ordering that searches rely on, which the BST invariant
+    /// check alone may not notice.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn all_comparable(&self) -> bool {
+        let values = self.in_order();
+
+        values
+            .iter()
+            .all(|value| self.order.compare(value, value).is_some())
+            && values
+                .windows(2)
+                .all(|pair| self.order.compare(pair[0], pair[1]).is_some())
+    }
+
+    /// Walks this tree and the `other` one together in ascending order, yielding every element
+    /// as `DiffItem::Left` (only in `self`), `DiffItem::Right` (only in `other`) or
+    /// `DiffItem::Both` (in both trees).
+    ///
+    /// Elements that are incomparable to the current element of the other tree are yielded
+    /// as `DiffItem::Left` first.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - both trees are visited once.
+    pub fn merge_diff<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = DiffItem<'a, T>> {
+        let mut left = self.in_order().into_iter().peekable();
+        let mut right = other.in_order().into_iter().peekable();
+
+        std::iter::from_fn(move || match (left.peek(), right.peek()) {
+            (Some(a), Some(b)) => match self.order.compare(a, b) {
+                Some(Ordering::Greater) => right.next().map(DiffItem::Right),
+                Some(Ordering::Equal) => Some(DiffItem::Both(left.next()?, right.next()?)),
+                _ => left.next().map(DiffItem::Left),
+            },
+            (Some(_), None) => left.next().map(DiffItem::Left),
+            (None, _) => right.next().map(DiffItem::Right),
+        })
+    }
+
+    /// Checks if every element of the tree is also present in the `other` one.
+    /// The empty tree is a subset of every tree.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - the trees are walked together like in `merge_diff`, stopping at the first
+    /// element missing from `other` (*O*(1) if `self` has more elements than `other`).
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len()
+            && self
+                .merge_diff(other)
+                .all(|item| !matches!(item, DiffItem::Left(_)))
+    }
+
+    /// Checks if every element of the `other` tree is also present in this one.
+    /// Every tree is a superset of the empty tree.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - see `is_subset`.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Checks if the tree and the `other` one have no elements in common.
+    /// The empty tree is disjoint from every tree.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - the trees are walked together like in `merge_diff`, stopping at the first
+    /// common element.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.merge_diff(other)
+            .all(|item| !matches!(item, DiffItem::Both(..)))
+    }
+
+    /// Returns a lazy iterator over the elements of the tree in ascending order, which also
+    /// iterates in descending order from the back (`iter().rev()`).
+    ///
+    /// Unlike `in_order`, no vector of all elements is built, so stopping early is cheap.
+    ///
+    /// # Complexity:
+    /// *O*(h) to create, where `h` is the height of the tree, and amortized *O*(1) per element.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+
+    /// Returns a `Cursor` positioned before the smallest element, which reads the elements
+    /// in ascending order page by page (see `Cursor::next_page`).
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor::new(self)
+    }
+
+    /// Returns the number of elements of the tree.
+    ///
+    /// Kept for compatibility, delegates to [`len`](Self::len).
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn number_of_elements(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns an estimate of the memory used by the tree in bytes: the tree itself plus one
+    /// boxed node allocation per element (and per pooled allocation with the `pool` feature).
+    ///
+    /// Allocator padding and any heap memory owned by the values themselves are not counted.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn approx_memory_bytes(&self) -> usize {
+        let nodes = BinaryNode::size(&self.root) + self.pool.len();
+        size_of::<Self>() + nodes * size_of::<BinaryNode<T>>()
+    }
+
+    /// Returns the smallest element of the tree that is strictly greater than `value`, or `None`
+    /// if there is no such element. Unlike `ceil`, a stored `value` itself is never returned.
+    ///
+    /// # Complexity:
+    /// - Average case: *O*(log n) - for balanced trees
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees
+    pub fn successor(&self, value: &T) -> Option<&T> {
+        let mut result = None;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            if self.order.less(value, &node.value) {
+                result = Some(&node.value);
+                cursor = &node.left;
+            } else {
+                cursor = &node.right;
+            }
+        }
+
+        result
+    }
+
+    /// Returns the largest element of the tree that is strictly less than `value`, or `None`
+    /// if there is no such element. Unlike `floor`, a stored `value` itself is never returned.
+    ///
+    /// # Complexity:
+    /// - Average case: *O*(log n) - for balanced trees
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees
+    pub fn predecessor(&self, value: &T) -> Option<&T> {
+        let mut result = None;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            if self.order.less(&node.value, value) {
+                result = Some(&node.value);
+                cursor = &node.right;
+            } else {
+                cursor = &node.left;
+            }
+        }
+
+        result
+    }
+
+    /// Returns the stored element for which `f` returns `Equal`, or `None` if there is no such
+    /// element.
+    ///
+    /// Like in `slice::binary_search_by`, `f` returns the ordering of an element relative to the
+    /// searched key, so the elements can be looked up by a key that is ordered like the tree,
+    /// e.g. by a field of the elements in a tree built `with_comparator`.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::with_comparator(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+    /// for word in ["ccc", "a", "bb"] {
+    ///     bst.insert(word);
+    /// }
+    ///
+    /// assert_eq!(bst.get_by(|word| word.len().cmp(&2)), Some(&"bb"));
+    /// assert_eq!(bst.get_by(|word| word.len().cmp(&4)), None);
+    /// ```
+    pub fn get_by<F>(&self, f: F) -> Option<&T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        self.find_where(|element| Some(f(element).reverse()))
+    }
+
+    /// Returns the smallest element that is greater than or equal to the key searched by `f`
+    /// (see `get_by`), or `None` if there is no such element.
+    ///
+    /// # Complexity:
+    /// - Average case: *O*(log n) - for balanced trees
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees
+    pub fn ceil_by<F>(&self, f: F) -> Option<&T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        self.ceil_where(|element| Some(f(element).reverse()))
+    }
+
+    /// Returns the largest element that is less than or equal to the key searched by `f`
+    /// (see `get_by`), or `None` if there is no such element.
+    ///
+    /// # Complexity:
+    /// - Average case: *O*(log n) - for balanced trees
+    /// - Worst case: *O*(n) - for degenerate/unbalanced trees
+    pub fn floor_by<F>(&self, f: F) -> Option<&T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        self.floor_where(|element| Some(f(element).reverse()))
+    }
+
+    /// Removes the element for which `f` returns `Equal` (see `get_by`) and returns it, or
+    /// `None` if there was no such element.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn take_by<F>(&mut self, f: F) -> Option<T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        // The subtree sizes along the path may only shrink once the key is known to be present.
+        self.get_by(&f)?;
+
+        let removed = Self::unlink(&mut self.root, &mut self.pool, |element| {
+            Some(f(element).reverse())
+        });
+
+        if let Some(element) = &removed {
+            self.refresh_bounds_after_removal(element);
+        }
+
+        removed
+    }
+
+    /// Performs a tree traversal and returns all pairs of connections between nodes.
+    pub fn find_connections(&self) -> Vec<(&T, &T)> {
+        let mut result = Vec::new();
+        let mut queue = std::collections::VecDeque::new();
+
+        if let Some(root) = &self.root {
+            queue.push_back(root);
+        }
+
+        while let Some(node) = queue.pop_front() {
+            if let Some(left) = &node.left {
+                queue.push_back(left);
+                result.push((&node.value, &left.value));
+            }
+            if let Some(right) = &node.right {
+                queue.push_back(right);
+                result.push((&node.value, &right.value));
+            }
+        }
+
+        result
+    }
+
+    /// Lazily yields all pairs of connections between nodes (parent, child), in the same order
+    /// as `find_connections` (level order, the left child before the right one).
+    ///
+    /// Only the nodes of at most two levels are buffered, so the whole edge list
+    /// is never allocated at once.
+    ///
+    /// # Complexity:
+    /// *O*(n) for the full iteration, amortized *O*(1) per edge.
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T)> {
+        let mut queue: VecDeque<(&T, &BinaryNode<T>)> = VecDeque::new();
+        if let Some(root) = &self.root {
+            for child in [&root.left, &root.right].into_iter().flatten() {
+                queue.push_back((&root.value, child));
+            }
+        }
+
+        std::iter::from_fn(move || {
+            let (parent, node) = queue.pop_front()?;
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back((&node.value, child));
+            }
+            Some((parent, &node.value))
+        })
+    }
+
+    /// Checks if the tree is empty.
+    ///
+    /// # Complexity:
+    /// *O*(1) - checks if root is `None`.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the number of elements of the tree.
+    ///
+    /// The count is read from the subtree size cached in the root, so duplicates rejected by
+    /// `insert` and failed removals never affect it.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn len(&self) -> usize {
+        BinaryNode::size(&self.root)
+    }
+
+    /// Returns a reference to the leftmost (minimum) element or `None` if tree is empty.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    fn leftmost(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.value)
+    }
+
+    /// Returns a reference to the rightmost (maximum) element or `None` if tree is empty.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    fn rightmost(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.value)
+    }
+
+    /// Checks if the `value` can be inserted, i.e. its search ends at an empty link.
+    fn is_vacant(&self, value: &T) -> bool {
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            match self.order.compare(value, &current_node.value) {
+                Some(Ordering::Less) => cursor = &current_node.left,
+                Some(Ordering::Greater) => cursor = &current_node.right,
+                Some(Ordering::Equal) | None => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Returns the element for which `cmp` (the ordering of the searched value relative to an
+    /// element) returns `Equal`, or `None` if there is no such element.
+    fn find_where(&self, cmp: impl Fn(&T) -> Option<Ordering>) -> Option<&T> {
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            match cmp(&current_node.value)? {
+                Ordering::Less => cursor = &current_node.left,
+                Ordering::Greater => cursor = &current_node.right,
+                Ordering::Equal => return Some(&current_node.value),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the smallest element that the searched value is not greater than according to
+    /// `cmp` (see `find_where`), i.e. its ceil.
+    fn ceil_where(&self, cmp: impl Fn(&T) -> Option<Ordering>) -> Option<&T> {
+        let mut result = None;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            match cmp(&node.value) {
+                Some(Ordering::Equal) => return Some(&node.value),
+                Some(Ordering::Greater) => cursor = &node.right,
+                _ => {
+                    result = Some(&node.value);
+                    cursor = &node.left;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns the largest element that the searched value is not less than according to
+    /// `cmp` (see `find_where`), i.e. its floor.
+    fn floor_where(&self, cmp: impl Fn(&T) -> Option<Ordering>) -> Option<&T> {
+        let mut result = None;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            match cmp(&node.value) {
+                Some(Ordering::Equal) => return Some(&node.value),
+                Some(Ordering::Less) => cursor = &node.left,
+                _ => {
+                    result = Some(&node.value);
+                    cursor = &node.right;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Links a `value` known to be absent (`is_vacant`) into the tree and returns a mutable
+    /// reference to it (the min/max caches are left to the caller).
+    fn link(&mut self, value: T) -> &mut T {
+        let mut cursor = &mut self.root;
+
+        while let Some(current_node) = cursor {
+            current_node.size += 1;
+            if self.order.compare(&value, &current_node.value) == Some(Ordering::Less) {
+                cursor = &mut current_node.left;
+            } else {
+                cursor = &mut current_node.right;
+            }
+        }
+
+        &mut cursor.insert(self.pool.alloc(BinaryNode::new(value))).value
+    }
+
+    /// Detaches the node of the element for which `cmp` (the ordering of the searched value
+    /// relative to an element) returns `Equal` and returns that element.
+    ///
+    /// The subtree sizes along the path are decremented, so the element must be present.
+    fn unlink<F>(
+        root: &mut Option<Box<BinaryNode<T>>>,
+        pool: &mut NodePool<BinaryNode<T>>,
+        cmp: F,
+    ) -> Option<T>
+    where
+        F: Fn(&T) -> Option<Ordering>,
+    {
+        let mut removed = None;
+
+        let mut cursor = root;
+
+        while let Some(current) = cursor {
+            match cmp(&current.value) {
+                Some(Ordering::Less) => {
+                    current.size -= 1;
+                    cursor = &mut cursor.as_mut().unwrap().left;
+                }
+                Some(Ordering::Greater) => {
+                    current.size -= 1;
+                    cursor = &mut cursor.as_mut().unwrap().right;
+                }
+                Some(Ordering::Equal) => {
+                    match (current.left.as_mut(), current.right.as_mut()) {
+                        (Some(_), Some(_)) => {
+                            current.size -= 1;
+                            let successor =
+                                Self::pass_and_detach_local_minimum(&mut current.right, pool)
+                                    .unwrap();
+                            removed = Some(std::mem::replace(
+                                &mut cursor.as_mut().unwrap().value,
+                                successor,
+                            ));
+                        }
+                        _ => {
+                            let node = pool.release(cursor.take().unwrap());
+                            removed = Some(node.value);
+                            *cursor = node.left.or(node.right);
+                        }
+                    }
+                    break;
+                }
+                None => {
+                    break;
+                }
+            }
+        }
+
+        removed
+    }
+
+    fn pass_and_detach_local_minimum(
+        root: &mut Option<Box<BinaryNode<T>>>,
+        pool: &mut NodePool<BinaryNode<T>>,
+    ) -> Option<T> {
+        if root.is_none() {
+            return None;
+        }
+
+        if root.as_mut().unwrap().left.is_none() {
+            let node = pool.release(root.take().unwrap());
+            *root = node.right;
+            return Some(node.value);
+        }
+
+        let mut parent = root.as_mut().unwrap();
+        parent.size -= 1;
+        while parent.left.as_ref().unwrap().left.is_some() {
+            parent = parent.left.as_mut().unwrap();
+            parent.size -= 1;
+        }
+
+        let leftmost = pool.release(parent.left.take().unwrap());
+        parent.left = leftmost.right;
+        Some(leftmost.value)
+    }
+
+    /// Detaches the local maximum of a subtree and returns its value (the mirror
+    /// of `pass_and_detach_local_minimum`).
+    fn pass_and_detach_local_maximum(
+        root: &mut Option<Box<BinaryNode<T>>>,
+        pool: &mut NodePool<BinaryNode<T>>,
+    ) -> Option<T> {
+        if root.is_none() {
+            return None;
+        }
+
+        if root.as_mut().unwrap().right.is_none() {
+            let node = pool.release(root.take().unwrap());
+            *root = node.left;
+            return Some(node.value);
+        }
+
+        let mut parent = root.as_mut().unwrap();
+        parent.size -= 1;
+        while parent.right.as_ref().unwrap().right.is_some() {
+            parent = parent.right.as_mut().unwrap();
+            parent.size -= 1;
+        }
+
+        let rightmost = pool.release(parent.right.take().unwrap());
+        parent.right = rightmost.left;
+        Some(rightmost.value)
+    }
+}
+
+impl<T: PartialOrd> BinarySearchTree<T, Uncached> {
+    /// Creates a new empty `BinarySearchTree` that doesn't cache its minimum and maximum, so
+    /// the elements don't have to be `Clone` (e.g. large or unique payloads).
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// // Deliberately not `Clone`.
+    /// #[derive(Debug, PartialEq, PartialOrd)]
+    /// struct Payload(u32);
+    ///
+    /// let mut bst = BinarySearchTree::new_uncached();
+    /// bst.insert(Payload(2));
+    /// bst.insert(Payload(1));
+    ///
+    /// assert!(bst.contains(&Payload(1)));
+    /// assert_eq!(bst.min(), Some(&Payload(1)));
+    /// ```
+    pub fn new_uncached() -> Self {
+        BinarySearchTree {
+            root: None,
+            min_value: (),
+            max_value: (),
+            pool: NodePool::new(),
+            order: Order::Natural,
+            kind: PhantomData,
+        }
+    }
+
+    /// Creates a new empty `BinarySearchTree` that doesn't cache its minimum and maximum (see
+    /// `new_uncached`), ordering its elements with `cmp` (see `with_comparator`).
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::binary_search_tree::BinarySearchTree;
+    ///
+    /// // Deliberately not `Clone`.
+    /// #[derive(Debug, PartialEq, PartialOrd)]
+    /// struct Task(u32, &'static str);
+    ///
+    /// let mut bst = BinarySearchTree::new_uncached_with_comparator(|a: &Task, b: &Task| b.0.cmp(&a.0));
+    /// bst.insert(Task(1, "later"));
+    /// bst.insert(Task(5, "now"));
+    ///
+    /// assert_eq!(bst.min(), Some(&Task(5, "now")));
+    /// assert_eq!(bst.get(&Task(1, "")), Some(&Task(1, "later")));
+    /// ```
+    pub fn new_uncached_with_comparator<F>(cmp: F) -> Self
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    {
+        let mut tree = Self::new_uncached();
+        tree.order = Order::custom(cmp);
+        tree
+    }
+
+    /// Checks if the tree contains an element equal to the `value`.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    pub fn contains(&self, value: &T) -> bool {
+        self.get(value).is_some()
+    }
+
+    /// Returns a reference to the stored element that compares equal to the `value`,
+    /// or `None` if there is no such element.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    pub fn get(&self, value: &T) -> Option<&T> {
+        self.find_where(|element| self.order.compare(value, element))
+    }
+
+    /// Returns the smallest element of the tree that is greater than or equal to `value`,
+    /// or `None` if there is no such element.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    pub fn ceil(&self, value: &T) -> Option<&T> {
+        self.ceil_where(|element| self.order.compare(value, element))
+    }
+
+    /// Returns the largest element of the tree that is less than or equal to `value`,
+    /// or `None` if there is no such element.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (root match)
+    pub fn floor(&self, value: &T) -> Option<&T> {
+        self.floor_where(|element| self.order.compare(value, element))
+    }
+
+    /// Removes a `value` from the tree.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (leaf node)
+    pub fn remove(&mut self, value: &T) {
+        self.take(value);
+    }
+
+    /// Removes a `value` from the tree like `remove` and returns the stored element equal to
+    /// it, or `None` if there was no such element.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (leaf node)
+    pub fn take(&mut self, value: &T) -> Option<T> {
+        // The subtree sizes along the path may only shrink once the value is known to be present.
+        self.get(value)?;
+
+        let order = &self.order;
+        Self::unlink(&mut self.root, &mut self.pool, |element| {
+            order.compare(value, element)
+        })
+    }
+}
+
+impl<T: PartialOrd + Clone> Default for BinarySearchTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two trees are equal if they hold the same elements, regardless of their shape
+/// (and so of the order in which the elements were inserted).
+impl<T: PartialOrd + Clone> PartialEq for BinarySearchTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.in_order() == other.in_order()
+    }
+}
+
+impl<T: PartialOrd + Clone + Eq> Eq for BinarySearchTree<T> {}
+
+/// Builds the tree out of `values` inserted in order, failing on the first value that is
+/// already present instead of silently skipping it.
+impl<T: PartialOrd + Clone> TryFrom<Vec<T>> for BinarySearchTree<T> {
+    type Error = DuplicateError<T>;
+
+    fn try_from(values: Vec<T>) -> Result<Self, Self::Error> {
+        let mut tree = Self::new();
+
+        for value in values {
+            if tree.contains(&value) {
+                return Err(DuplicateError { value });
+            }
+            tree.insert(value);
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<T: PartialOrd + Clone + LeBytes> BinarySearchTree<T> {
+    /// Serializes the tree as the number of its elements (a little-endian `u64`) followed by
+    /// the little-endian bytes of the elements in ascending order (the order of the tree, so
+    /// a tree built `with_comparator` is restored with `from_bytes_with_comparator`).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytes::encode(self.in_order().into_iter())
+    }
+
+    /// Deserializes a tree serialized with `to_bytes`, rebuilding it balanced.
+    ///
+    /// Fails if the length prefix is missing or does not match the data, or if the elements
+    /// are not strictly ascending.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is built bottom-up from the sorted sequence.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DecodeError> {
+        Self::from_bytes_in(data, Order::Natural)
+    }
+
+    /// Deserializes a tree built `with_comparator(cmp)` and serialized with `to_bytes` like
+    /// `from_bytes`, requiring the elements to be strictly ascending in the order of `cmp`.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is built bottom-up from the sorted sequence.
+    pub fn from_bytes_with_comparator<F>(data: &[u8], cmp: F) -> Result<Self, DecodeError>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    {
+        Self::from_bytes_in(data, Order::custom(cmp))
+    }
+
+    /// Deserializes a tree of the given `order`.
+    fn from_bytes_in(data: &[u8], order: Order<T>) -> Result<Self, DecodeError> {
+        let values = bytes::decode_ascending(data, &order)?;
+
+        let mut tree = Self::new();
+        tree.order = order;
+        tree.min_value = values.first().cloned();
+        tree.max_value = values.last().cloned();
+
+        let len = values.len();
+        tree.root = Self::build_balanced(&mut values.into_iter(), len, &mut tree.pool);
+        Ok(tree)
+    }
+}
+
+/// Formats the tree as a set of its elements in ascending order, e.g. `{1, 2, 3}`.
+///
+/// The alternate form (`{:#}`) draws the tree sideways instead, for debugging: one node per
+/// line with the root at the left margin, every level indented by four more spaces and the
+/// right subtree above its parent, or `<empty>` for an empty tree.
+///
+/// # Example:
+///
+/// A tree of `2, 1, 3` is formatted by `{}` and `{:#}` like this:
+/// ```text
+/// {1, 2, 3}
+///
+///     3
+/// 2
+///     1
+/// ```
+impl<T: PartialOrd + Clone + fmt::Display> fmt::Display for BinarySearchTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_sideways(f);
+        }
+
+        write!(f, "{{")?;
+        for (i, value) in self.in_order().into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value}")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<T: PartialOrd + Clone + fmt::Display> BinarySearchTree<T> {
+    /// Draws the tree sideways (the alternate form of `Display`).
+    fn fmt_sideways(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.root.is_none() {
+            return write!(f, "<empty>");
+        }
+
+        // A reverse in-order traversal puts the right subtree above its parent.
+        let mut stack = Vec::new();
+        let mut current = (&self.root, 0);
+        let mut separator = "";
+
+        loop {
+            while let (Some(node), depth) = current {
+                stack.push((node, depth));
+                current = (&node.right, depth + 1);
+            }
+
+            let Some((node, depth)) = stack.pop() else {
+                break;
+            };
+            let indent = 4 * depth;
+            write!(f, "{separator}{:indent$}{}", "", node.value)?;
+            separator = "\n";
+            current = (&node.left, depth + 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// The state of `in_order_morris`: the subtree being visited, with all its links intact, and
+/// the chain of its ancestors, each with the link towards the subtree pointing at its own
+/// parent instead. Dropping the state (also when unwinding) restores the links of the tree.
+struct Reversal<'a, T: PartialOrd> {
+    root: &'a mut Option<Box<BinaryNode<T>>>,
+    order: &'a Order<T>,
+    current: Option<Box<BinaryNode<T>>>,
+    parent: Option<Box<BinaryNode<T>>>,
+}
+
+impl<T: PartialOrd> Reversal<'_, T> {
+    /// Returns the root of the current subtree.
+    fn node(&self) -> &BinaryNode<T> {
+        self.current.as_ref().unwrap()
+    }
+
+    /// Moves down to the left (or right) child of the current node, reversing the link.
+    fn descend(&mut self, left: bool) {
+        let mut node = self.current.take().unwrap();
+        let link = if left {
+            &mut node.left
+        } else {
+            &mut node.right
+        };
+        self.current = std::mem::replace(link, self.parent.take());
+        self.parent = Some(node);
+    }
+
+    /// Moves up to the parent of the current subtree, restoring the link. Returns whether the
+    /// subtree was the left one, or `None` at the root of the tree.
+    fn ascend(&mut self) -> Option<bool> {
+        // The elements are distinct, so the side of the subtree follows from the ordering.
+        let parent = self.parent.as_ref()?;
+        let left = self.order.less(&self.node().value, &parent.value);
+        let mut node = self.parent.take().unwrap();
+        let link = if left {
+            &mut node.left
+        } else {
+            &mut node.right
+        };
+        self.parent = std::mem::replace(link, self.current.take());
+        self.current = Some(node);
+        Some(left)
+    }
+}
+
+impl<T: PartialOrd> Drop for Reversal<'_, T> {
+    fn drop(&mut self) {
+        while self.ascend().is_some() {}
+        *self.root = self.current.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+
+    #[test]
+    fn is_and_isnt_empty_tree() {
+        let bst_1 = BinarySearchTree::<i32>::new();
+        assert!(bst_1.is_empty());
+
+        let mut bst_2 = BinarySearchTree::<i32>::new();
+        bst_2.insert(42);
+        assert!(!bst_2.is_empty());
+    }
+
+    #[test]
+    fn contains_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert!(!bst.contains(&0));
+    }
+
+    #[test]
+    fn contains_in_single_node_tree() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(1);
+
+        assert!(bst.contains(&1));
+    }
+
+    #[test]
+    fn contains_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+        }
+
+        for i in 0..=10 {
+            assert!(bst_degenerate_right.contains(&i));
+            assert!(bst_degenerate_left.contains(&i));
+        }
+    }
+
+    #[test]
+    fn contains_basic() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            bst_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            bst_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            bst_diff_heights_two.insert(value);
+        }
+
+        for value in &values_1 {
+            assert!(bst_diff_heights_null.contains(&value));
+        }
+        for value in &values_2 {
+            assert!(bst_diff_heights_one.contains(&value));
+        }
+        for value in &values_3 {
+            assert!(bst_diff_heights_two.contains(&value));
+        }
+    }
+
+    #[test]
+    fn remove_from_empty_tree() {
+        let mut bst = BinarySearchTree::<i32>::new();
+
+        bst.remove(&42);
+
+        assert!(!bst.contains(&42));
+        assert_eq!(bst.min(), None);
+        assert_eq!(bst.max(), None);
+    }
+
+    #[test]
+    fn remove_from_single_node_tree_check_min_max_updating() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.max() == bst.min() && bst.max().is_none());
+
+        bst.insert(1);
+        assert!(bst.max() == bst.min() && bst.max() == Some(&1));
+        assert!(bst.contains(&1));
+
+        bst.remove(&1);
+        assert!(bst.max() == bst.min() && bst.max().is_none());
+        assert!(!bst.contains(&1));
+    }
+
+    #[test]
+    fn remove_from_degenerate_trees_check_min_max_updating() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+            assert_eq!(bst_degenerate_right.min(), Some(&0));
+            assert_eq!(bst_degenerate_right.max(), Some(&i));
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+            assert_eq!(bst_degenerate_left.min(), Some(&i));
+            assert_eq!(bst_degenerate_left.max(), Some(&10));
+        }
+
+        for i in 0..=10 {
+            bst_degenerate_right.remove(&i);
+            assert!(!bst_degenerate_right.contains(&i));
+
+            bst_degenerate_left.remove(&i);
+            assert!(!bst_degenerate_left.contains(&i));
+
+            if i < 10 {
+                assert_eq!(bst_degenerate_right.min(), Some(&(i + 1)));
+                assert_eq!(bst_degenerate_right.max(), Some(&10));
+
+                assert_eq!(bst_degenerate_left.min(), Some(&(i + 1)));
+                assert_eq!(bst_degenerate_left.max(), Some(&10));
+            } else {
+                assert_eq!(bst_degenerate_right.min(), None);
+                assert_eq!(bst_degenerate_right.max(), None);
+
+                assert_eq!(bst_degenerate_left.min(), None);
+                assert_eq!(bst_degenerate_left.max(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn remove_basic_check_min_max_updating() {
+        let mut bst1 = BinarySearchTree::new();
+        let mut bst2 = BinarySearchTree::new();
+        let mut bst3 = BinarySearchTree::new();
+
+        let values1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values2 = vec![4, 2, 6, 1, 3, 5];
+        let values3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+
+        for &v in &values1 {
+            bst1.insert(v);
+        }
+        for &v in &values2 {
+            bst2.insert(v);
+        }
+        for &v in &values3 {
+            bst3.insert(v);
+        }
+
+        let mut remaining1 = values1.clone();
+        let mut remaining2 = values2.clone();
+        let mut remaining3 = values3.clone();
+
+        for &v in &values1 {
+            bst1.remove(&v);
+            remaining1.retain(|&x| x != v);
+
+            assert!(!bst1.contains(&v));
+            assert_eq!(bst1.min(), remaining1.iter().min());
+            assert_eq!(bst1.max(), remaining1.iter().max());
+        }
+
+        for &v in &values2 {
+            bst2.remove(&v);
+            remaining2.retain(|&x| x != v);
+
+            assert!(!bst2.contains(&v));
+            assert_eq!(bst2.min(), remaining2.iter().min());
+            assert_eq!(bst2.max(), remaining2.iter().max());
+        }
+
+        for &v in &values3 {
+            bst3.remove(&v);
+            remaining3.retain(|&x| x != v);
+
+            assert!(!bst3.contains(&v));
+            assert_eq!(bst3.min(), remaining3.iter().min());
+            assert_eq!(bst3.max(), remaining3.iter().max());
+        }
+    }
+
+    #[test]
+    fn min_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.min(), None);
+    }
+
+    #[test]
+    fn min_in_degenerate_trees_check_updating() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+            assert_eq!(bst_degenerate_right.min(), Some(&0));
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+            assert_eq!(bst_degenerate_left.min(), Some(&i));
+        }
+    }
+
+    #[test]
+    fn min_basic_check_updating() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+
+        let mut current_min_1 = None;
+        let mut current_min_2 = None;
+        let mut current_min_3 = None;
+
+        for value in &values_1 {
+            bst_diff_heights_null.insert(*value);
+            current_min_1 = Some(*value)
+                .filter(|&x| current_min_1.is_none_or(|min| x < min))
+                .or(current_min_1);
+            assert_eq!(bst_diff_heights_null.min(), current_min_1.as_ref());
+        }
+
+        for value in &values_2 {
+            bst_diff_heights_one.insert(*value);
+            current_min_2 = Some(*value)
+                .filter(|&x| current_min_2.is_none_or(|min| x < min))
+                .or(current_min_2);
+            assert_eq!(bst_diff_heights_one.min(), current_min_2.as_ref());
+        }
+
+        for value in &values_3 {
+            bst_diff_heights_two.insert(*value);
+            current_min_3 = Some(*value)
+                .filter(|&x| current_min_3.is_none_or(|min| x < min))
+                .or(current_min_3);
+            assert_eq!(bst_diff_heights_two.min(), current_min_3.as_ref());
+        }
+    }
+
+    #[test]
+    fn max_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.max(), None);
+    }
+
+    #[test]
+    fn max_in_degenerate_trees_check_updating() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+            assert_eq!(bst_degenerate_right.max(), Some(&i));
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+            assert_eq!(bst_degenerate_left.max(), Some(&10));
+        }
+    }
+
+    #[test]
+    fn max_basic_check_updating() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+
+        let mut current_max_1 = None;
+        let mut current_max_2 = None;
+        let mut current_max_3 = None;
+
+        for value in &values_1 {
+            bst_diff_heights_null.insert(*value);
+            current_max_1 = Some(*value)
+                .filter(|&x| current_max_1.is_none_or(|max| x > max))
+                .or(current_max_1);
+            assert_eq!(bst_diff_heights_null.max(), current_max_1.as_ref());
+        }
+
+        for value in &values_2 {
+            bst_diff_heights_one.insert(*value);
+            current_max_2 = Some(*value)
+                .filter(|&x| current_max_2.is_none_or(|max| x > max))
+                .or(current_max_2);
+            assert_eq!(bst_diff_heights_one.max(), current_max_2.as_ref());
+        }
+
+        for value in &values_3 {
+            bst_diff_heights_two.insert(*value);
+            current_max_3 = Some(*value)
+                .filter(|&x| current_max_3.is_none_or(|max| x > max))
+                .or(current_max_3);
+            assert_eq!(bst_diff_heights_two.max(), current_max_3.as_ref());
+        }
+    }
+
+    #[test]
+    fn max_min_are_similar_for_single_element_tree() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(1);
+
+        assert!(bst.min() == bst.max() && bst.min() == Some(&1));
+    }
+
+    #[test]
+    fn height_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.height(), 0);
+    }
+
+    #[test]
+    fn height_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+        }
+
+        assert_eq!(bst_degenerate_right.height(), 10);
+        assert_eq!(bst_degenerate_left.height(), 10);
+    }
+
+    #[test]
+    fn height_basic() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            bst_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            bst_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            bst_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(bst_diff_heights_null.height(), 2);
+        assert_eq!(bst_diff_heights_one.height(), 2);
+        assert_eq!(bst_diff_heights_two.height(), 3);
+    }
+
+    #[test]
+    fn pre_order_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.pre_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn pre_order_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+        }
+
+        assert_eq!(
+            bst_degenerate_right.pre_order(),
+            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
+        );
+        assert_eq!(
+            bst_degenerate_left.pre_order(),
+            vec![&10, &9, &8, &7, &6, &5, &4, &3, &2, &1, &0]
+        );
+    }
+
+    #[test]
+    fn pre_order_basic() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            bst_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            bst_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            bst_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            bst_diff_heights_null.pre_order(),
+            vec![&&5, &&3, &&2, &&4, &&7, &&6, &&8]
+        );
+
+        assert_eq!(
+            bst_diff_heights_one.pre_order(),
+            vec![&&4, &&2, &&1, &&3, &&6, &&5]
+        );
+
+        assert_eq!(
+            bst_diff_heights_two.pre_order(),
+            vec![&&8, &&4, &&2, &&1, &&6, &&7, &&12, &&10]
+        );
+    }
+
+    #[test]
+    fn in_order_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.in_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn in_order_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+        }
+
+        assert_eq!(
+            bst_degenerate_right.in_order(),
+            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
+        );
+        assert_eq!(
+            bst_degenerate_left.in_order(),
+            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
+        );
+    }
+
+    #[test]
+    fn in_order_basic() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            bst_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            bst_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            bst_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            bst_diff_heights_null.in_order(),
+            vec![&&2, &&3, &&4, &&5, &&6, &&7, &&8]
+        );
+
+        assert_eq!(
+            bst_diff_heights_one.in_order(),
+            vec![&&1, &&2, &&3, &&4, &&5, &&6]
+        );
+
+        assert_eq!(
+            bst_diff_heights_two.in_order(),
+            vec![&&1, &&2, &&4, &&6, &&7, &&8, &&10, &&12]
+        );
+    }
+
+    #[test]
+    fn post_order_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.post_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn post_order_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+        }
+
+        assert_eq!(
+            bst_degenerate_right.post_order(),
+            vec![&10, &9, &8, &7, &6, &5, &4, &3, &2, &1, &0]
+        );
+        assert_eq!(
+            bst_degenerate_left.post_order(),
+            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
+        );
+    }
+
+    #[test]
+    fn post_order_basic() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            bst_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            bst_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            bst_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            bst_diff_heights_null.post_order(),
+            vec![&&2, &&4, &&3, &&6, &&8, &&7, &&5]
+        );
+
+        assert_eq!(
+            bst_diff_heights_one.post_order(),
+            vec![&&1, &&3, &&2, &&5, &&6, &&4]
+        );
+
+        assert_eq!(
+            bst_diff_heights_two.post_order(),
+            vec![&&1, &&2, &&7, &&6, &&4, &&10, &&12, &&8]
+        );
+    }
+
+    #[test]
+    fn level_order_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.level_order(), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn level_order_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+        }
+
+        assert_eq!(
+            bst_degenerate_right.level_order(),
+            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
+        );
+        assert_eq!(
+            bst_degenerate_left.level_order(),
+            vec![&10, &9, &8, &7, &6, &5, &4, &3, &2, &1, &0]
+        );
+    }
+
+    #[test]
+    fn level_order_basic() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            bst_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            bst_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            bst_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(
+            bst_diff_heights_null.level_order(),
+            vec![&&5, &&3, &&7, &&2, &&4, &&6, &&8]
+        );
+
+        assert_eq!(
+            bst_diff_heights_one.level_order(),
+            vec![&&4, &&2, &&6, &&1, &&3, &&5]
+        );
+
+        assert_eq!(
+            bst_diff_heights_two.level_order(),
+            vec![&&8, &&4, &&12, &&2, &&6, &&10, &&1, &&7]
+        );
+    }
+
+    #[test]
+    fn number_of_elements_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.number_of_elements(), 0);
+    }
+
+    #[test]
+    fn number_of_elements_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            bst_degenerate_right.insert(i);
+        }
+        for i in (0..=10).rev() {
+            bst_degenerate_left.insert(i);
+        }
+
+        assert_eq!(bst_degenerate_right.number_of_elements(), 10 + 1);
+        assert_eq!(bst_degenerate_left.number_of_elements(), 10 + 1);
+    }
+
+    #[test]
+    fn number_of_elements_basic() {
+        let mut bst_diff_heights_null = BinarySearchTree::new();
+        let mut bst_diff_heights_one = BinarySearchTree::new();
+        let mut bst_diff_heights_two = BinarySearchTree::new();
+
+        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
+        let values_2 = vec![4, 2, 6, 1, 3, 5];
+        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in &values_1 {
+            bst_diff_heights_null.insert(value);
+        }
+        for value in &values_2 {
+            bst_diff_heights_one.insert(value);
+        }
+        for value in &values_3 {
+            bst_diff_heights_two.insert(value);
+        }
+
+        assert_eq!(bst_diff_heights_null.number_of_elements(), values_1.len());
+        assert_eq!(bst_diff_heights_one.number_of_elements(), values_2.len());
+        assert_eq!(bst_diff_heights_two.number_of_elements(), values_3.len());
+    }
+
+    #[test]
+    fn ceil_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.ceil(&0), None);
+    }
+
+    #[test]
+    fn ceil_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            let val = i as f64 / 10.0;
+            bst_degenerate_right.insert(val);
+        }
+
+        for i in (0..=10).rev() {
+            let val = i as f64 / 10.0;
+            bst_degenerate_left.insert(val);
+        }
+
+        assert_eq!(bst_degenerate_right.ceil(&0.0), Some(&0.0));
+        assert_eq!(bst_degenerate_left.ceil(&0.0), Some(&0.0));
+
+        assert_eq!(bst_degenerate_right.ceil(&(0.03)), Some(&0.1));
+        assert_eq!(bst_degenerate_left.ceil(&(0.03)), Some(&0.1));
+        assert_eq!(bst_degenerate_right.ceil(&(0.07)), Some(&0.1));
+        assert_eq!(bst_degenerate_left.ceil(&(0.07)), Some(&0.1));
+
+        assert_eq!(bst_degenerate_right.ceil(&1.1), None);
+        assert_eq!(bst_degenerate_left.ceil(&1.1), None);
+    }
+
+    #[test]
+    fn ceil_basic() {
+        let mut bst = BinarySearchTree::<i32>::new();
+
+        bst.insert(1);
+        bst.insert(2);
+        bst.insert(5);
+
+        assert_eq!(bst.ceil(&6), None);
+        assert_eq!(bst.ceil(&5), Some(&5));
+        assert_eq!(bst.ceil(&4), Some(&5));
+        assert_eq!(bst.ceil(&3), Some(&5));
+        assert_eq!(bst.ceil(&2), Some(&2));
+        assert_eq!(bst.ceil(&1), Some(&1));
+        assert_eq!(bst.ceil(&0), Some(&1));
+    }
+
+    #[test]
+    fn floor_in_empty_tree() {
+        let bst = BinarySearchTree::<i32>::new();
+
+        assert_eq!(bst.floor(&0), None);
+    }
+
+    #[test]
+    fn floor_in_degenerate_trees() {
+        let mut bst_degenerate_right = BinarySearchTree::new();
+        let mut bst_degenerate_left = BinarySearchTree::new();
+
+        for i in 0..=10 {
+            let val = i as f64 / 10.0;
+            bst_degenerate_right.insert(val);
+        }
+
+        for i in (0..=10).rev() {
+            let val = i as f64 / 10.0;
+            bst_degenerate_left.insert(val);
+        }
+
+        assert_eq!(bst_degenerate_right.floor(&0.0), Some(&0.0));
+        assert_eq!(bst_degenerate_left.floor(&0.0), Some(&0.0));
+
+        assert_eq!(bst_degenerate_right.floor(&(0.03)), Some(&0.0));
+        assert_eq!(bst_degenerate_left.floor(&(0.03)), Some(&0.0));
+        assert_eq!(bst_degenerate_right.floor(&(0.07)), Some(&0.0));
+        assert_eq!(bst_degenerate_left.floor(&(0.07)), Some(&0.0));
+
+        assert_eq!(bst_degenerate_right.floor(&(-0.9)), None);
+        assert_eq!(bst_degenerate_left.floor(&(-0.9)), None);
+    }
+
+    #[test]
+    fn floor_basic() {
+        let mut bst = BinarySearchTree::<i32>::new();
+
+        bst.insert(1);
+        bst.insert(2);
+        bst.insert(5);
+
+        assert_eq!(bst.floor(&6), Some(&5));
+        assert_eq!(bst.floor(&5), Some(&5));
+        assert_eq!(bst.floor(&4), Some(&2));
+        assert_eq!(bst.floor(&3), Some(&2));
+        assert_eq!(bst.floor(&2), Some(&2));
+        assert_eq!(bst.floor(&1), Some(&1));
+        assert_eq!(bst.floor(&0), None);
+    }
+
+    /// Ordered and compared by `key` only, so that equal elements may carry different payloads.
+    #[derive(Debug, Clone)]
+    struct Keyed {
+        key: i32,
+        payload: &'static str,
+    }
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            self.key.partial_cmp(&other.key)
+        }
+    }
+
+    fn keyed(key: i32, payload: &'static str) -> Keyed {
+        Keyed { key, payload }
+    }
+
+    #[test]
+    fn insert_or_replace_basic() {
+        let mut bst = BinarySearchTree::new();
+
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            assert_eq!(bst.insert_or_replace(keyed(key, "old")), None);
+        }
+
+        let replaced = bst.insert_or_replace(keyed(4, "new")).unwrap();
+        assert_eq!(replaced.payload, "old");
+        assert_eq!(bst.number_of_elements(), 7);
+
+        let payloads: Vec<_> = bst.in_order().iter().map(|k| k.payload).collect();
+        assert_eq!(
+            payloads,
+            vec!["old", "old", "old", "new", "old", "old", "old"]
+        );
+    }
+
+    #[test]
+    fn insert_or_replace_updates_min_max() {
+        let mut bst = BinarySearchTree::new();
+
+        for key in [2, 1, 3] {
+            bst.insert(keyed(key, "old"));
+        }
+
+        assert_eq!(
+            bst.insert_or_replace(keyed(1, "new")).unwrap().payload,
+            "old"
+        );
+        assert_eq!(
+            bst.insert_or_replace(keyed(3, "new")).unwrap().payload,
+            "old"
+        );
+
+        assert_eq!(bst.min().unwrap().payload, "new");
+        assert_eq!(bst.max().unwrap().payload, "new");
+    }
+
+    #[test]
+    fn retain_range_keeps_middle_window() {
+        let mut bst = BinarySearchTree::new();
+        for i in 0..100 {
+            bst.insert((i * 37) % 100);
+        }
+
+        bst.retain_range(&30, &60);
+
+        let expected: Vec<i32> = (30..=60).collect();
+        assert_eq!(bst.in_order(), expected.iter().collect::<Vec<_>>());
+        assert_eq!(bst.min(), Some(&30));
+        assert_eq!(bst.max(), Some(&60));
+        bst.retain_range(&-10, &35);
+        assert_eq!(bst.in_order(), vec![&30, &31, &32, &33, &34, &35]);
+
+        bst.retain_range(&5, &1);
+        assert!(bst.is_empty());
+        assert_eq!(bst.min(), None);
+        assert_eq!(bst.max(), None);
+    }
+
+    #[test]
+    fn display_as_set() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(format!("{bst}"), "{}");
+
+        bst.insert(2);
+        assert_eq!(format!("{bst}"), "{2}");
+
+        bst.insert(3);
+        bst.insert(1);
+        assert_eq!(format!("{bst}"), "{1, 2, 3}");
+    }
+
+    #[test]
+    fn traversals_cloned() {
+        let mut bst = BinarySearchTree::new();
+        for value in [4, 2, 5, 1, 3, 6] {
+            bst.insert(value);
+        }
+
+        let mut sorted = vec![4, 2, 5, 1, 3, 6];
+        sorted.sort();
+        assert_eq!(bst.in_order_cloned(), sorted);
+
+        let owned = |refs: Vec<&i32>| refs.into_iter().copied().collect::<Vec<_>>();
+        assert_eq!(bst.pre_order_cloned(), owned(bst.pre_order()));
+        assert_eq!(bst.post_order_cloned(), owned(bst.post_order()));
+        assert_eq!(bst.level_order_cloned(), owned(bst.level_order()));
+        assert!(BinarySearchTree::<i32>::new().in_order_cloned().is_empty());
+    }
+
+    #[test]
+    fn empty_and_with_value() {
+        let bst = BinarySearchTree::<i32>::empty();
+        assert!(bst.is_empty());
+        assert_eq!(bst.min(), None);
+
+        let bst = BinarySearchTree::with_value(5);
+        assert_eq!(bst.min(), Some(&5));
+        assert_eq!(bst.max(), Some(&5));
+        assert_eq!(bst.number_of_elements(), 1);
+        assert!(bst.contains(&5));
+    }
+
+    #[test]
+    fn find_returns_rank_of_present_values() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.find(&1), None);
+
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            bst.insert(value);
+        }
+
+        assert_eq!(bst.find(&20), Some(0));
+        assert_eq!(bst.find(&50), Some(3));
+        assert_eq!(bst.find(&80), Some(6));
+        assert_eq!(bst.find(&45), None);
+
+        bst.remove(&30);
+        assert_eq!(bst.find(&40), Some(1));
+        assert_eq!(bst.find(&80), Some(5));
+    }
+
+    #[test]
+    fn retain_range_keeps_ranks() {
+        let mut bst = BinarySearchTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80, 35, 45, 65, 75] {
+            bst.insert(value);
+        }
+
+        bst.retain_range(&33, &72);
+
+        let kept = [35, 40, 45, 50, 60, 65, 70];
+        for (rank, value) in kept.iter().enumerate() {
+            assert_eq!(bst.find(value), Some(rank));
+        }
+        assert_eq!(bst.find(&30), None);
+        assert_eq!(bst.find(&75), None);
+    }
+
+    #[test]
+    fn level_order_with_nulls() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.level_order_with_nulls().is_empty());
+
+        for value in [4, 2, 5, 1, 3, 6] {
+            bst.insert(value);
+        }
+
+        assert_eq!(
+            bst.level_order_with_nulls(),
+            vec![
+                Some(&4),
+                Some(&2),
+                Some(&5),
+                Some(&1),
+                Some(&3),
+                None,
+                Some(&6)
+            ]
+        );
+    }
+
+    #[test]
+    fn from_level_order_with_nulls_round_trip() {
+        assert!(BinarySearchTree::<i32>::from_level_order_with_nulls(vec![]).is_empty());
+        assert!(BinarySearchTree::from_level_order_with_nulls(vec![None, Some(1)]).is_empty());
+
+        let mut bst = BinarySearchTree::new();
+        for value in [8, 3, 10, 1, 6, 14, 4, 7, 13, 9] {
+            bst.insert(value);
+        }
+
+        let rebuilt =
+            BinarySearchTree::from_level_order_with_nulls(bst.level_order_with_nulls_cloned());
+
+        assert_eq!(
+            rebuilt.level_order_with_nulls(),
+            bst.level_order_with_nulls()
+        );
+        assert_eq!(rebuilt.pre_order(), bst.pre_order());
+        assert_eq!(rebuilt.min(), Some(&1));
+        assert_eq!(rebuilt.max(), Some(&14));
+        assert_eq!(rebuilt.find(&14), Some(9));
+    }
+
+    #[test]
+    fn from_level_order_with_nulls_is_literal() {
+        let bst = BinarySearchTree::from_level_order_with_nulls(vec![
+            Some(1),
+            Some(5),
+            None,
+            None,
+            Some(3),
+            Some(9),
+        ]);
+
+        // Reconstructed as given, even though 5 is not less than 1.
+        assert_eq!(bst.pre_order(), vec![&1, &5, &3, &9]);
+        assert_eq!(
+            bst.level_order_with_nulls_cloned(),
+            vec![Some(1), Some(5), None, None, Some(3), Some(9)]
+        );
+        assert_eq!(bst.number_of_elements(), 4);
+    }
+
+    #[test]
+    fn walk_matches_traversals() {
+        let mut bst = BinarySearchTree::new();
+        for value in [4, 2, 5, 1, 3, 6, 8, 7] {
+            bst.insert(value);
+        }
+
+        let walked = |order| {
+            let mut values = Vec::new();
+            bst.walk(order, |value| values.push(*value));
+            values
+        };
+        let owned = |refs: Vec<&i32>| refs.into_iter().copied().collect::<Vec<_>>();
+
+        assert_eq!(walked(TraversalOrder::PreOrder), owned(bst.pre_order()));
+        assert_eq!(walked(TraversalOrder::InOrder), owned(bst.in_order()));
+        assert_eq!(walked(TraversalOrder::PostOrder), owned(bst.post_order()));
+        assert_eq!(walked(TraversalOrder::LevelOrder), owned(bst.level_order()));
+
+        let mut calls = 0;
+        BinarySearchTree::<i32>::new().walk(TraversalOrder::InOrder, |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn try_from_vec() {
+        let bst = BinarySearchTree::try_from(vec![3, 1, 2]).unwrap();
+        assert_eq!(bst.in_order(), vec![&1, &2, &3]);
+        assert_eq!(bst.min(), Some(&1));
+        assert_eq!(bst.max(), Some(&3));
+
+        let empty = BinarySearchTree::<i32>::try_from(vec![]).unwrap();
+        assert!(empty.is_empty());
+
+        let err = BinarySearchTree::try_from(vec![3, 1, 2, 1, 3]).unwrap_err();
+        assert_eq!(err, DuplicateError { value: 1 });
+        assert_eq!(err.to_string(), "duplicate value: 1");
+    }
+
+    #[test]
+    fn stored_equal_to_returns_the_stored_representative() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.stored_equal_to(&keyed(1, "query")), None);
+
+        bst.insert(keyed(1, "first"));
+        bst.insert(keyed(2, "second"));
+        bst.insert(keyed(1, "ignored"));
+
+        let stored = bst.stored_equal_to(&keyed(1, "query")).unwrap();
+        assert_eq!(stored.payload, "first");
+        assert_eq!(bst.stored_equal_to(&keyed(3, "query")), None);
+
+        bst.insert_or_replace(keyed(1, "replaced"));
+        assert_eq!(
+            bst.stored_equal_to(&keyed(1, "query")).unwrap().payload,
+            "replaced"
+        );
+    }
+
+    #[test]
+    fn has_any_in_range() {
+        let mut bst = BinarySearchTree::new();
+        assert!(!bst.has_any_in_range(&0, &100));
+
+        for value in [10, 5, 20, 1, 7, 30, 25] {
+            bst.insert(value);
+        }
+
+        assert!(!bst.has_any_in_range(&11, &19));
+        assert!(!bst.has_any_in_range(&31, &40));
+        assert!(!bst.has_any_in_range(&-5, &0));
+        assert!(bst.has_any_in_range(&21, &26));
+        assert!(bst.has_any_in_range(&7, &7));
+        assert!(bst.has_any_in_range(&-100, &100));
+        assert!(!bst.has_any_in_range(&26, &21));
+    }
+
+    #[test]
+    fn refresh_bounds_after_direct_root_mutation() {
+        let mut bst = BinarySearchTree::new();
+        for value in [10, 5, 20, 1, 30] {
+            bst.insert(value);
+        }
+
+        let root = bst.root.as_mut().unwrap();
+        root.left = None;
+        root.right.as_mut().unwrap().right = None;
+        assert_eq!(bst.min(), Some(&1));
+        assert_eq!(bst.max(), Some(&30));
+
+        bst.refresh_bounds();
+        assert_eq!(bst.min(), Some(&10));
+        assert_eq!(bst.max(), Some(&20));
+
+        bst.root = None;
+        bst.refresh_bounds();
+        assert_eq!(bst.min(), None);
+        assert_eq!(bst.max(), None);
+    }
+
+    #[test]
+    fn same_elements_as() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.same_elements_as(&[]));
+
+        for value in [5, 3, 8, 1, 4] {
+            bst.insert(value);
+        }
+
+        assert!(bst.same_elements_as(&[1, 3, 4, 5, 8]));
+        assert!(!bst.same_elements_as(&[5, 3, 8, 1, 4]));
+        assert!(!bst.same_elements_as(&[1, 3, 4, 5]));
+        assert!(!bst.same_elements_as(&[1, 3, 4, 5, 9]));
+    }
+
+    #[test]
+    fn reverse_ordering() {
+        let mut bst = BinarySearchTree::new();
+        for value in [5, 3, 8, 1, 4, 9] {
+            bst.insert(Reverse(value));
+        }
+
+        let descending: Vec<i32> = bst.in_order().into_iter().map(|value| value.0).collect();
+        assert_eq!(descending, vec![9, 8, 5, 4, 3, 1]);
+        assert_eq!(bst.min(), Some(&Reverse(9)));
+        assert_eq!(bst.max(), Some(&Reverse(1)));
+        assert_eq!(bst.ceil(&Reverse(6)), Some(&Reverse(5)));
+        assert_eq!(bst.floor(&Reverse(6)), Some(&Reverse(8)));
+
+        bst.remove(&Reverse(9));
+        bst.remove(&Reverse(1));
+        assert_eq!(bst.min(), Some(&Reverse(8)));
+        assert_eq!(bst.max(), Some(&Reverse(3)));
+        assert_eq!(bst.find(&Reverse(8)), Some(0));
+    }
+
+    #[test]
+    fn balance_rebuilds_degenerate_tree() {
+        let mut bst = BinarySearchTree::new();
+        bst.balance();
+        assert!(bst.is_empty());
+
+        for value in 1..=15 {
+            bst.insert(value);
+        }
+        assert_eq!(bst.height(), 14);
+
+        bst.balance();
+        assert_eq!(bst.height(), 3);
+        assert_eq!(bst.in_order_cloned(), (1..=15).collect::<Vec<_>>());
+        assert_eq!(bst.level_order()[..3], [&8, &4, &12]);
+        assert_eq!(bst.min(), Some(&1));
+        assert_eq!(bst.max(), Some(&15));
+        assert_eq!(bst.find(&15), Some(14));
+    }
+
+    #[test]
+    fn insert_with_rebalance_threshold_bounds_height() {
+        let mut bst = BinarySearchTree::new();
+        let max_height = 12;
+        let mut rebalances = 0;
+
+        for value in 0..1000 {
+            if bst.insert_with_rebalance_threshold(value, max_height) {
+                rebalances += 1;
             }
+            assert!(bst.height() <= max_height);
         }
 
-        result
-    }
-}
-
-impl<T: PartialOrd + Clone> Default for BinarySearchTree<T> {
-    fn default() -> Self {
-        Self::new()
+        assert!(rebalances > 1);
+        assert!(rebalances < 1000);
+        assert_eq!(bst.in_order_cloned(), (0..1000).collect::<Vec<_>>());
+        assert!(!bst.insert_with_rebalance_threshold(1000, 100));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn is_and_isnt_empty_tree() {
-        let bst_1 = BinarySearchTree::<i32>::new();
-        assert!(bst_1.is_empty());
+    fn split_off_partitions_at_key() {
+        let values = [50, 30, 70, 20, 40, 60, 80, 10, 35, 65];
 
-        let mut bst_2 = BinarySearchTree::<i32>::new();
-        bst_2.insert(42);
-        assert!(!bst_2.is_empty());
+        for key in [0, 10, 35, 36, 50, 80, 81] {
+            let mut bst = BinarySearchTree::new();
+            for value in values {
+                bst.insert(value);
+            }
+
+            let other = bst.split_off(&key);
+
+            let mut sorted = values.to_vec();
+            sorted.sort();
+            let (less, rest): (Vec<i32>, Vec<i32>) = sorted.iter().partition(|&&v| v < key);
+            assert_eq!(bst.in_order_cloned(), less);
+            assert_eq!(other.in_order_cloned(), rest);
+
+            assert_eq!(bst.min(), less.first());
+            assert_eq!(bst.max(), less.last());
+            assert_eq!(other.min(), rest.first());
+            assert_eq!(other.max(), rest.last());
+            assert_eq!(
+                bst.find(&less.last().copied().unwrap_or(key)),
+                less.len().checked_sub(1)
+            );
+            assert_eq!(
+                other.find(&rest.last().copied().unwrap_or(key)),
+                rest.len().checked_sub(1)
+            );
+        }
     }
 
     #[test]
-    fn contains_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn append_overlapping_trees() {
+        let mut bst = BinarySearchTree::new();
+        for value in [5, 1, 9, 3, 7] {
+            bst.insert(value);
+        }
+        let mut other = BinarySearchTree::new();
+        for value in [6, 3, 12, 0, 9] {
+            other.insert(value);
+        }
 
-        assert!(!bst.contains(&0));
+        bst.append(&mut other);
+
+        assert_eq!(bst.in_order_cloned(), vec![0, 1, 3, 5, 6, 7, 9, 12]);
+        assert_eq!(bst.min(), Some(&0));
+        assert_eq!(bst.max(), Some(&12));
+        assert_eq!(bst.find(&12), Some(7));
+        assert!(other.is_empty());
+        assert_eq!(other.min(), None);
+        assert_eq!(other.max(), None);
+
+        bst.append(&mut other);
+        assert_eq!(bst.number_of_elements(), 8);
     }
 
     #[test]
-    fn contains_in_single_node_tree() {
+    fn append_keeps_own_copy_of_equal_values() {
         let mut bst = BinarySearchTree::new();
-        bst.insert(1);
+        bst.insert(keyed(1, "self"));
+        let mut other = BinarySearchTree::new();
+        other.insert(keyed(1, "other"));
+        other.insert(keyed(2, "other"));
 
-        assert!(bst.contains(&1));
+        bst.append(&mut other);
+
+        assert_eq!(
+            bst.stored_equal_to(&keyed(1, "")).map(|k| k.payload),
+            Some("self")
+        );
+        assert_eq!(bst.number_of_elements(), 2);
     }
 
     #[test]
-    fn contains_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
-
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
-        }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
+    fn drain_range_moves_middle_window() {
+        let mut bst = BinarySearchTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80, 10, 35, 65, 75] {
+            bst.insert(value);
         }
 
-        for i in 0..=10 {
-            assert!(bst_degenerate_right.contains(&i));
-            assert!(bst_degenerate_left.contains(&i));
-        }
+        let drained = bst.drain_range(&33, &65);
+
+        assert_eq!(drained.in_order_cloned(), vec![35, 40, 50, 60, 65]);
+        assert_eq!(drained.min(), Some(&35));
+        assert_eq!(drained.max(), Some(&65));
+        assert_eq!(drained.find(&65), Some(4));
+        assert_eq!(bst.in_order_cloned(), vec![10, 20, 30, 70, 75, 80]);
+        assert_eq!(bst.min(), Some(&10));
+        assert_eq!(bst.max(), Some(&80));
+        assert_eq!(bst.find(&80), Some(5));
+
+        assert!(bst.drain_range(&31, &69).is_empty());
+        assert!(bst.drain_range(&80, &10).is_empty());
+        assert_eq!(bst.number_of_elements(), 6);
+
+        let drained = bst.drain_range(&0, &100);
+        assert_eq!(drained.in_order_cloned(), vec![10, 20, 30, 70, 75, 80]);
+        assert!(bst.is_empty());
+        assert_eq!(bst.min(), None);
     }
 
     #[test]
-    fn contains_basic() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
+    fn approx_memory_bytes_scales_linearly() {
+        let mut bst = BinarySearchTree::new();
+        let empty = bst.approx_memory_bytes();
+        assert_eq!(empty, size_of::<BinarySearchTree<i32>>());
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            bst_diff_heights_null.insert(value);
+        for value in 0..100 {
+            bst.insert(value);
         }
-        for value in &values_2 {
-            bst_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            bst_diff_heights_two.insert(value);
+        let hundred = bst.approx_memory_bytes();
+        for value in 100..200 {
+            bst.insert(value);
         }
+        let two_hundred = bst.approx_memory_bytes();
 
-        for value in &values_1 {
-            assert!(bst_diff_heights_null.contains(&value));
-        }
-        for value in &values_2 {
-            assert!(bst_diff_heights_one.contains(&value));
-        }
-        for value in &values_3 {
-            assert!(bst_diff_heights_two.contains(&value));
-        }
+        assert!(hundred > empty);
+        assert_eq!(two_hundred - hundred, hundred - empty);
+        assert_eq!(hundred - empty, 100 * size_of::<BinaryNode<i32>>());
     }
 
     #[test]
-    fn remove_from_empty_tree() {
-        let mut bst = BinarySearchTree::<i32>::new();
+    fn from_insertion_log_reproduces_the_tree() {
+        let log = [
+            Op::Insert(5),
+            Op::Insert(3),
+            Op::Insert(8),
+            Op::Insert(1),
+            Op::Remove(3),
+            Op::Insert(9),
+            Op::Remove(42),
+            Op::Insert(4),
+            Op::Insert(5),
+            Op::Remove(5),
+        ];
 
+        let mut bst = BinarySearchTree::new();
+        bst.insert(5);
+        bst.insert(3);
+        bst.insert(8);
+        bst.insert(1);
+        bst.remove(&3);
+        bst.insert(9);
         bst.remove(&42);
+        bst.insert(4);
+        bst.insert(5);
+        bst.remove(&5);
 
-        assert!(!bst.contains(&42));
-        assert_eq!(bst.min(), None);
-        assert_eq!(bst.max(), None);
+        let replayed = BinarySearchTree::from_insertion_log(&log);
+        assert_eq!(
+            replayed.level_order_with_nulls(),
+            bst.level_order_with_nulls()
+        );
+        assert_eq!(replayed.in_order(), vec![&1, &4, &8, &9]);
+        assert_eq!(replayed.min(), Some(&1));
+        assert_eq!(replayed.max(), Some(&9));
+
+        assert!(BinarySearchTree::<i32>::from_insertion_log(&[]).is_empty());
     }
 
     #[test]
-    fn remove_from_single_node_tree_check_min_max_updating() {
+    fn root_value() {
         let mut bst = BinarySearchTree::new();
-        assert!(bst.max() == bst.min() && bst.max().is_none());
+        assert_eq!(bst.root_value(), None);
 
-        bst.insert(1);
-        assert!(bst.max() == bst.min() && bst.max() == Some(&1));
-        assert!(bst.contains(&1));
+        for value in 1..=7 {
+            bst.insert(value);
+        }
+        assert_eq!(bst.root_value(), Some(&1));
+        assert_eq!(bst.root_value(), bst.level_order().first().copied());
 
         bst.remove(&1);
-        assert!(bst.max() == bst.min() && bst.max().is_none());
-        assert!(!bst.contains(&1));
+        assert_eq!(bst.root_value(), Some(&2));
     }
 
     #[test]
-    fn remove_from_degenerate_trees_check_min_max_updating() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
+    fn edges_match_find_connections() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.edges().next(), None);
 
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
-            assert_eq!(bst_degenerate_right.min(), Some(&0));
-            assert_eq!(bst_degenerate_right.max(), Some(&i));
+        bst.insert(4);
+        assert_eq!(bst.edges().next(), None);
+
+        for value in [2, 6, 1, 3, 5, 7, 8, 0] {
+            bst.insert(value);
+            assert_eq!(bst.edges().collect::<Vec<_>>(), bst.find_connections());
         }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
-            assert_eq!(bst_degenerate_left.min(), Some(&i));
-            assert_eq!(bst_degenerate_left.max(), Some(&10));
+        assert_eq!(bst.edges().count(), bst.number_of_elements() - 1);
+    }
+
+    #[test]
+    fn clamp_prefers_floor_then_min() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.clamp(&5), None);
+
+        for value in [20, 10, 30] {
+            bst.insert(value);
         }
 
-        for i in 0..=10 {
-            bst_degenerate_right.remove(&i);
-            assert!(!bst_degenerate_right.contains(&i));
+        assert_eq!(bst.clamp(&5), Some(&10));
+        assert_eq!(bst.clamp(&10), Some(&10));
+        assert_eq!(bst.clamp(&25), Some(&20));
+        assert_eq!(bst.clamp(&20), Some(&20));
+        assert_eq!(bst.clamp(&30), Some(&30));
+        assert_eq!(bst.clamp(&99), Some(&30));
+    }
 
-            bst_degenerate_left.remove(&i);
-            assert!(!bst_degenerate_left.contains(&i));
+    #[test]
+    fn bytes_round_trip() {
+        let mut bst = BinarySearchTree::new();
+        for value in [5, -3, 8, 1, i32::MIN, 4, i32::MAX] {
+            bst.insert(value);
+        }
 
-            if i < 10 {
-                assert_eq!(bst_degenerate_right.min(), Some(&(i + 1)));
-                assert_eq!(bst_degenerate_right.max(), Some(&10));
+        let data = bst.to_bytes();
+        assert_eq!(data.len(), 8 + 7 * 4);
+
+        let decoded = BinarySearchTree::<i32>::from_bytes(&data).unwrap();
+        assert_eq!(decoded.in_order(), bst.in_order());
+        assert_eq!(decoded.min(), Some(&i32::MIN));
+        assert_eq!(decoded.max(), Some(&i32::MAX));
+        assert_eq!(decoded.height(), 2);
+        assert_eq!(decoded.find(&i32::MAX), Some(6));
+
+        let empty = BinarySearchTree::<u8>::new().to_bytes();
+        assert!(
+            BinarySearchTree::<u8>::from_bytes(&empty)
+                .unwrap()
+                .is_empty()
+        );
+    }
 
-                assert_eq!(bst_degenerate_left.min(), Some(&(i + 1)));
-                assert_eq!(bst_degenerate_left.max(), Some(&10));
-            } else {
-                assert_eq!(bst_degenerate_right.min(), None);
-                assert_eq!(bst_degenerate_right.max(), None);
+    #[test]
+    fn from_bytes_rejects_malformed_data() {
+        let mut data = 2u64.to_le_bytes().to_vec();
+        assert_eq!(
+            BinarySearchTree::<u16>::from_bytes(&data[..7]).unwrap_err(),
+            DecodeError::MissingLength
+        );
 
-                assert_eq!(bst_degenerate_left.min(), None);
-                assert_eq!(bst_degenerate_left.max(), None);
+        data.extend_from_slice(&[1, 0, 2]);
+        assert_eq!(
+            BinarySearchTree::<u16>::from_bytes(&data).unwrap_err(),
+            DecodeError::LengthMismatch {
+                declared: 2,
+                bytes: 3
             }
-        }
+        );
+
+        data.push(0);
+        assert!(BinarySearchTree::<u16>::from_bytes(&data).is_ok());
+
+        data[10] = 1;
+        assert_eq!(
+            BinarySearchTree::<u16>::from_bytes(&data).unwrap_err(),
+            DecodeError::NotAscending { index: 1 }
+        );
+
+        let nan = [1u64.to_le_bytes().as_slice(), &f64::NAN.to_le_bytes()].concat();
+        assert!(BinarySearchTree::<f64>::from_bytes(&nan).is_ok());
     }
 
     #[test]
-    fn remove_basic_check_min_max_updating() {
-        let mut bst1 = BinarySearchTree::new();
-        let mut bst2 = BinarySearchTree::new();
-        let mut bst3 = BinarySearchTree::new();
-
-        let values1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values2 = vec![4, 2, 6, 1, 3, 5];
-        let values3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+    fn into_level_order_matches_level_order() {
+        assert!(
+            BinarySearchTree::<String>::new()
+                .into_level_order()
+                .is_empty()
+        );
 
-        for &v in &values1 {
-            bst1.insert(v);
-        }
-        for &v in &values2 {
-            bst2.insert(v);
-        }
-        for &v in &values3 {
-            bst3.insert(v);
+        let mut bst = BinarySearchTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80, 10, 65] {
+            bst.insert(value.to_string());
         }
 
-        let mut remaining1 = values1.clone();
-        let mut remaining2 = values2.clone();
-        let mut remaining3 = values3.clone();
+        let expected = bst.level_order_cloned();
+        assert_eq!(bst.into_level_order(), expected);
+    }
 
-        for &v in &values1 {
-            bst1.remove(&v);
-            remaining1.retain(|&x| x != v);
+    #[test]
+    fn all_comparable_detects_nan() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.all_comparable());
 
-            assert!(!bst1.contains(&v));
-            assert_eq!(bst1.min(), remaining1.iter().min());
-            assert_eq!(bst1.max(), remaining1.iter().max());
+        for value in [2.0, 1.0, 3.0, f64::INFINITY] {
+            bst.insert(value);
         }
+        assert!(bst.all_comparable());
 
-        for &v in &values2 {
-            bst2.remove(&v);
-            remaining2.retain(|&x| x != v);
+        bst.root.as_mut().unwrap().value = f64::NAN;
+        assert!(!bst.all_comparable());
 
-            assert!(!bst2.contains(&v));
-            assert_eq!(bst2.min(), remaining2.iter().min());
-            assert_eq!(bst2.max(), remaining2.iter().max());
+        let mut single = BinarySearchTree::new();
+        single.root = Some(Box::new(BinaryNode::new(f64::NAN)));
+        assert!(!single.all_comparable());
+    }
+
+    #[test]
+    fn balance_quality() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.balance_quality(), 1.0);
+
+        for value in [4, 2, 6, 1, 3, 5, 7] {
+            bst.insert(value);
         }
+        assert_eq!(bst.balance_quality(), 1.0);
 
-        for &v in &values3 {
-            bst3.remove(&v);
-            remaining3.retain(|&x| x != v);
+        bst.insert(8);
+        assert_eq!(bst.balance_quality(), 1.0);
+        bst.insert(9);
+        assert_eq!(bst.balance_quality(), 0.8);
 
-            assert!(!bst3.contains(&v));
-            assert_eq!(bst3.min(), remaining3.iter().min());
-            assert_eq!(bst3.max(), remaining3.iter().max());
+        let mut chain = BinarySearchTree::new();
+        for value in 0..1000 {
+            chain.insert(value);
         }
+        assert!(chain.balance_quality() < 0.02);
+
+        chain.balance();
+        assert_eq!(chain.balance_quality(), 1.0);
     }
 
     #[test]
-    fn min_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn merge_diff_classifies_elements() {
+        let mut bst = BinarySearchTree::new();
+        let mut other = BinarySearchTree::new();
+        assert_eq!(bst.merge_diff(&other).next(), None);
 
-        assert_eq!(bst.min(), None);
+        for value in [5, 1, 3, 7, 9] {
+            bst.insert(value);
+        }
+        for value in [4, 3, 9, 10, 0] {
+            other.insert(value);
+        }
+
+        assert_eq!(
+            bst.merge_diff(&other).collect::<Vec<_>>(),
+            vec![
+                DiffItem::Right(&0),
+                DiffItem::Left(&1),
+                DiffItem::Both(&3, &3),
+                DiffItem::Right(&4),
+                DiffItem::Left(&5),
+                DiffItem::Left(&7),
+                DiffItem::Both(&9, &9),
+                DiffItem::Right(&10),
+            ]
+        );
+        assert!(
+            bst.merge_diff(&bst)
+                .all(|item| matches!(item, DiffItem::Both(a, b) if a == b))
+        );
+        assert_eq!(
+            bst.merge_diff(&BinarySearchTree::new()).count(),
+            bst.number_of_elements()
+        );
     }
 
     #[test]
-    fn min_in_degenerate_trees_check_updating() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
-
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
-            assert_eq!(bst_degenerate_right.min(), Some(&0));
-        }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
-            assert_eq!(bst_degenerate_left.min(), Some(&i));
+    fn with_capacity_behaves_like_new() {
+        let values: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+
+        let mut bst = BinarySearchTree::with_capacity(1000);
+        assert!(bst.is_empty());
+        let mut expected = BinarySearchTree::new();
+        for &value in &values {
+            bst.insert(value);
+            expected.insert(value);
         }
+
+        assert_eq!(
+            bst.level_order_with_nulls(),
+            expected.level_order_with_nulls()
+        );
+        assert_eq!(bst.min(), expected.min());
+        assert_eq!(bst.max(), expected.max());
     }
 
     #[test]
-    fn min_basic_check_updating() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
+    fn filter_map_halves_evens_and_drops_odds() {
+        let mut bst = BinarySearchTree::new();
+        for value in [8, 3, 12, 1, 6, 10, 14, 7] {
+            bst.insert(value);
+        }
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        let halves = bst.filter_map(|&v| (v % 2 == 0).then_some(v / 2));
+        assert_eq!(halves.in_order(), vec![&3, &4, &5, &6, &7]);
+        assert_eq!(halves.min(), Some(&3));
+        assert_eq!(halves.max(), Some(&7));
 
-        let mut current_min_1 = None;
-        let mut current_min_2 = None;
-        let mut current_min_3 = None;
+        let collapsed = bst.filter_map(|&v| Some(v / 4));
+        assert_eq!(collapsed.in_order(), vec![&0, &1, &2, &3]);
 
-        for value in &values_1 {
-            bst_diff_heights_null.insert(*value);
-            current_min_1 = Some(*value)
-                .filter(|&x| current_min_1.is_none_or(|min| x < min))
-                .or(current_min_1);
-            assert_eq!(bst_diff_heights_null.min(), current_min_1.as_ref());
+        let labels = bst.filter_map(|&v| (v > 10).then(|| v.to_string()));
+        assert_eq!(labels.in_order_cloned(), vec!["12", "14"]);
+
+        assert!(bst.filter_map(|_| None::<i32>).is_empty());
+    }
+
+    #[test]
+    fn nearest_k_expands_symmetrically() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.nearest_k(&5, 3).is_empty());
+
+        for value in [50, 25, 75, 12, 37, 62, 87] {
+            bst.insert(value);
+        }
+        for value in 0..100 {
+            bst.insert(value);
         }
 
-        for value in &values_2 {
-            bst_diff_heights_one.insert(*value);
-            current_min_2 = Some(*value)
-                .filter(|&x| current_min_2.is_none_or(|min| x < min))
-                .or(current_min_2);
-            assert_eq!(bst_diff_heights_one.min(), current_min_2.as_ref());
+        assert_eq!(bst.nearest_k(&50, 5), vec![&50, &49, &51, &48, &52]);
+        assert!(bst.nearest_k(&50, 0).is_empty());
+        assert_eq!(bst.nearest_k(&-10, 2), vec![&0, &1]);
+        assert_eq!(bst.nearest_k(&200, 2), vec![&99, &98]);
+
+        let all = bst.nearest_k(&0, 1000);
+        assert_eq!(all.len(), 100);
+        assert_eq!(all, bst.in_order());
+
+        let mut evens = BinarySearchTree::new();
+        for value in [10u32, 4, 16, 2, 8, 14, 18, 6, 12] {
+            evens.insert(value);
+        }
+        assert_eq!(evens.nearest_k(&11, 4), vec![&10, &12, &8, &14]);
+        assert_eq!(evens.nearest_k(&1, 3), vec![&2, &4, &6]);
+    }
+
+    #[test]
+    fn remove_min_and_remove_max_empty_the_tree_in_order() {
+        let mut bst = BinarySearchTree::new();
+        assert!(!bst.remove_min());
+        assert!(!bst.remove_max());
+
+        let values: Vec<i32> = (0..200).map(|i| (i * 7919) % 200).collect();
+        for &value in &values {
+            bst.insert(value);
         }
 
-        for value in &values_3 {
-            bst_diff_heights_two.insert(*value);
-            current_min_3 = Some(*value)
-                .filter(|&x| current_min_3.is_none_or(|min| x < min))
-                .or(current_min_3);
-            assert_eq!(bst_diff_heights_two.min(), current_min_3.as_ref());
+        for expected in 0..100 {
+            assert_eq!(bst.min(), Some(&expected));
+            assert!(bst.remove_min());
+            assert_eq!(bst.find(&199), Some(198 - expected as usize));
+        }
+        for expected in (100..200).rev() {
+            assert_eq!(bst.max(), Some(&expected));
+            assert_eq!(bst.min(), Some(&100));
+            assert!(bst.remove_max());
         }
+
+        assert!(bst.is_empty());
+        assert_eq!(bst.min(), None);
+        assert_eq!(bst.max(), None);
+        assert!(!bst.remove_min());
     }
 
     #[test]
-    fn max_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn try_walk_stops_at_first_match() {
+        let mut bst = BinarySearchTree::new();
+        for value in [50, 20, 80, 10, 30, 60, 90, 25, 35] {
+            bst.insert(value);
+        }
 
-        assert_eq!(bst.max(), None);
+        let mut visited = 0;
+        let found = bst.try_walk(TraversalOrder::InOrder, |&value| {
+            visited += 1;
+            if value > 28 {
+                ControlFlow::Break(value)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(found, ControlFlow::Break(30));
+        assert_eq!(visited, 4);
+
+        let mut pre_order = Vec::new();
+        let result = bst.try_walk(TraversalOrder::PreOrder, |&value| {
+            pre_order.push(value);
+            ControlFlow::<i32>::Continue(())
+        });
+        assert_eq!(result, ControlFlow::Continue(()));
+        assert_eq!(pre_order, bst.pre_order_cloned());
+
+        let first = bst.try_walk(TraversalOrder::LevelOrder, |&value| {
+            ControlFlow::Break(value)
+        });
+        assert_eq!(first, ControlFlow::Break(*bst.root_value().unwrap()));
     }
 
     #[test]
-    fn max_in_degenerate_trees_check_updating() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
+    fn clear_and_reuse() {
+        let mut bst = BinarySearchTree::new();
+        bst.clear();
+        assert!(bst.is_empty());
 
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
-            assert_eq!(bst_degenerate_right.max(), Some(&i));
+        for value in [5, 3, 8, 1, 4] {
+            bst.insert(value);
         }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
-            assert_eq!(bst_degenerate_left.max(), Some(&10));
+        bst.clear();
+
+        assert!(bst.is_empty());
+        assert_eq!(bst.min(), None);
+        assert_eq!(bst.max(), None);
+        assert_eq!(bst.number_of_elements(), 0);
+        assert!(!bst.contains(&5));
+
+        for value in [7, 2, 9] {
+            bst.insert(value);
         }
+        assert_eq!(bst.in_order(), vec![&2, &7, &9]);
+        assert_eq!(bst.min(), Some(&2));
+        assert_eq!(bst.max(), Some(&9));
+        assert_eq!(bst.number_of_elements(), 3);
     }
 
     #[test]
-    fn max_basic_check_updating() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
+    fn len_ignores_duplicates_and_absent_removals() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.len(), 0);
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
+        for value in [5, 3, 8, 5, 3, 1, 8, 9] {
+            bst.insert(value);
+        }
+        assert_eq!(bst.len(), 5);
 
-        let mut current_max_1 = None;
-        let mut current_max_2 = None;
-        let mut current_max_3 = None;
+        bst.remove(&42);
+        bst.remove(&3);
+        bst.remove(&3);
+        bst.insert(1);
+        bst.insert(4);
+        assert_eq!(bst.len(), 5);
+        assert_eq!(bst.len(), bst.in_order().len());
+        assert_eq!(bst.number_of_elements(), bst.len());
 
-        for value in &values_1 {
-            bst_diff_heights_null.insert(*value);
-            current_max_1 = Some(*value)
-                .filter(|&x| current_max_1.is_none_or(|max| x > max))
-                .or(current_max_1);
-            assert_eq!(bst_diff_heights_null.max(), current_max_1.as_ref());
+        for value in [1, 4, 5, 8, 9, 0] {
+            bst.remove(&value);
         }
+        assert_eq!(bst.len(), 0);
+        assert!(bst.is_empty());
+    }
 
-        for value in &values_2 {
-            bst_diff_heights_one.insert(*value);
-            current_max_2 = Some(*value)
-                .filter(|&x| current_max_2.is_none_or(|max| x > max))
-                .or(current_max_2);
-            assert_eq!(bst_diff_heights_one.max(), current_max_2.as_ref());
+    #[test]
+    fn successor_and_predecessor_are_strict() {
+        let mut bst = BinarySearchTree::new();
+        for value in [20, 10, 30, 5, 15, 25, 35] {
+            bst.insert(value);
         }
 
-        for value in &values_3 {
-            bst_diff_heights_two.insert(*value);
-            current_max_3 = Some(*value)
-                .filter(|&x| current_max_3.is_none_or(|max| x > max))
-                .or(current_max_3);
-            assert_eq!(bst_diff_heights_two.max(), current_max_3.as_ref());
-        }
+        // For stored values the strict queries step past the value, `ceil`/`floor` do not.
+        assert_eq!(bst.ceil(&15), Some(&15));
+        assert_eq!(bst.successor(&15), Some(&20));
+        assert_eq!(bst.floor(&15), Some(&15));
+        assert_eq!(bst.predecessor(&15), Some(&10));
+        assert_eq!(bst.successor(&20), Some(&25));
+        assert_eq!(bst.predecessor(&20), Some(&15));
+
+        // For absent values both kinds agree.
+        assert_eq!(bst.successor(&16), bst.ceil(&16));
+        assert_eq!(bst.predecessor(&16), bst.floor(&16));
+
+        assert_eq!(bst.successor(&35), None);
+        assert_eq!(bst.predecessor(&5), None);
+        assert_eq!(bst.successor(&0), Some(&5));
+        assert_eq!(bst.predecessor(&99), Some(&35));
+
+        let empty = BinarySearchTree::<i32>::new();
+        assert_eq!(empty.successor(&1), None);
+        assert_eq!(empty.predecessor(&1), None);
     }
 
     #[test]
-    fn max_min_are_similar_for_single_element_tree() {
+    fn range_yields_bounded_values_in_order() {
         let mut bst = BinarySearchTree::new();
-        bst.insert(1);
+        for value in [50, 20, 80, 10, 30, 70, 90, 25, 35, 75] {
+            bst.insert(value);
+        }
 
-        assert!(bst.min() == bst.max() && bst.min() == Some(&1));
+        assert_eq!(
+            bst.range(&25, &75).collect::<Vec<_>>(),
+            vec![&25, &30, &35, &50, &70, &75]
+        );
+        assert_eq!(bst.range(&0, &100).collect::<Vec<_>>(), bst.in_order());
+        assert_eq!(bst.range(&30, &30).collect::<Vec<_>>(), vec![&30]);
+        assert_eq!(bst.range(&51, &69).next(), None);
+        assert_eq!(bst.range(&75, &25).next(), None);
+        assert_eq!(bst.range(&91, &99).next(), None);
+
+        let empty = BinarySearchTree::<i32>::new();
+        assert_eq!(empty.range(&0, &10).next(), None);
     }
 
     #[test]
-    fn height_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn take_returns_removed_value() {
+        let mut bst = BinarySearchTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            bst.insert(value);
+        }
 
-        assert_eq!(bst.height(), 0);
+        assert_eq!(bst.take(&45), None);
+        assert_eq!(bst.take(&50), Some(50));
+        assert_eq!(bst.take(&20), Some(20));
+        assert_eq!(bst.take(&80), Some(80));
+        assert_eq!(bst.take(&80), None);
+        assert_eq!(bst.in_order(), vec![&30, &40, &60, &70]);
+        assert_eq!(bst.min(), Some(&30));
+        assert_eq!(bst.max(), Some(&70));
+        assert_eq!(bst.len(), 4);
+
+        let mut empty = BinarySearchTree::<i32>::new();
+        assert_eq!(empty.take(&1), None);
     }
 
     #[test]
-    fn height_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
+    fn try_insert_reports_new_values() {
+        let mut bst = BinarySearchTree::new();
 
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
-        }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
-        }
+        assert!(bst.try_insert(5));
+        assert!(bst.try_insert(3));
+        assert!(!bst.try_insert(5));
+        assert!(!bst.try_insert(3));
+        assert_eq!(bst.len(), 2);
 
-        assert_eq!(bst_degenerate_right.height(), 10);
-        assert_eq!(bst_degenerate_left.height(), 10);
+        assert!(bst.try_insert(8));
+        assert!(!bst.try_insert(8));
+        assert_eq!(bst.len(), 3);
+        assert_eq!(bst.in_order(), vec![&3, &5, &8]);
     }
 
     #[test]
-    fn height_basic() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
+    fn get_returns_the_stored_payload() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.get(&keyed(1, "query")).is_none());
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            bst_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            bst_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            bst_diff_heights_two.insert(value);
-        }
+        bst.insert(keyed(2, "two"));
+        bst.insert(keyed(1, "one"));
+        bst.insert(keyed(3, "three"));
 
-        assert_eq!(bst_diff_heights_null.height(), 2);
-        assert_eq!(bst_diff_heights_one.height(), 2);
-        assert_eq!(bst_diff_heights_two.height(), 3);
+        assert_eq!(bst.get(&keyed(1, "query")).unwrap().payload, "one");
+        assert_eq!(bst.get(&keyed(3, "query")).unwrap().payload, "three");
+        assert!(bst.get(&keyed(4, "query")).is_none());
     }
 
     #[test]
-    fn pre_order_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn clone_is_independent() {
+        let mut bst = BinarySearchTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            bst.insert(value);
+        }
 
-        assert_eq!(bst.pre_order(), Vec::<&i32>::new());
+        let mut copy = bst.clone();
+        copy.remove(&50);
+        copy.remove(&20);
+        copy.insert(90);
+
+        assert_eq!(bst.in_order(), vec![&20, &30, &40, &50, &60, &70, &80]);
+        assert_eq!(bst.min(), Some(&20));
+        assert_eq!(bst.max(), Some(&80));
+        assert_eq!(bst.len(), 7);
+
+        assert_eq!(copy.in_order(), vec![&30, &40, &60, &70, &80, &90]);
+        assert_eq!(copy.min(), Some(&30));
+        assert_eq!(copy.max(), Some(&90));
+        assert_eq!(copy.len(), 6);
     }
 
     #[test]
-    fn pre_order_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
-
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
+    fn equality_ignores_insertion_order() {
+        let mut first = BinarySearchTree::new();
+        let mut second = BinarySearchTree::new();
+        for value in [1, 2, 3, 4, 5] {
+            first.insert(value);
         }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
+        for value in [3, 5, 1, 4, 2, 5] {
+            second.insert(value);
         }
+        assert_eq!(first, second);
+        assert_eq!(BinarySearchTree::<i32>::new(), BinarySearchTree::new());
 
+        second.remove(&5);
+        second.insert(6);
+        assert_ne!(first, second);
+
+        second.remove(&6);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn into_sorted_vec_moves_values_out() {
+        let mut bst = BinarySearchTree::new();
+        for value in [5, 2, 8, 1, 9, 3, 2] {
+            bst.insert(Box::new(value));
+        }
+
+        let sorted = bst.into_sorted_vec();
+        assert_eq!(sorted.len(), 6);
+        assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
         assert_eq!(
-            bst_degenerate_right.pre_order(),
-            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
-        );
-        assert_eq!(
-            bst_degenerate_left.pre_order(),
-            vec![&10, &9, &8, &7, &6, &5, &4, &3, &2, &1, &0]
+            sorted.into_iter().map(|value| *value).collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 8, 9]
         );
+
+        assert!(BinarySearchTree::<i32>::new().into_sorted_vec().is_empty());
     }
 
     #[test]
-    fn pre_order_basic() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
+    fn from_sorted_slice_is_balanced() {
+        let data: Vec<i32> = (0..1000).collect();
+        let bst = BinarySearchTree::from_sorted_slice(&data);
+
+        // A perfectly balanced tree of 1000 nodes has 10 levels (the height counts edges).
+        assert_eq!(bst.height(), 9);
+        assert_eq!(bst.len(), 1000);
+        assert_eq!(bst.min(), Some(&0));
+        assert_eq!(bst.max(), Some(&999));
+        assert_eq!(bst.in_order_cloned(), data);
+        assert_eq!(bst.root_value(), Some(&500));
+
+        let empty = BinarySearchTree::<i32>::from_sorted_slice(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.min(), None);
+    }
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            bst_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            bst_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            bst_diff_heights_two.insert(value);
+    #[test]
+    fn level_order_grouped_by_depth() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.level_order_grouped().is_empty());
+
+        for value in [8, 4, 12, 2, 6, 10, 1, 7] {
+            bst.insert(value);
         }
 
         assert_eq!(
-            bst_diff_heights_null.pre_order(),
-            vec![&&5, &&3, &&2, &&4, &&7, &&6, &&8]
+            bst.level_order_grouped(),
+            vec![vec![&8], vec![&4, &12], vec![&2, &6, &10], vec![&1, &7]]
         );
+        assert_eq!(bst.level_order_grouped().concat(), bst.level_order());
+    }
 
-        assert_eq!(
-            bst_diff_heights_one.pre_order(),
-            vec![&&4, &&2, &&1, &&3, &&6, &&5]
-        );
+    #[test]
+    fn with_comparator_orders_by_key() {
+        let mut bst = BinarySearchTree::with_comparator(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+        for word in ["banana", "fig", "apple", "kiwi", "cherry", "plum"] {
+            bst.insert(word);
+        }
 
-        assert_eq!(
-            bst_diff_heights_two.pre_order(),
-            vec![&&8, &&4, &&2, &&1, &&6, &&7, &&12, &&10]
-        );
+        // "cherry" and "plum" are dropped as duplicates of "banana" and "kiwi" by length.
+        assert_eq!(bst.in_order(), vec![&"fig", &"kiwi", &"apple", &"banana"]);
+        assert_eq!(bst.min(), Some(&"fig"));
+        assert_eq!(bst.max(), Some(&"banana"));
+
+        let by_length = |key: &'static str| move |word: &&str| word.len().cmp(&key.len());
+        assert_eq!(bst.get_by(by_length("pear")), Some(&"kiwi"));
+        assert_eq!(bst.get_by(by_length("ab")), None);
+        assert_eq!(bst.ceil_by(by_length("ab")), Some(&"fig"));
+        assert_eq!(bst.floor_by(by_length("melon")), Some(&"apple"));
+        assert_eq!(bst.floor_by(by_length("watermelon")), Some(&"banana"));
+        assert_eq!(bst.successor_or_equal(&"ab"), Some(&"fig"));
+        assert_eq!(bst.predecessor_or_equal(&"melon"), Some(&"apple"));
+
+        assert_eq!(bst.take_by(by_length("grape")), Some("apple"));
+        assert_eq!(bst.take_by(by_length("oranges")), None);
+        assert_eq!(bst.in_order(), vec![&"fig", &"kiwi", &"banana"]);
+
+        let copy = bst.clone();
+        assert!(copy.get_by(by_length("date")).is_some());
     }
 
     #[test]
-    fn in_order_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn lookups_accept_borrowed_forms() {
+        let mut bst = BinarySearchTree::new();
+        for word in ["bar", "foo", "qux"] {
+            bst.insert(word.to_string());
+        }
 
-        assert_eq!(bst.in_order(), Vec::<&i32>::new());
+        assert!(bst.contains("foo"));
+        assert!(!bst.contains("baz"));
+        assert_eq!(bst.get("qux"), Some(&"qux".to_string()));
+        assert_eq!(bst.ceil("baz"), Some(&"foo".to_string()));
+        assert_eq!(bst.floor("baz"), Some(&"bar".to_string()));
+
+        assert_eq!(bst.take("bar"), Some("bar".to_string()));
+        bst.remove("foo");
+        assert!(!bst.contains("foo"));
+        assert_eq!(bst.len(), 1);
     }
 
     #[test]
-    fn in_order_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
+    fn lookups_accept_keys_that_do_not_own_the_elements() {
+        #[derive(Debug, Clone, PartialEq, PartialOrd)]
+        struct Id(i32);
 
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
+        impl Borrow<i32> for Id {
+            fn borrow(&self) -> &i32 {
+                &self.0
+            }
         }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
+
+        let mut bst = BinarySearchTree::new();
+        for id in [2, 1, 3] {
+            bst.insert(Id(id));
         }
 
-        assert_eq!(
-            bst_degenerate_right.in_order(),
-            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
-        );
-        assert_eq!(
-            bst_degenerate_left.in_order(),
-            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
-        );
+        assert!(bst.contains(&1));
+        assert_eq!(bst.get(&3), Some(&Id(3)));
+        assert_eq!(bst.ceil(&0), Some(&Id(1)));
+        assert_eq!(bst.floor(&4), Some(&Id(3)));
+        assert_eq!(bst.take(&2), Some(Id(2)));
+        assert_eq!(bst.len(), 2);
+
+        bst.ceil_mut(&2).unwrap().0 = 4;
+        bst.floor_mut(&1).unwrap().0 = 0;
+        assert_eq!(bst.in_order(), vec![&Id(0), &Id(4)]);
     }
 
     #[test]
-    fn in_order_basic() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
-
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            bst_diff_heights_null.insert(value);
+    fn set_relationships() {
+        let mut small = BinarySearchTree::new();
+        let mut large = BinarySearchTree::new();
+        let mut other = BinarySearchTree::new();
+        for value in [2, 4, 6] {
+            small.insert(value);
         }
-        for value in &values_2 {
-            bst_diff_heights_one.insert(value);
+        for value in 1..=6 {
+            large.insert(value);
         }
-        for value in &values_3 {
-            bst_diff_heights_two.insert(value);
+        for value in [7, 9] {
+            other.insert(value);
         }
 
-        assert_eq!(
-            bst_diff_heights_null.in_order(),
-            vec![&&2, &&3, &&4, &&5, &&6, &&7, &&8]
-        );
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(large.is_superset(&small));
+        assert!(!small.is_superset(&large));
+        assert!(small.is_subset(&small) && small.is_superset(&small));
 
-        assert_eq!(
-            bst_diff_heights_one.in_order(),
-            vec![&&1, &&2, &&3, &&4, &&5, &&6]
-        );
+        assert!(small.is_disjoint(&other));
+        assert!(!small.is_disjoint(&large));
 
-        assert_eq!(
-            bst_diff_heights_two.in_order(),
-            vec![&&1, &&2, &&4, &&6, &&7, &&8, &&10, &&12]
-        );
+        let empty = BinarySearchTree::new();
+        assert!(empty.is_subset(&small) && empty.is_subset(&empty));
+        assert!(small.is_superset(&empty));
+        assert!(!empty.is_superset(&small));
+        assert!(empty.is_disjoint(&small) && empty.is_disjoint(&empty));
     }
 
     #[test]
-    fn post_order_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn for_each_sums_in_order() {
+        let mut bst = BinarySearchTree::new();
+        for value in [8, 3, 10, 1, 6, 14, 4, 7, 13] {
+            bst.insert(value);
+        }
 
-        assert_eq!(bst.post_order(), Vec::<&i32>::new());
+        let mut sum = 0;
+        bst.for_each(TraversalOrder::InOrder, |value| sum += value);
+
+        assert_eq!(sum, bst.in_order().into_iter().sum::<i32>());
+    }
+
+    #[test]
+    fn map_doubles_values() {
+        let mut bst = BinarySearchTree::new();
+        for value in [5, 2, 8, 1, 3, 9] {
+            bst.insert(value);
+        }
+
+        let doubled = bst.map(|value| value * 2);
+
+        assert_eq!(doubled.in_order(), vec![&2, &4, &6, &10, &16, &18]);
+        assert_eq!(doubled.height(), bst.height());
+        assert_eq!((doubled.min(), doubled.max()), (Some(&2), Some(&18)));
+
+        let collapsed = bst.map(|value| value / 4);
+        assert_eq!(collapsed.in_order(), vec![&0, &1, &2]);
+        assert_eq!(collapsed.len(), 3);
+    }
+
+    #[test]
+    fn depth_of_values() {
+        //        8
+        //       / \
+        //      3   10
+        //     / \    \
+        //    1   6    14
+        //       /
+        //      4
+        let mut bst = BinarySearchTree::new();
+        for value in [8, 3, 10, 1, 6, 14, 4] {
+            bst.insert(value);
+        }
+
+        assert_eq!(bst.depth(&8), Some(0));
+        assert_eq!(bst.depth(&10), Some(1));
+        assert_eq!(bst.depth(&1), Some(2));
+        assert_eq!(bst.depth(&14), Some(2));
+        assert_eq!(bst.depth(&4), Some(3));
+        assert_eq!(bst.depth(&4), Some(bst.height()));
+        assert_eq!(bst.depth(&5), None);
+        assert_eq!(BinarySearchTree::<i32>::new().depth(&8), None);
+    }
+
+    #[test]
+    fn lowest_common_ancestor_of_pairs() {
+        //          8
+        //        /   \
+        //       4     12
+        //      / \   /  \
+        //     2   6 10   14
+        //    / \
+        //   1   3
+        let mut bst = BinarySearchTree::new();
+        for value in [8, 4, 12, 2, 6, 10, 14, 1, 3] {
+            bst.insert(value);
+        }
+
+        assert_eq!(bst.lowest_common_ancestor(&1, &3), Some(&2));
+        assert_eq!(bst.lowest_common_ancestor(&3, &6), Some(&4));
+        assert_eq!(bst.lowest_common_ancestor(&10, &14), Some(&12));
+        assert_eq!(bst.lowest_common_ancestor(&1, &14), Some(&8));
+        assert_eq!(bst.lowest_common_ancestor(&6, &10), Some(&8));
+
+        assert_eq!(bst.lowest_common_ancestor(&4, &3), Some(&4));
+        assert_eq!(bst.lowest_common_ancestor(&6, &6), Some(&6));
+
+        assert_eq!(bst.lowest_common_ancestor(&1, &5), None);
+        assert_eq!(bst.lowest_common_ancestor(&15, &14), None);
     }
 
     #[test]
-    fn post_order_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
+    fn diameter_of_linear_and_balanced_trees() {
+        assert_eq!(BinarySearchTree::<i32>::new().diameter(), 0);
 
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
+        let mut bst = BinarySearchTree::new();
+        bst.insert(1);
+        assert_eq!(bst.diameter(), 0);
+
+        for value in 2..=10 {
+            bst.insert(value);
         }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
+        assert_eq!(bst.diameter(), 9);
+
+        //          8
+        //        /   \
+        //       4     12
+        //      / \      \
+        //     2   6      14
+        //    /             \
+        //   1               15
+        let mut bst = BinarySearchTree::new();
+        for value in [8, 4, 12, 2, 6, 14, 1, 15] {
+            bst.insert(value);
         }
-
-        assert_eq!(
-            bst_degenerate_right.post_order(),
-            vec![&10, &9, &8, &7, &6, &5, &4, &3, &2, &1, &0]
-        );
-        assert_eq!(
-            bst_degenerate_left.post_order(),
-            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
-        );
+        assert_eq!(bst.diameter(), 6);
     }
 
     #[test]
-    fn post_order_basic() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
+    fn count_leaves_and_internal_nodes() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!((bst.count_leaves(), bst.count_internal_nodes()), (0, 0));
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            bst_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            bst_diff_heights_one.insert(value);
+        bst.insert(1);
+        assert_eq!((bst.count_leaves(), bst.count_internal_nodes()), (1, 0));
+
+        for value in 2..=10 {
+            bst.insert(value);
         }
-        for value in &values_3 {
-            bst_diff_heights_two.insert(value);
+        assert_eq!((bst.count_leaves(), bst.count_internal_nodes()), (1, 9));
+
+        let mut bst = BinarySearchTree::new();
+        for value in [4, 2, 6, 1, 3, 5, 7] {
+            bst.insert(value);
         }
+        assert_eq!((bst.count_leaves(), bst.count_internal_nodes()), (4, 3));
+        assert_eq!(bst.count_leaves() + bst.count_internal_nodes(), bst.len());
+    }
 
-        assert_eq!(
-            bst_diff_heights_null.post_order(),
-            vec![&&2, &&4, &&3, &&6, &&8, &&7, &&5]
-        );
+    #[test]
+    fn closest_prefers_smaller_on_ties() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.closest(&5), None);
 
-        assert_eq!(
-            bst_diff_heights_one.post_order(),
-            vec![&&1, &&3, &&2, &&5, &&6, &&4]
-        );
+        for value in [20, 10, 30] {
+            bst.insert(value);
+        }
 
-        assert_eq!(
-            bst_diff_heights_two.post_order(),
-            vec![&&1, &&2, &&7, &&6, &&4, &&10, &&12, &&8]
-        );
+        assert_eq!(bst.closest(&15), Some(&10));
+        assert_eq!(bst.closest(&25), Some(&20));
+        assert_eq!(bst.closest(&14), Some(&10));
+        assert_eq!(bst.closest(&16), Some(&20));
+        assert_eq!(bst.closest(&20), Some(&20));
+        assert_eq!(bst.closest(&-5), Some(&10));
+        assert_eq!(bst.closest(&99), Some(&30));
     }
 
     #[test]
-    fn level_order_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn floor_mut_and_ceil_mut_update_payloads() {
+        let mut bst = BinarySearchTree::new();
+        for key in [10, 20, 30] {
+            bst.insert(keyed(key, "old"));
+        }
 
-        assert_eq!(bst.level_order(), Vec::<&i32>::new());
+        bst.floor_mut(&keyed(25, "")).unwrap().payload = "floor";
+        bst.ceil_mut(&keyed(25, "")).unwrap().payload = "ceil";
+        bst.floor_mut(&keyed(10, "")).unwrap().payload = "exact";
+
+        assert_eq!(bst.get(&keyed(20, "")).unwrap().payload, "floor");
+        assert_eq!(bst.get(&keyed(30, "")).unwrap().payload, "ceil");
+        assert_eq!(bst.get(&keyed(10, "")).unwrap().payload, "exact");
+        assert!(bst.floor_mut(&keyed(5, "")).is_none());
+        assert!(bst.ceil_mut(&keyed(35, "")).is_none());
     }
 
     #[test]
-    fn level_order_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
-
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
+    fn is_valid_bst_detects_corruption() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.is_valid_bst());
+        for value in [5, 3, 8, 1, 4, 9] {
+            bst.insert(value);
         }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
+        assert!(bst.is_valid_bst());
+        bst.remove(&3);
+        assert!(bst.is_valid_bst());
+
+        let mut by_length =
+            BinarySearchTree::with_comparator(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+        for word in ["kiwi", "fig", "banana", "apple"] {
+            by_length.insert(word);
         }
-
-        assert_eq!(
-            bst_degenerate_right.level_order(),
-            vec![&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10]
-        );
-        assert_eq!(
-            bst_degenerate_left.level_order(),
-            vec![&10, &9, &8, &7, &6, &5, &4, &3, &2, &1, &0]
-        );
+        assert!(by_length.is_valid_bst());
+
+        // 6 is in the left subtree of 5.
+        let mut root = Box::new(BinaryNode::new(5));
+        let mut left = Box::new(BinaryNode::new(3));
+        left.right = Some(Box::new(BinaryNode::new(6)));
+        root.left = Some(left);
+        bst.root = Some(root);
+        assert!(!bst.is_valid_bst());
+
+        let mut root = Box::new(BinaryNode::new(5));
+        root.right = Some(Box::new(BinaryNode::new(5)));
+        bst.root = Some(root);
+        assert!(!bst.is_valid_bst());
     }
 
     #[test]
-    fn level_order_basic() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
-
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            bst_diff_heights_null.insert(value);
-        }
-        for value in &values_2 {
-            bst_diff_heights_one.insert(value);
-        }
-        for value in &values_3 {
-            bst_diff_heights_two.insert(value);
+    fn to_avl_balances_degenerate_tree() {
+        let mut bst = BinarySearchTree::new();
+        for value in 0..1000 {
+            bst.insert(value);
         }
+        assert_eq!(bst.height(), 999);
 
-        assert_eq!(
-            bst_diff_heights_null.level_order(),
-            vec![&&5, &&3, &&7, &&2, &&4, &&6, &&8]
-        );
-
-        assert_eq!(
-            bst_diff_heights_one.level_order(),
-            vec![&&4, &&2, &&6, &&1, &&3, &&5]
-        );
+        let avl = bst.to_avl();
 
-        assert_eq!(
-            bst_diff_heights_two.level_order(),
-            vec![&&8, &&4, &&12, &&2, &&6, &&10, &&1, &&7]
-        );
+        assert!(avl.is_balanced());
+        assert_eq!(avl.height(), 9);
+        assert_eq!(avl.len(), 1000);
+        assert_eq!((avl.min(), avl.max()), (Some(&0), Some(&999)));
+        assert_eq!(avl.in_order(), bst.in_order());
+        assert!(BinarySearchTree::<i32>::new().to_avl().is_empty());
     }
 
     #[test]
-    fn number_of_elements_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn rebalance_flattens_degenerate_tree() {
+        let mut bst = BinarySearchTree::new();
+        bst.rebalance();
+        assert!(bst.is_empty());
 
-        assert_eq!(bst.number_of_elements(), 0);
+        for value in 0..1000 {
+            bst.insert(value);
+        }
+        assert_eq!(bst.height(), 999);
+
+        bst.rebalance();
+
+        assert_eq!(bst.height(), 9);
+        assert!(bst.is_valid_bst());
+        assert_eq!(bst.len(), 1000);
+        assert!(bst.same_elements_as(&(0..1000).collect::<Vec<_>>()));
+        assert!((0..1000).all(|value| bst.find(&value) == Some(value as usize)));
+        assert_eq!((bst.min(), bst.max()), (Some(&0), Some(&999)));
+
+        for len in [1u32, 2, 3, 7, 8, 100] {
+            let mut bst = BinarySearchTree::new();
+            for value in (0..len).rev() {
+                bst.insert(value);
+            }
+            bst.rebalance();
+            assert_eq!(bst.height(), len.ilog2() as usize);
+            assert!(bst.is_valid_bst());
+        }
     }
 
     #[test]
-    fn number_of_elements_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
+    fn display_alternate_draws_tree_sideways() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(format!("{bst:#}"), "<empty>");
 
-        for i in 0..=10 {
-            bst_degenerate_right.insert(i);
-        }
-        for i in (0..=10).rev() {
-            bst_degenerate_left.insert(i);
+        for value in [4, 2, 6, 1, 3, 7] {
+            bst.insert(value);
         }
 
-        assert_eq!(bst_degenerate_right.number_of_elements(), 10 + 1);
-        assert_eq!(bst_degenerate_left.number_of_elements(), 10 + 1);
+        let expected = "        7\n    6\n4\n        3\n    2\n        1";
+        assert_eq!(format!("{bst:#}"), expected);
+        assert_eq!(format!("{bst}"), "{1, 2, 3, 4, 6, 7}");
+    }
+
+    /// Collects the elements of `bst` with `in_order_morris`.
+    fn morris<T: PartialOrd + Clone>(bst: &mut BinarySearchTree<T>) -> Vec<T> {
+        let mut walked = Vec::new();
+        bst.in_order_morris(|value| walked.push(value.clone()));
+        walked
     }
 
     #[test]
-    fn number_of_elements_basic() {
-        let mut bst_diff_heights_null = BinarySearchTree::new();
-        let mut bst_diff_heights_one = BinarySearchTree::new();
-        let mut bst_diff_heights_two = BinarySearchTree::new();
+    fn in_order_morris_matches_in_order() {
+        let mut bst = BinarySearchTree::new();
+        assert!(morris(&mut bst).is_empty());
 
-        let values_1 = vec![5, 3, 7, 2, 4, 6, 8];
-        let values_2 = vec![4, 2, 6, 1, 3, 5];
-        let values_3 = vec![8, 4, 12, 2, 6, 10, 1, 7];
-        for value in &values_1 {
-            bst_diff_heights_null.insert(value);
+        for i in 0..500 {
+            bst.insert((i * 7919) % 1000);
         }
-        for value in &values_2 {
-            bst_diff_heights_one.insert(value);
+        let shape = bst.level_order_with_nulls_cloned();
+        assert_eq!(morris(&mut bst), bst.in_order_cloned());
+        assert_eq!(bst.level_order_with_nulls_cloned(), shape);
+        assert!(bst.is_valid_bst());
+
+        let mut by_length =
+            BinarySearchTree::with_comparator(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+        for word in ["kiwi", "fig", "banana", "apple"] {
+            by_length.insert(word);
         }
-        for value in &values_3 {
-            bst_diff_heights_two.insert(value);
+        assert_eq!(morris(&mut by_length), by_length.in_order_cloned());
+    }
+
+    #[test]
+    fn in_order_morris_walks_degenerate_trees() {
+        let mut ascending = BinarySearchTree::new();
+        let mut descending = BinarySearchTree::new();
+        for value in 0..5000 {
+            ascending.insert(value);
+            descending.insert(4999 - value);
         }
 
-        assert_eq!(bst_diff_heights_null.number_of_elements(), values_1.len());
-        assert_eq!(bst_diff_heights_one.number_of_elements(), values_2.len());
-        assert_eq!(bst_diff_heights_two.number_of_elements(), values_3.len());
+        for bst in [&mut ascending, &mut descending] {
+            assert_eq!(bst.height(), 4999);
+            assert_eq!(morris(bst), (0..5000).collect::<Vec<_>>());
+            assert_eq!(bst.height(), 4999);
+            assert_eq!(bst.len(), 5000);
+            assert_eq!((bst.min(), bst.max()), (Some(&0), Some(&4999)));
+            assert!(bst.is_valid_bst());
+        }
     }
 
     #[test]
-    fn ceil_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
-
-        assert_eq!(bst.ceil(&0), None);
+    fn in_order_morris_restores_links_on_panic() {
+        let mut bst = BinarySearchTree::new();
+        for value in [4, 2, 6, 1, 3, 5, 7] {
+            bst.insert(value);
+        }
+        let shape = bst.level_order_with_nulls_cloned();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            bst.in_order_morris(|&value| assert_ne!(value, 3));
+        }));
+        assert!(result.is_err());
+        assert_eq!(bst.level_order_with_nulls_cloned(), shape);
+        assert_eq!(bst.len(), 7);
+        assert!(bst.is_valid_bst());
     }
 
     #[test]
-    fn ceil_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
+    fn reverse_in_order_descends() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.reverse_in_order().is_empty());
 
-        for i in 0..=10 {
-            let val = i as f64 / 10.0;
-            bst_degenerate_right.insert(val);
+        for i in 0..100 {
+            bst.insert((i * 37) % 100);
         }
 
-        for i in (0..=10).rev() {
-            let val = i as f64 / 10.0;
-            bst_degenerate_left.insert(val);
+        let descending: Vec<i32> = (0..100).rev().collect();
+        let expected: Vec<&i32> = descending.iter().collect();
+        assert_eq!(bst.reverse_in_order(), expected);
+        assert_eq!(bst.iter().rev().collect::<Vec<_>>(), bst.reverse_in_order());
+    }
+
+    #[test]
+    fn zigzag_and_vertical_orders() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.zigzag_level_order().is_empty());
+        assert!(bst.vertical_order().is_empty());
+
+        //          8
+        //        /   \
+        //       4     12
+        //      / \   /  \
+        //     2   6 10   14
+        //    /   /
+        //   1   5
+        for value in [8, 4, 12, 2, 6, 10, 14, 1, 5] {
+            bst.insert(value);
         }
 
-        assert_eq!(bst_degenerate_right.ceil(&0.0), Some(&0.0));
-        assert_eq!(bst_degenerate_left.ceil(&0.0), Some(&0.0));
+        assert_eq!(
+            bst.zigzag_level_order(),
+            vec![
+                vec![&8],
+                vec![&12, &4],
+                vec![&2, &6, &10, &14],
+                vec![&5, &1]
+            ]
+        );
+        assert_eq!(
+            bst.vertical_order(),
+            vec![
+                vec![&1],
+                vec![&2],
+                vec![&4, &5],
+                vec![&8, &6, &10],
+                vec![&12],
+                vec![&14]
+            ]
+        );
+    }
 
-        assert_eq!(bst_degenerate_right.ceil(&(0.03)), Some(&0.1));
-        assert_eq!(bst_degenerate_left.ceil(&(0.03)), Some(&0.1));
-        assert_eq!(bst_degenerate_right.ceil(&(0.07)), Some(&0.1));
-        assert_eq!(bst_degenerate_left.ceil(&(0.07)), Some(&0.1));
+    #[test]
+    fn views_from_each_side() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.left_view().is_empty() && bst.bottom_view().is_empty());
+
+        //          8
+        //        /   \
+        //       4     12
+        //      / \   /  \
+        //     2   6 10   14
+        //    /   /
+        //   1   5
+        for value in [8, 4, 12, 2, 6, 10, 14, 1, 5] {
+            bst.insert(value);
+        }
 
-        assert_eq!(bst_degenerate_right.ceil(&1.1), None);
-        assert_eq!(bst_degenerate_left.ceil(&1.1), None);
+        assert_eq!(bst.left_view(), vec![&8, &4, &2, &1]);
+        assert_eq!(bst.right_view(), vec![&8, &12, &14, &5]);
+        assert_eq!(bst.top_view(), vec![&1, &2, &4, &8, &12, &14]);
+        assert_eq!(bst.bottom_view(), vec![&1, &2, &5, &10, &12, &14]);
     }
 
     #[test]
-    fn ceil_basic() {
-        let mut bst = BinarySearchTree::<i32>::new();
+    fn uncached_tree_holds_non_clone_values() {
+        #[derive(Debug, PartialEq, PartialOrd)]
+        struct Payload(u32, Vec<u8>);
 
-        bst.insert(1);
-        bst.insert(2);
-        bst.insert(5);
+        let mut bst = BinarySearchTree::new_uncached();
+        for key in [5, 3, 8, 1, 4, 9, 3] {
+            bst.insert(Payload(key, vec![0; 16]));
+        }
 
-        assert_eq!(bst.ceil(&6), None);
-        assert_eq!(bst.ceil(&5), Some(&5));
-        assert_eq!(bst.ceil(&4), Some(&5));
-        assert_eq!(bst.ceil(&3), Some(&5));
-        assert_eq!(bst.ceil(&2), Some(&2));
-        assert_eq!(bst.ceil(&1), Some(&1));
-        assert_eq!(bst.ceil(&0), Some(&1));
+        assert_eq!(bst.len(), 6);
+        assert!(bst.contains(&Payload(4, vec![0; 16])));
+        assert!(!bst.contains(&Payload(4, vec![])));
+        assert!(!bst.contains(&Payload(7, vec![0; 16])));
+        assert_eq!(bst.min().map(|p| p.0), Some(1));
+        assert_eq!(bst.max().map(|p| p.0), Some(9));
+
+        let taken = bst.take(&Payload(1, vec![0; 16]));
+        assert_eq!(taken.map(|p| p.0), Some(1));
+        bst.remove(&Payload(9, vec![0; 16]));
+        assert_eq!(bst.len(), 4);
+        assert_eq!(bst.min().map(|p| p.0), Some(3));
+        assert_eq!(bst.max().map(|p| p.0), Some(8));
+        assert!(bst.is_valid_bst());
     }
 
     #[test]
-    fn floor_in_empty_tree() {
-        let bst = BinarySearchTree::<i32>::new();
+    fn uncached_tree_shares_the_non_clone_methods() {
+        #[derive(Debug, PartialEq, PartialOrd)]
+        struct Payload(u32, Vec<u8>);
+        let payload = |key| Payload(key, vec![0; 16]);
+        let keys = |values: Vec<&Payload>| values.iter().map(|p| p.0).collect::<Vec<_>>();
+
+        let mut bst = BinarySearchTree::new_uncached_with_comparator(|a: &Payload, b: &Payload| {
+            b.0.cmp(&a.0)
+        });
+        for key in [5, 3, 8, 1, 4, 9, 6] {
+            bst.insert(payload(key));
+        }
 
-        assert_eq!(bst.floor(&0), None);
+        assert_eq!(keys(bst.in_order()), vec![9, 8, 6, 5, 4, 3, 1]);
+        assert_eq!(keys(bst.iter().rev().collect()), vec![1, 3, 4, 5, 6, 8, 9]);
+        assert_eq!(keys(bst.pre_order()), vec![5, 8, 9, 6, 3, 4, 1]);
+        assert_eq!(keys(bst.post_order()), vec![9, 6, 8, 4, 1, 3, 5]);
+        assert_eq!(keys(bst.level_order()), vec![5, 8, 3, 9, 6, 4, 1]);
+        assert_eq!(bst.height(), 2);
+        assert_eq!((bst.min().unwrap().0, bst.max().unwrap().0), (9, 1));
+
+        assert!(bst.contains(&payload(4)));
+        assert_eq!(bst.ceil(&payload(7)).map(|p| p.0), Some(6));
+        assert_eq!(bst.floor(&payload(7)).map(|p| p.0), Some(8));
+        assert_eq!(bst.successor(&payload(5)).map(|p| p.0), Some(4));
+        assert_eq!(bst.find(&payload(8)), Some(1));
+        assert_eq!(bst.range(&payload(8), &payload(5)).count(), 3);
+
+        let mut rest = bst.split_off(&payload(4));
+        assert_eq!(keys(bst.in_order()), vec![9, 8, 6, 5]);
+        assert_eq!(keys(rest.in_order()), vec![4, 3, 1]);
+        rest.insert(payload(2));
+        assert_eq!(rest.take(&payload(4)).map(|p| p.0), Some(4));
+        assert_eq!((rest.min().unwrap().0, rest.max().unwrap().0), (3, 1));
+        bst.append(&mut rest);
+        assert_eq!(keys(bst.in_order()), vec![9, 8, 6, 5, 3, 2, 1]);
+        assert!(rest.is_empty());
+        assert!(bst.is_valid_bst());
+
+        // Nothing is cached, so the tree is smaller than a cached one.
+        assert!(size_of::<BinarySearchTree<u64, Uncached>>() < size_of::<BinarySearchTree<u64>>());
     }
 
     #[test]
-    fn floor_in_degenerate_trees() {
-        let mut bst_degenerate_right = BinarySearchTree::new();
-        let mut bst_degenerate_left = BinarySearchTree::new();
-
-        for i in 0..=10 {
-            let val = i as f64 / 10.0;
-            bst_degenerate_right.insert(val);
+    fn remove_rescans_only_for_extreme_values() {
+        let mut bst = BinarySearchTree::new();
+        for value in 0..1_000 {
+            bst.insert(value);
         }
+        let refinds = || REFINDS.with(|refinds| refinds.get());
+        let before = refinds();
 
-        for i in (0..=10).rev() {
-            let val = i as f64 / 10.0;
-            bst_degenerate_left.insert(val);
+        for value in (1..999).step_by(3) {
+            bst.remove(&value);
         }
+        bst.remove(&5_000);
+        assert_eq!(refinds(), before);
+        assert_eq!((bst.min(), bst.max()), (Some(&0), Some(&999)));
+
+        bst.remove(&999);
+        assert_eq!(refinds(), before + 1);
+        assert_eq!(bst.max(), Some(&998));
+
+        bst.remove(&0);
+        assert_eq!(refinds(), before + 2);
+        assert_eq!(bst.min(), Some(&2));
+        assert_eq!(bst.in_order().first(), Some(&&2));
+        assert_eq!(bst.in_order().last(), Some(&&998));
+    }
 
-        assert_eq!(bst_degenerate_right.floor(&0.0), Some(&0.0));
-        assert_eq!(bst_degenerate_left.floor(&0.0), Some(&0.0));
+    /// A tree of `values` in descending order (built with a reversing comparator).
+    fn descending(values: &[i32]) -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        for &value in values {
+            bst.insert(value);
+        }
+        bst
+    }
 
-        assert_eq!(bst_degenerate_right.floor(&(0.03)), Some(&0.0));
-        assert_eq!(bst_degenerate_left.floor(&(0.03)), Some(&0.0));
-        assert_eq!(bst_degenerate_right.floor(&(0.07)), Some(&0.0));
-        assert_eq!(bst_degenerate_left.floor(&(0.07)), Some(&0.0));
+    #[test]
+    fn comparator_tree_restructures_in_its_order() {
+        let values = [5, 3, 8, 1, 4, 9, 6];
+
+        let mut bst = descending(&values);
+        let mut other = bst.split_off(&5);
+        assert_eq!(bst.in_order(), vec![&9, &8, &6]);
+        assert_eq!(other.in_order(), vec![&5, &4, &3, &1]);
+        other.insert(7);
+        other.insert(2);
+        assert_eq!(other.in_order(), vec![&7, &5, &4, &3, &2, &1]);
+        assert!(other.is_valid_bst());
+
+        let mut bst = descending(&values);
+        let mut drained = bst.drain_range(&8, &4);
+        assert_eq!(bst.in_order(), vec![&9, &3, &1]);
+        assert_eq!(drained.in_order(), vec![&8, &6, &5, &4]);
+        drained.insert(7);
+        assert_eq!(drained.min(), Some(&8));
+        assert!(drained.is_valid_bst());
+        assert!(bst.drain_range(&3, &9).is_empty());
+
+        let mut bst = descending(&values[..4]);
+        bst.append(&mut descending(&values[4..]));
+        assert_eq!(bst.in_order(), vec![&9, &8, &6, &5, &4, &3, &1]);
+        assert_eq!((bst.min(), bst.max()), (Some(&9), Some(&1)));
+        assert!(bst.is_valid_bst());
+
+        let mut bst = descending(&values);
+        bst.retain_range(&8, &3);
+        assert_eq!(bst.in_order(), vec![&8, &6, &5, &4, &3]);
+        assert_eq!(bst.len(), 5);
+        bst.retain_range(&3, &8);
+        assert!(bst.is_empty());
+
+        let mut bst = BinarySearchTree::with_comparator(|a: &Keyed, b: &Keyed| b.key.cmp(&a.key));
+        for key in values {
+            bst.insert(keyed(key, "old"));
+        }
+        assert_eq!(
+            bst.insert_or_replace(keyed(9, "new")).unwrap().payload,
+            "old"
+        );
+        assert_eq!(bst.insert_or_replace(keyed(7, "new")).map(|k| k.key), None);
+        assert_eq!(bst.min().unwrap().payload, "new");
+        assert_eq!(bst.len(), 8);
+        assert!(bst.is_valid_bst());
 
-        assert_eq!(bst_degenerate_right.floor(&(-0.9)), None);
-        assert_eq!(bst_degenerate_left.floor(&(-0.9)), None);
+        let bst = descending(&values);
+        let bytes = bst.to_bytes();
+        assert_eq!(
+            BinarySearchTree::<i32>::from_bytes(&bytes),
+            Err(DecodeError::NotAscending { index: 1 })
+        );
+        let decoded =
+            BinarySearchTree::from_bytes_with_comparator(&bytes, |a: &i32, b: &i32| b.cmp(a))
+                .unwrap();
+        assert_eq!(decoded.in_order(), bst.in_order());
+        assert_eq!((decoded.min(), decoded.max()), (Some(&9), Some(&1)));
+        assert!(decoded.is_valid_bst());
     }
 
     #[test]
-    fn floor_basic() {
-        let mut bst = BinarySearchTree::<i32>::new();
+    fn comparator_tree_answers_queries_in_its_order() {
+        let bst = descending(&[5, 3, 8, 1, 4, 9, 6]);
 
-        bst.insert(1);
-        bst.insert(2);
-        bst.insert(5);
+        assert_eq!(bst.find(&8), Some(1));
+        assert_eq!(bst.find(&7), None);
 
-        assert_eq!(bst.floor(&6), Some(&5));
-        assert_eq!(bst.floor(&5), Some(&5));
-        assert_eq!(bst.floor(&4), Some(&2));
-        assert_eq!(bst.floor(&3), Some(&2));
-        assert_eq!(bst.floor(&2), Some(&2));
-        assert_eq!(bst.floor(&1), Some(&1));
-        assert_eq!(bst.floor(&0), None);
+        assert!(bst.has_any_in_range(&7, &6));
+        assert!(!bst.has_any_in_range(&7, &7));
+        assert!(!bst.has_any_in_range(&6, &7));
+
+        assert_eq!(bst.index_range(&8, &4), 1..5);
+        assert_eq!(bst.range(&8, &4).collect::<Vec<_>>(), vec![&8, &6, &5, &4]);
+        assert_eq!(bst.range(&4, &8).next(), None);
+
+        assert_eq!(bst.successor(&5), Some(&4));
+        assert_eq!(bst.predecessor(&5), Some(&6));
+        assert_eq!(bst.successor(&1), None);
+        assert_eq!(bst.predecessor(&9), None);
+
+        assert_eq!(bst.nearest_k(&5, 3), vec![&5, &6, &4]);
+        assert_eq!(bst.closest(&7), Some(&8));
+
+        assert_eq!(
+            bst.merge_diff(&descending(&[9, 7, 5])).collect::<Vec<_>>(),
+            vec![
+                DiffItem::Both(&9, &9),
+                DiffItem::Left(&8),
+                DiffItem::Right(&7),
+                DiffItem::Left(&6),
+                DiffItem::Both(&5, &5),
+                DiffItem::Left(&4),
+                DiffItem::Left(&3),
+                DiffItem::Left(&1),
+            ]
+        );
+        assert!(descending(&[9, 5]).is_subset(&bst));
+        assert!(bst.all_comparable());
+
+        let mut cursor = bst.cursor();
+        assert_eq!(cursor.next_page(3), vec![&9, &8, &6]);
+        assert_eq!(cursor.next_page(3), vec![&5, &4, &3]);
+        assert_eq!(cursor.next_page(3), vec![&1]);
+    }
+
+    #[test]
+    fn bulk_built_comparator_tree_is_balanced_and_descending() {
+        let values = (0..1000).map(|i| (i * 7919) % 1000).chain(0..10);
+        let bst = BinarySearchTree::from_unsorted_by(values, |a: &i32, b: &i32| b.cmp(a));
+
+        let descending: Vec<i32> = (0..1000).rev().collect();
+        assert_eq!(bst.in_order_cloned(), descending);
+        assert_eq!(bst.height(), 9);
+        assert_eq!((bst.min(), bst.max()), (Some(&999), Some(&0)));
+        assert!(bst.is_valid_bst());
+
+        // Queries and later insertions use the same comparator.
+        assert_eq!(bst.successor(&500), Some(&499));
+        assert_eq!(bst.range(&3, &1).count(), 3);
+        let mut bst =
+            BinarySearchTree::from_sorted_slice_by(&descending, |a: &i32, b: &i32| b.cmp(a));
+        assert_eq!(bst.height(), 9);
+        assert_eq!(bst.in_order_cloned(), descending);
+        bst.insert(1000);
+        assert_eq!(bst.get_by(|value| 0.cmp(value)), Some(&0));
+        assert_eq!(bst.min(), Some(&1000));
+        assert!(bst.is_valid_bst());
+
+        let keyed_values = [keyed(2, "first"), keyed(1, "x"), keyed(2, "second")];
+        let bst = BinarySearchTree::from_unsorted_by(keyed_values, |a: &Keyed, b: &Keyed| {
+            b.key.cmp(&a.key)
+        });
+        assert_eq!(bst.len(), 2);
+        assert_eq!(bst.min().unwrap().payload, "first");
     }
 }
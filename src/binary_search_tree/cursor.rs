@@ -0,0 +1,127 @@
+use super::node::BinaryNode;
+use super::{BinarySearchTree, Kind};
+use crate::order::Order;
+
+/// A read position over the elements of a `BinarySearchTree` in ascending order (the order of
+/// the tree, see `BinarySearchTree::with_comparator`), used for pagination.
+///
+/// The cursor remembers the last element it returned and resumes right after it, so every page
+/// costs *O*(log n + page size) regardless of how many elements were already read.
+#[derive(Debug)]
+pub struct Cursor<'a, T: PartialOrd> {
+    /// Root node of the tree being read.
+    root: &'a Option<Box<BinaryNode<T>>>,
+
+    /// Order of the elements of the tree being read.
+    order: &'a Order<T>,
+
+    /// The last element returned (`None` before the first page).
+    last: Option<&'a T>,
+}
+
+impl<'a, T: PartialOrd> Cursor<'a, T> {
+    /// Creates a cursor positioned before the smallest element of the `tree`.
+    pub(super) fn new<C: Kind<T>>(tree: &'a BinarySearchTree<T, C>) -> Self {
+        Cursor {
+            root: &tree.root,
+            order: &tree.order,
+            last: None,
+        }
+    }
+
+    /// Returns the next (at most) `n` elements in ascending order and advances past them.
+    ///
+    /// An empty vector means that all elements have been read.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n + n)
+    /// - Worst: *O*(h + n), where `h` is the height of the tree (degenerate/unbalanced trees)
+    pub fn next_page(&mut self, n: usize) -> Vec<&'a T> {
+        let mut page = Vec::with_capacity(n);
+        if n == 0 {
+            return page;
+        }
+
+        // Descend to the first element greater than the last returned one, keeping the
+        // ancestors that come after it on the stack.
+        let mut stack = Vec::new();
+        let mut current = self.root;
+        while let Some(node) = current {
+            if self
+                .last
+                .is_some_and(|last| self.order.less_or_equal(&node.value, last))
+            {
+                current = &node.right;
+            } else {
+                stack.push(node);
+                current = &node.left;
+            }
+        }
+
+        while page.len() < n {
+            let Some(node) = stack.pop() else {
+                break;
+            };
+            page.push(&node.value);
+
+            let mut current = &node.right;
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+        }
+
+        if let Some(&last) = page.last() {
+            self.last = Some(last);
+        }
+
+        page
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_cover_all_elements_without_gaps_or_overlaps() {
+        let mut bst = BinarySearchTree::new();
+        for i in 0..1000 {
+            bst.insert((i * 7919) % 1000);
+        }
+
+        let mut cursor = bst.cursor();
+        let mut read: Vec<&i32> = Vec::new();
+        for page_number in 0..10 {
+            let page = cursor.next_page(100);
+            assert_eq!(page.len(), 100);
+            assert_eq!(page.first(), Some(&&(page_number * 100)));
+            read.extend(page);
+        }
+
+        assert!(cursor.next_page(100).is_empty());
+        assert_eq!(read, bst.in_order());
+    }
+
+    #[test]
+    fn last_page_may_be_shorter() {
+        let mut bst = BinarySearchTree::new();
+        for i in 1..=5 {
+            bst.insert(i);
+        }
+
+        let mut cursor = bst.cursor();
+        assert!(cursor.next_page(0).is_empty());
+        assert_eq!(cursor.next_page(2), vec![&1, &2]);
+        assert_eq!(cursor.next_page(2), vec![&3, &4]);
+        assert_eq!(cursor.next_page(2), vec![&5]);
+        assert!(cursor.next_page(2).is_empty());
+
+        assert!(
+            BinarySearchTree::<i32>::new()
+                .cursor()
+                .next_page(10)
+                .is_empty()
+        );
+    }
+}
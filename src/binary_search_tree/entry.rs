@@ -0,0 +1,169 @@
+use super::{BinarySearchTree, Cached, Kind};
+use std::fmt;
+
+/// The place of a value in a `BinarySearchTree`, returned by `BinarySearchTree::entry`:
+/// either the stored element equal to it or the value ready to be inserted.
+#[derive(Debug)]
+pub enum Entry<'a, T: PartialOrd, C: Kind<T> = Cached> {
+    /// An element equal to the value is already stored in the tree.
+    ///
+    /// Changing the element in a way that changes its ordering breaks the BST invariant.
+    Occupied(&'a mut T),
+
+    /// No equal element is stored, the entry holds the value until it is inserted.
+    Vacant(VacantEntry<'a, T, C>),
+}
+
+/// A value absent from a `BinarySearchTree`, ready to be inserted (see `Entry::Vacant`).
+pub struct VacantEntry<'a, T: PartialOrd, C: Kind<T> = Cached> {
+    /// The value to insert.
+    pub(super) value: T,
+
+    /// The tree the value belongs to.
+    pub(super) tree: &'a mut BinarySearchTree<T, C>,
+}
+
+impl<T: PartialOrd + fmt::Debug, C: Kind<T>> fmt::Debug for VacantEntry<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VacantEntry")
+            .field("value", &self.value)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T: PartialOrd, C: Kind<T>> Entry<'a, T, C> {
+    /// Inserts the value if the entry is vacant and returns a mutable reference to the stored
+    /// element (the already present one if the entry is occupied).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - the value is linked in a second descent, which updates the
+    ///   subtree sizes along the path
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    /// - Best: *O*(1) (occupied entry)
+    pub fn or_insert(self) -> &'a mut T {
+        match self {
+            Entry::Occupied(element) => element,
+            Entry::Vacant(entry) => entry.insert(),
+        }
+    }
+}
+
+impl<'a, T: PartialOrd, C: Kind<T>> VacantEntry<'a, T, C> {
+    /// Returns a reference to the value that would be inserted.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Inserts the value into the tree and returns a mutable reference to it.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n) - the value is linked in a second descent, which updates the
+    ///   subtree sizes along the path
+    /// - Worst: *O*(n) (degenerate/unbalanced trees)
+    pub fn insert(self) -> &'a mut T {
+        self.tree.insert_vacant(self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_search_tree::BinarySearchTree;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn or_insert_inserts_once() {
+        let mut bst = BinarySearchTree::new();
+        for value in [5, 3, 8] {
+            bst.insert(value);
+        }
+
+        assert!(matches!(bst.entry(4), Entry::Vacant(ref entry) if entry.value() == &4));
+        assert_eq!(bst.len(), 3);
+        assert_eq!(bst.entry(4).or_insert(), &mut 4);
+        assert!(matches!(bst.entry(4), Entry::Occupied(&mut 4)));
+        assert_eq!(bst.entry(4).or_insert(), &mut 4);
+
+        assert_eq!(bst.in_order(), vec![&3, &4, &5, &8]);
+        assert_eq!(bst.len(), 4);
+        assert_eq!(bst.find(&5), Some(2));
+    }
+
+    #[test]
+    fn or_insert_updates_min_and_max() {
+        let mut bst = BinarySearchTree::new();
+        bst.entry(5).or_insert();
+        bst.entry(1).or_insert();
+        bst.entry(9).or_insert();
+        bst.entry(3).or_insert();
+
+        assert_eq!(bst.min(), Some(&1));
+        assert_eq!(bst.max(), Some(&9));
+        assert_eq!(bst.height(), 2);
+    }
+
+    #[test]
+    fn entry_compares_only_along_the_path() {
+        let comparisons = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&comparisons);
+        let mut bst = BinarySearchTree::with_comparator(move |a: &i32, b: &i32| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            a.cmp(b)
+        });
+        for value in [4, 2, 6, 1, 3, 5, 7] {
+            bst.insert(value);
+        }
+
+        // A vacant entry is searched once, checked against the cached bounds and linked in
+        // a second descent, like in `insert`.
+        comparisons.store(0, Ordering::Relaxed);
+        bst.entry(8).or_insert();
+        assert_eq!(comparisons.load(Ordering::Relaxed), 3 + 2 + 3);
+
+        // An occupied entry is found once and borrowed in a second descent.
+        comparisons.store(0, Ordering::Relaxed);
+        bst.entry(6).or_insert();
+        assert_eq!(comparisons.load(Ordering::Relaxed), 2 + 2);
+        assert_eq!(bst.len(), 8);
+        assert_eq!(bst.max(), Some(&8));
+    }
+
+    #[test]
+    fn incomparable_value_is_given_back() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(1.0);
+
+        let error = bst.try_entry(f64::NAN).unwrap_err();
+        assert!(error.value.is_nan());
+        assert_eq!(bst.len(), 1);
+        assert!(matches!(bst.try_entry(1.0), Ok(Entry::Occupied(&mut 1.0))));
+    }
+
+    #[test]
+    #[should_panic(expected = "incomparable")]
+    fn entry_of_incomparable_value_panics() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(1.0);
+        bst.entry(f64::NAN);
+    }
+
+    #[test]
+    fn uncached_tree_has_entries() {
+        #[derive(Debug, PartialEq, PartialOrd)]
+        struct Payload(u32);
+
+        let mut bst = BinarySearchTree::new_uncached();
+        for key in [2, 1, 3, 1] {
+            bst.entry(Payload(key)).or_insert();
+        }
+
+        assert!(matches!(
+            bst.entry(Payload(3)),
+            Entry::Occupied(&mut Payload(3))
+        ));
+        assert_eq!(bst.len(), 3);
+        assert_eq!(bst.in_order(), vec![&Payload(1), &Payload(2), &Payload(3)]);
+        assert_eq!(bst.min(), Some(&Payload(1)));
+    }
+}
@@ -0,0 +1,126 @@
+use super::node::BinaryNode;
+use super::{BinarySearchTree, Kind};
+
+/// A lazy iterator over the elements of a `BinarySearchTree` in ascending order
+/// (descending from the back), created by `BinarySearchTree::iter`.
+///
+/// Only the nodes on the paths to the next elements from both ends are kept on the stacks,
+/// so no vector of all elements is built.
+#[derive(Debug)]
+pub struct Iter<'a, T: PartialOrd> {
+    /// Ancestors of the next element from the front, the next one on top.
+    front: Vec<&'a BinaryNode<T>>,
+
+    /// Ancestors of the next element from the back, the next one on top.
+    back: Vec<&'a BinaryNode<T>>,
+
+    /// The number of elements not yet returned from either end.
+    remaining: usize,
+}
+
+impl<'a, T: PartialOrd> Iter<'a, T> {
+    /// Creates an iterator over all elements of the `tree`.
+    pub(super) fn new<C: Kind<T>>(tree: &'a BinarySearchTree<T, C>) -> Self {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            remaining: BinaryNode::size(&tree.root),
+        };
+        iter.push_left_spine(&tree.root);
+        iter.push_right_spine(&tree.root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut current: &'a Option<Box<BinaryNode<T>>>) {
+        while let Some(node) = current {
+            self.front.push(node);
+            current = &node.left;
+        }
+    }
+
+    fn push_right_spine(&mut self, mut current: &'a Option<Box<BinaryNode<T>>>) {
+        while let Some(node) = current {
+            self.back.push(node);
+            current = &node.right;
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.front.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: PartialOrd> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.back.pop()?;
+        self.push_right_spine(&node.left);
+        Some(&node.value)
+    }
+}
+
+impl<T: PartialOrd> ExactSizeIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterates_in_both_directions() {
+        let mut bst = BinarySearchTree::new();
+        assert_eq!(bst.iter().next(), None);
+        assert_eq!(bst.iter().next_back(), None);
+
+        for value in [50, 20, 80, 10, 30, 60, 90, 25, 35] {
+            bst.insert(value);
+        }
+
+        assert_eq!(bst.iter().collect::<Vec<_>>(), bst.in_order());
+        assert_eq!(
+            bst.iter().rev().collect::<Vec<_>>(),
+            vec![&90, &80, &60, &50, &35, &30, &25, &20, &10]
+        );
+        assert_eq!(bst.iter().len(), 9);
+    }
+
+    #[test]
+    fn ends_meet_without_overlap() {
+        let mut bst = BinarySearchTree::new();
+        for value in [4, 2, 6, 1, 3, 5, 7] {
+            bst.insert(value);
+        }
+
+        let mut iter = bst.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&7));
+        assert_eq!(iter.next_back(), Some(&6));
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&4));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        assert!(bst.iter().take_while(|&&value| value < 3).eq([&1, &2]));
+    }
+}
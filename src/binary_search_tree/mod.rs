@@ -3,10 +3,23 @@ mod bst_operations;
 /// Internal implementation of `BinarySearchTree` nodes.
 pub mod node;
 
+/// Paginated reading in ascending order.
+pub mod cursor;
+
+/// Lazy iteration in ascending order.
+pub mod iter;
+
+/// Insertion through the place of a value (see `BinarySearchTree::entry`).
+pub mod entry;
+
 /// For visualizing (Graphviz, DOT format).
 pub mod visualization;
 
+use crate::order::Order;
+use crate::pool::NodePool;
 use node::BinaryNode;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
 
 /// A binary search tree implementation.
 ///
@@ -14,14 +27,153 @@ use node::BinaryNode;
 /// - All values in the left subtree are less than the node's value
 /// - All values in the right subtree are greater than the node's value
 /// - Duplicate values are not allowed
-#[derive(Debug)]
-pub struct BinarySearchTree<T: PartialOrd + Clone> {
+///
+/// "Less" and "greater" follow the `PartialOrd` implementation of `T` (or the comparator of a
+/// tree built `with_comparator`), so the order can also be reversed with the
+/// `std::cmp::Reverse` wrapper.
+///
+/// # Example:
+///
+/// ```
+/// use data_forest::binary_search_tree::BinarySearchTree;
+/// use std::cmp::Reverse;
+///
+/// let mut bst = BinarySearchTree::new();
+/// for value in [2, 3, 1] {
+///     bst.insert(Reverse(value));
+/// }
+///
+/// assert_eq!(bst.in_order(), vec![&Reverse(3), &Reverse(2), &Reverse(1)]);
+/// assert_eq!(bst.min(), Some(&Reverse(3)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinarySearchTree<T: PartialOrd, C: Kind<T> = Cached> {
     /// Root node of the tree (private to maintain invariants)
     root: Option<Box<BinaryNode<T>>>,
 
-    /// Cached minimum value (None if tree is empty)
-    min_value: Option<T>,
+    /// Cached minimum value in the order of the tree (None if tree is empty, `()` if the kind
+    /// doesn't cache it)
+    min_value: C::Bound,
+
+    /// Cached maximum value in the order of the tree (None if tree is empty, `()` if the kind
+    /// doesn't cache it)
+    max_value: C::Bound,
+
+    /// Node allocations kept for reuse (only populated with the `pool` feature)
+    pool: NodePool<BinaryNode<T>>,
+
+    /// Order of the elements (`PartialOrd` unless built with `with_comparator`)
+    order: Order<T>,
+
+    /// The kind of the tree (`Cached` or `Uncached`)
+    kind: PhantomData<C>,
+}
+
+/// The default kind of `BinarySearchTree`: clones of the minimum and maximum elements are
+/// cached, so `min`/`max` are *O*(1), but the elements must be `Clone`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Cached;
+
+/// The kind of `BinarySearchTree` created by `BinarySearchTree::new_uncached`: the minimum and
+/// maximum are found by walking the tree in *O*(h), so the elements don't have to be `Clone`.
+///
+/// Such a tree provides all the methods that don't return or store clones of the elements.
+/// Its lookups (`contains`, `get`, `ceil`, `floor`, `take`/`remove`) take an element instead
+/// of any borrowed form of it, so that they can use a custom comparator (see
+/// `new_uncached_with_comparator`) without converting the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Uncached;
+
+/// The kinds of `BinarySearchTree` (`Cached` and `Uncached`), deciding whether the tree keeps
+/// clones of its minimum and maximum elements.
+pub trait Kind<T>: sealed::Sealed {
+    /// What the tree stores for its minimum and for its maximum.
+    #[doc(hidden)]
+    type Bound;
+
+    /// Makes the stored bound out of the element returned by `find`, which is only called
+    /// if the kind caches the bounds.
+    #[doc(hidden)]
+    fn bound<'a>(find: impl FnOnce() -> Option<&'a T>) -> Self::Bound
+    where
+        T: 'a;
+
+    /// Returns the cached element (`None` if the tree is empty or the kind caches nothing).
+    #[doc(hidden)]
+    fn cached(bound: &Self::Bound) -> Option<&T>;
+}
+
+impl<T: Clone> Kind<T> for Cached {
+    type Bound = Option<T>;
+
+    fn bound<'a>(find: impl FnOnce() -> Option<&'a T>) -> Self::Bound
+    where
+        T: 'a,
+    {
+        find().cloned()
+    }
+
+    fn cached(bound: &Self::Bound) -> Option<&T> {
+        bound.as_ref()
+    }
+}
+
+impl<T> Kind<T> for Uncached {
+    type Bound = ();
+
+    fn bound<'a>(_find: impl FnOnce() -> Option<&'a T>) -> Self::Bound
+    where
+        T: 'a,
+    {
+    }
+
+    fn cached(_bound: &Self::Bound) -> Option<&T> {
+        None
+    }
+}
+
+mod sealed {
+    /// Keeps `Kind` from being implemented outside of the crate.
+    pub trait Sealed {}
+
+    impl Sealed for super::Cached {}
+    impl Sealed for super::Uncached {}
+}
 
-    /// Cached maximum value (None if tree is empty)
-    max_value: Option<T>,
+impl<T: PartialOrd, C: Kind<T>> BinarySearchTree<T, C> {
+    /// Check BST invariant for Binary Search Tree (in the order of the tree, see
+    /// `with_comparator`).
+    pub fn is_valid_bst(&self) -> bool {
+        fn check<T: PartialOrd>(
+            order: &Order<T>,
+            node: &Option<Box<BinaryNode<T>>>,
+            min: Option<&T>,
+            max: Option<&T>,
+        ) -> bool {
+            match node {
+                Some(node) => {
+                    if let Some(min_val) = min
+                        && matches!(
+                            order.compare(&node.value, min_val),
+                            Some(Ordering::Less | Ordering::Equal)
+                        )
+                    {
+                        return false;
+                    }
+                    if let Some(max_val) = max
+                        && matches!(
+                            order.compare(&node.value, max_val),
+                            Some(Ordering::Greater | Ordering::Equal)
+                        )
+                    {
+                        return false;
+                    }
+                    check(order, &node.left, min, Some(&node.value))
+                        && check(order, &node.right, Some(&node.value), max)
+                }
+                None => true,
+            }
+        }
+        check(&self.order, &self.root, None, None)
+    }
 }
@@ -2,7 +2,7 @@
 ///
 /// Each node contains a `value` of generic type `T`
 /// and optional left/right child nodes wrapped in `Box` for heap allocation.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BinaryNode<T: PartialOrd> {
     /// The value stored in this node.
     pub value: T,
@@ -12,6 +12,9 @@ pub struct BinaryNode<T: PartialOrd> {
 
     /// Right child node (greater than parent value).
     pub right: Option<Box<BinaryNode<T>>>,
+
+    /// Number of nodes in this node's subtree (leaf nodes have size 1).
+    pub size: usize,
 }
 
 impl<T: PartialOrd> BinaryNode<T> {
@@ -21,6 +24,17 @@ impl<T: PartialOrd> BinaryNode<T> {
             value,
             left: None,
             right: None,
+            size: 1,
         }
     }
+
+    /// Returns the size of a node's subtree (0 for `None`).
+    pub fn size(node: &Option<Box<Self>>) -> usize {
+        node.as_ref().map_or(0, |n| n.size)
+    }
+
+    /// Updates this node's subtree size based on children's sizes.
+    pub fn update_size(&mut self) {
+        self.size = 1 + Self::size(&self.left) + Self::size(&self.right);
+    }
 }
@@ -2,25 +2,33 @@ use std::fs::File;
 use std::io;
 use std::io::Write;
 
-/// Converts pairs of connections between `BinaryNode`s in `BinarySearchTree` to graphviz description.
-pub fn convert_to_graphviz<T: std::fmt::Display>(
+/// Writes pairs of connections between `BinaryNode`s in `BinarySearchTree` as a graphviz description
+/// into any writer (a file, a buffer or stdout).
+pub fn write_graphviz<T: std::fmt::Display, W: Write>(
     connections: &[(T, T)],
-    filename: &str,
+    out: &mut W,
 ) -> io::Result<()> {
-    let mut file = File::create(filename)?;
-
-    writeln!(&mut file, "digraph BST {{")?;
-    writeln!(&mut file, "    node [shape=circle];")?;
+    writeln!(out, "digraph BST {{")?;
+    writeln!(out, "    node [shape=circle];")?;
 
     for (parent, child) in connections {
-        writeln!(&mut file, "    {parent} -> {child};")?;
+        writeln!(out, "    {parent} -> {child};")?;
     }
 
-    writeln!(&mut file, "}}")?;
+    writeln!(out, "}}")?;
 
     Ok(())
 }
 
+/// Converts pairs of connections between `BinaryNode`s in `BinarySearchTree` to graphviz description.
+pub fn convert_to_graphviz<T: std::fmt::Display>(
+    connections: &[(T, T)],
+    filename: &str,
+) -> io::Result<()> {
+    let mut file = File::create(filename)?;
+    write_graphviz(connections, &mut file)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,4 +107,21 @@ mod tests {
         convert_to_graphviz(&connections, "dots/BST/empty_tree.dot").unwrap();
         assert!(Path::new("dots/BST/empty_tree.dot").exists());
     }
+
+    #[test]
+    fn write_graphviz_into_buffer() {
+        let mut bst = BinarySearchTree::new();
+        for value in [2, 1, 3] {
+            bst.insert(value);
+        }
+
+        let mut buf = Vec::new();
+        write_graphviz(&bst.find_connections(), &mut buf).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+
+        assert!(dot.starts_with("digraph BST {\n"));
+        assert!(dot.contains("    2 -> 1;\n"));
+        assert!(dot.contains("    2 -> 3;\n"));
+        assert!(dot.ends_with("}\n"));
+    }
 }
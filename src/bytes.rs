@@ -0,0 +1,115 @@
+use crate::error::DecodeError;
+use crate::order::Order;
+use std::cmp::Ordering;
+
+/// Values with a fixed-size little-endian byte representation, which makes the trees
+/// of them serializable with `to_bytes` / `from_bytes`.
+///
+/// Implemented for all integer (except `isize` and `usize`, whose size depends on the platform)
+/// and floating point primitives.
+pub trait LeBytes: Sized {
+    /// The number of bytes a value is encoded with.
+    const SIZE: usize;
+
+    /// Appends the little-endian bytes of the value to `out`.
+    fn write_le_bytes(&self, out: &mut Vec<u8>);
+
+    /// Reads a value from exactly `SIZE` little-endian `bytes`.
+    fn read_le_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_le_bytes {
+    ($($t:ty),*) => {
+        $(
+            impl LeBytes for $t {
+                const SIZE: usize = size_of::<$t>();
+
+                fn write_le_bytes(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_le_bytes(bytes: &[u8]) -> Self {
+                    let mut buffer = [0; size_of::<$t>()];
+                    buffer.copy_from_slice(bytes);
+                    <$t>::from_le_bytes(buffer)
+                }
+            }
+        )*
+    };
+}
+
+impl_le_bytes!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// The number of bytes of the length prefix (a little-endian `u64`).
+const LENGTH_SIZE: usize = size_of::<u64>();
+
+/// Encodes ascending `values` (in the order of their tree) as their number followed by the
+/// values themselves.
+pub(crate) fn encode<'a, T: LeBytes + 'a>(values: impl ExactSizeIterator<Item = &'a T>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(LENGTH_SIZE + values.len() * T::SIZE);
+    out.extend_from_slice(&(values.len() as u64).to_le_bytes());
+    for value in values {
+        value.write_le_bytes(&mut out);
+    }
+    out
+}
+
+/// Decodes the output of `encode`, checking that the values are strictly ascending in the `order`.
+pub(crate) fn decode_ascending<T: LeBytes + PartialOrd>(
+    data: &[u8],
+    order: &Order<T>,
+) -> Result<Vec<T>, DecodeError> {
+    let decoded = decode(data)?;
+    let mut values: Vec<T> = Vec::with_capacity(decoded.len());
+    for (index, value) in decoded.enumerate() {
+        let ascending = values.last().is_none_or(|last| order.less(last, &value));
+        if !ascending {
+            return Err(DecodeError::NotAscending { index });
+        }
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+/// Decodes the output of `encode` for a multiset, collapsing the runs of equal values into
+/// (value, count) pairs and checking that the runs are strictly ascending in the `order`.
+pub(crate) fn decode_counted<T: LeBytes + PartialOrd>(
+    data: &[u8],
+    order: &Order<T>,
+) -> Result<Vec<(T, usize)>, DecodeError> {
+    let mut runs: Vec<(T, usize)> = Vec::new();
+    for (index, value) in decode(data)?.enumerate() {
+        match runs.last_mut() {
+            Some((last, count)) if order.compare(last, &value) == Some(Ordering::Equal) => {
+                *count += 1;
+            }
+            Some((last, _)) if !order.less(last, &value) => {
+                return Err(DecodeError::NotAscending { index });
+            }
+            _ => runs.push((value, 1)),
+        }
+    }
+
+    Ok(runs)
+}
+
+/// Checks the length prefix of the output of `encode` and returns an iterator over the values.
+fn decode<T: LeBytes>(data: &[u8]) -> Result<impl ExactSizeIterator<Item = T>, DecodeError> {
+    let (length, body) = data
+        .split_first_chunk::<LENGTH_SIZE>()
+        .ok_or(DecodeError::MissingLength)?;
+    let declared = u64::from_le_bytes(*length);
+
+    let expected = usize::try_from(declared)
+        .ok()
+        .and_then(|len| len.checked_mul(T::SIZE));
+    if expected != Some(body.len()) {
+        return Err(DecodeError::LengthMismatch {
+            declared,
+            bytes: body.len(),
+        });
+    }
+
+    Ok(body.chunks_exact(T::SIZE).map(T::read_le_bytes))
+}
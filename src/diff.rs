@@ -0,0 +1,13 @@
+/// An element of the merge of two trees in ascending order, classified by the trees it is
+/// present in (see `merge_diff`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffItem<'a, T> {
+    /// The element is present only in the first tree.
+    Left(&'a T),
+
+    /// The element is present only in the second tree.
+    Right(&'a T),
+
+    /// Equal elements are present in both trees (the first one from the first tree).
+    Both(&'a T, &'a T),
+}
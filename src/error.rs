@@ -0,0 +1,74 @@
+use std::fmt;
+
+/// The error returned when building a tree out of values that contain a duplicate
+/// (see the `TryFrom<Vec<T>>` implementations of the trees).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateError<T> {
+    /// The first value that compared equal to an earlier one.
+    pub value: T,
+}
+
+impl<T: fmt::Debug> fmt::Display for DuplicateError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate value: {:?}", self.value)
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for DuplicateError<T> {}
+
+/// The error returned when a value can't be placed in a tree because it is incomparable to an
+/// element on its search path, e.g. `NaN` (see `BinarySearchTree::entry`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncomparableError<T> {
+    /// The rejected value.
+    pub value: T,
+}
+
+impl<T: fmt::Debug> fmt::Display for IncomparableError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "incomparable value: {:?}", self.value)
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for IncomparableError<T> {}
+
+/// The error returned when decoding a tree from malformed bytes (see `from_bytes`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data is shorter than the length prefix.
+    MissingLength,
+
+    /// The number of bytes after the length prefix does not match the declared number of values.
+    LengthMismatch {
+        /// The number of values declared by the length prefix.
+        declared: u64,
+
+        /// The number of bytes following the length prefix.
+        bytes: usize,
+    },
+
+    /// The value at `index` is not greater than the previous one.
+    NotAscending {
+        /// The position of the offending value in the encoded sequence.
+        index: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::MissingLength => write!(f, "missing length prefix"),
+            DecodeError::LengthMismatch { declared, bytes } => {
+                write!(f, "{bytes} bytes do not hold {declared} values")
+            }
+            DecodeError::NotAscending { index } => {
+                write!(
+                    f,
+                    "value at index {index} is not greater than the previous one"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
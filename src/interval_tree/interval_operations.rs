@@ -0,0 +1,306 @@
+use super::*;
+use std::cmp::Ordering;
+
+impl<T: PartialOrd + Clone> IntervalTree<T> {
+    /// Creates a new empty `IntervalTree`.
+    pub fn new() -> Self {
+        IntervalTree { root: None }
+    }
+
+    /// Checks if the tree is empty.
+    ///
+    /// # Complexity:
+    /// *O*(1) - checks if root is `None`.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Inserts a closed `interval` `(start, end)` into the tree while maintaining AVL balance
+    /// properties and the maximum ends of the subtrees.
+    ///
+    /// Intervals with `start > end` (or with incomparable ends) are ignored.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn insert(&mut self, interval: (T, T)) {
+        if !matches!(
+            interval.0.partial_cmp(&interval.1),
+            Some(Ordering::Less | Ordering::Equal)
+        ) {
+            return;
+        }
+
+        self.root = Self::insert_rec(self.root.take(), interval);
+    }
+
+    fn insert_rec(
+        node: Option<Box<IntervalNode<T>>>,
+        interval: (T, T),
+    ) -> Option<Box<IntervalNode<T>>> {
+        match node {
+            None => Some(Box::new(IntervalNode::new(interval))),
+            Some(mut n) => {
+                match interval.partial_cmp(&n.interval) {
+                    Some(Ordering::Less) => {
+                        n.left = Self::insert_rec(n.left.take(), interval);
+                    }
+                    Some(Ordering::Greater) => {
+                        n.right = Self::insert_rec(n.right.take(), interval);
+                    }
+                    _ => return Some(n),
+                }
+
+                n.update();
+                Some(n.rebalance())
+            }
+        }
+    }
+
+    /// Removes an `interval` from the tree while maintaining AVL balance properties
+    /// and the maximum ends of the subtrees.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn remove(&mut self, interval: &(T, T)) {
+        self.root = Self::remove_node(self.root.take(), interval);
+    }
+
+    fn remove_node(
+        node: Option<Box<IntervalNode<T>>>,
+        interval: &(T, T),
+    ) -> Option<Box<IntervalNode<T>>> {
+        let mut n = node?;
+
+        match interval.partial_cmp(&n.interval) {
+            Some(Ordering::Less) => {
+                n.left = Self::remove_node(n.left.take(), interval);
+            }
+            Some(Ordering::Greater) => {
+                n.right = Self::remove_node(n.right.take(), interval);
+            }
+            Some(Ordering::Equal) => match (n.left.take(), n.right.take()) {
+                (Some(left), Some(right)) => {
+                    let (min_interval, new_right) = Self::detach_min(right);
+                    n.interval = min_interval;
+                    n.left = Some(left);
+                    n.right = new_right;
+                }
+                (left, right) => return left.or(right),
+            },
+            None => return Some(n),
+        }
+
+        n.update();
+        Some(n.rebalance())
+    }
+
+    fn detach_min(mut node: Box<IntervalNode<T>>) -> ((T, T), Option<Box<IntervalNode<T>>>) {
+        match node.left.take() {
+            Some(left) => {
+                let (min_interval, new_left) = Self::detach_min(left);
+                node.left = new_left;
+                node.update();
+                (min_interval, Some(node.rebalance()))
+            }
+            None => (node.interval, node.right),
+        }
+    }
+
+    /// Checks if the tree contains an `interval`.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - guaranteed due to AVL balancing.
+    pub fn contains(&self, interval: &(T, T)) -> bool {
+        let mut current = &self.root;
+
+        while let Some(node) = current {
+            match interval.partial_cmp(&node.interval) {
+                Some(Ordering::Less) => current = &node.left,
+                Some(Ordering::Greater) => current = &node.right,
+                Some(Ordering::Equal) => return true,
+                None => return false,
+            }
+        }
+
+        false
+    }
+
+    /// Returns references to all stored intervals overlapping the closed interval `query`
+    /// (i.e. the intervals `(start, end)` with `start <= query.1` and `end >= query.0`),
+    /// ordered by `start`.
+    ///
+    /// Subtrees whose maximum end lies before `query.0`, and right subtrees of the nodes
+    /// starting after `query.1`, are skipped.
+    ///
+    /// # Complexity:
+    /// *O*(log n + k) for balanced trees, where `k` is the number of returned intervals.
+    ///
+    /// # Example:
+    ///
+    /// If the intervals `(1, 3)`, `(2, 6)`, `(8, 9)` are stored, then `overlapping((3, 7))`
+    /// returns `vec![&(1, 3), &(2, 6)]`.
+    pub fn overlapping(&self, query: (T, T)) -> Vec<&(T, T)> {
+        let mut result = Vec::new();
+        Self::collect_overlapping(&self.root, &query, &mut result);
+        result
+    }
+
+    fn collect_overlapping<'a>(
+        node: &'a Option<Box<IntervalNode<T>>>,
+        query: &(T, T),
+        result: &mut Vec<&'a (T, T)>,
+    ) {
+        let Some(node) = node else {
+            return;
+        };
+
+        // Every interval of this subtree ends before the query starts
+        if node.max_end < query.0 {
+            return;
+        }
+
+        Self::collect_overlapping(&node.left, query, result);
+
+        // This interval and the whole right subtree start after the query ends
+        if node.interval.0 > query.1 {
+            return;
+        }
+
+        if node.interval.1 >= query.0 {
+            result.push(&node.interval);
+        }
+
+        Self::collect_overlapping(&node.right, query, result);
+    }
+
+    /// Returns references to the intervals of the tree ordered by `start` (then by `end`).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn in_order(&self) -> Vec<&(T, T)> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+
+            if let Some(node) = stack.pop() {
+                result.push(&node.interval);
+                current = &node.right;
+            }
+        }
+
+        result
+    }
+
+    /// Returns the number of intervals stored in the tree.
+    ///
+    /// # Complexity:
+    /// *O*(n) - traverses entire tree.
+    pub fn number_of_elements(&self) -> usize {
+        self.in_order().len()
+    }
+}
+
+impl<T: PartialOrd + Clone> Default for IntervalTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree_of(intervals: &[(i32, i32)]) -> IntervalTree<i32> {
+        let mut tree = IntervalTree::new();
+        for &interval in intervals {
+            tree.insert(interval);
+        }
+        tree
+    }
+
+    #[test]
+    fn is_and_isnt_empty_tree() {
+        let mut tree = IntervalTree::new();
+        assert!(tree.is_empty());
+
+        tree.insert((1, 2));
+        assert!(!tree.is_empty());
+    }
+
+    #[test]
+    fn insert_ignores_duplicates_and_reversed_intervals() {
+        let tree = tree_of(&[(1, 5), (1, 5), (4, 2), (1, 3)]);
+
+        assert_eq!(tree.in_order(), vec![&(1, 3), &(1, 5)]);
+        assert!(tree.contains(&(1, 5)));
+        assert!(!tree.contains(&(4, 2)));
+        assert!(tree.is_valid());
+    }
+
+    #[test]
+    fn overlapping_in_empty_tree() {
+        let tree = IntervalTree::<i32>::new();
+        assert!(tree.overlapping((0, 10)).is_empty());
+    }
+
+    #[test]
+    fn overlapping_nested_intervals() {
+        let tree = tree_of(&[(0, 100), (10, 50), (20, 30), (24, 26)]);
+
+        assert_eq!(
+            tree.overlapping((25, 25)),
+            vec![&(0, 100), &(10, 50), &(20, 30), &(24, 26)]
+        );
+        assert_eq!(tree.overlapping((40, 60)), vec![&(0, 100), &(10, 50)]);
+        assert_eq!(tree.overlapping((60, 70)), vec![&(0, 100)]);
+        assert!(tree.overlapping((101, 200)).is_empty());
+    }
+
+    #[test]
+    fn overlapping_disjoint_intervals() {
+        let tree = tree_of(&[(0, 1), (3, 4), (6, 7), (9, 10)]);
+
+        assert!(tree.overlapping((2, 2)).is_empty());
+        assert!(tree.overlapping((-5, -1)).is_empty());
+        assert!(tree.overlapping((11, 20)).is_empty());
+        assert_eq!(tree.overlapping((4, 6)), vec![&(3, 4), &(6, 7)]);
+        assert_eq!(tree.overlapping((10, 10)), vec![&(9, 10)]);
+    }
+
+    #[test]
+    fn overlapping_partially_overlapping_intervals() {
+        let tree = tree_of(&[(1, 3), (2, 6), (5, 8), (8, 9), (15, 20)]);
+
+        assert_eq!(tree.overlapping((3, 7)), vec![&(1, 3), &(2, 6), &(5, 8)]);
+        assert_eq!(tree.overlapping((9, 16)), vec![&(8, 9), &(15, 20)]);
+        assert_eq!(tree.overlapping((0, 1)), vec![&(1, 3)]);
+        assert_eq!(tree.overlapping((10, 14)), Vec::<&(i32, i32)>::new());
+    }
+
+    #[test]
+    fn remove_keeps_max_end_updated() {
+        let mut tree = tree_of(&[(5, 6), (1, 100), (7, 8), (0, 1), (3, 4)]);
+        assert_eq!(tree.overlapping((50, 60)), vec![&(1, 100)]);
+
+        tree.remove(&(1, 100));
+        assert!(tree.is_valid());
+        assert!(tree.overlapping((50, 60)).is_empty());
+        assert_eq!(tree.number_of_elements(), 4);
+
+        tree.remove(&(42, 43));
+        assert_eq!(tree.number_of_elements(), 4);
+
+        for interval in [(5, 6), (7, 8), (0, 1), (3, 4)] {
+            tree.remove(&interval);
+            assert!(tree.is_valid());
+            assert!(!tree.contains(&interval));
+        }
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+mod interval_operations;
+
+/// Internal implementation of `IntervalTree` nodes.
+pub mod node;
+
+use node::IntervalNode;
+
+/// An interval tree implementation.
+///
+/// Stores closed intervals `(start, end)` in an AVL-balanced tree ordered by `start`
+/// (then by `end`), where every node is augmented with the largest `end` in its subtree.
+/// This allows finding all intervals overlapping a query without visiting the subtrees
+/// that cannot contain any of them.
+/// - Duplicate intervals are not allowed
+/// - Intervals with `start > end` are not stored
+#[derive(Debug)]
+pub struct IntervalTree<T: PartialOrd + Clone> {
+    /// Root node of the tree (private to maintain invariants)
+    root: Option<Box<IntervalNode<T>>>,
+}
+
+impl<T: PartialOrd + Clone> IntervalTree<T> {
+    /// Check AVL balance and the maximum end augmentation of every node.
+    pub fn is_valid(&self) -> bool {
+        fn check<T: PartialOrd + Clone>(node: &Option<Box<IntervalNode<T>>>) -> Option<usize> {
+            match node {
+                Some(node) => {
+                    let left = check(&node.left)?;
+                    let right = check(&node.right)?;
+
+                    let mut max_end = &node.interval.1;
+                    for child in [&node.left, &node.right].into_iter().flatten() {
+                        if child.max_end > *max_end {
+                            max_end = &child.max_end;
+                        }
+                    }
+
+                    let balanced = left.abs_diff(right) <= 1;
+                    let augmented = node.max_end == *max_end && node.height == 1 + left.max(right);
+                    (balanced && augmented).then_some(node.height)
+                }
+                None => Some(0),
+            }
+        }
+        check(&self.root).is_some()
+    }
+}
@@ -0,0 +1,118 @@
+/// A node in an interval tree structure with height and maximum end tracking.
+///
+/// Each node contains:
+/// - An `interval` `(start, end)` of generic type `T`
+/// - Optional left/right child nodes (wrapped in `Box`)
+/// - Height information for balancing
+/// - The largest `end` found in this node's subtree (used to prune overlap queries)
+///
+/// Maintains the AVL invariant: balance factor ∈ [-1, 0, 1]
+#[derive(Debug, Clone)]
+pub struct IntervalNode<T: PartialOrd> {
+    /// The interval stored in this node.
+    pub interval: (T, T),
+
+    /// Left child node (intervals less than parent).
+    pub left: Option<Box<IntervalNode<T>>>,
+
+    /// Right child node (intervals greater than parent).
+    pub right: Option<Box<IntervalNode<T>>>,
+
+    /// Height of this node's subtree (leaf nodes have height 1).
+    pub height: usize,
+
+    /// The largest interval end in this node's subtree.
+    pub max_end: T,
+}
+
+impl<T: PartialOrd + Clone> IntervalNode<T> {
+    /// Creates a new `IntervalNode` with the given `interval` and default height (1) and no children.
+    pub fn new(interval: (T, T)) -> Self {
+        IntervalNode {
+            max_end: interval.1.clone(),
+            interval,
+            left: None,
+            right: None,
+            height: 1,
+        }
+    }
+
+    /// Updates this node's height and maximum end based on its children.
+    pub fn update(&mut self) {
+        self.height = 1 + std::cmp::max(Self::height(&self.left), Self::height(&self.right));
+
+        self.max_end = self.interval.1.clone();
+        for child in [&self.left, &self.right].into_iter().flatten() {
+            if child.max_end > self.max_end {
+                self.max_end = child.max_end.clone();
+            }
+        }
+    }
+
+    /// Returns the height of a node (0 for `None`).
+    pub fn height(node: &Option<Box<Self>>) -> usize {
+        node.as_ref().map_or(0, |n| n.height)
+    }
+
+    /// Calculates the balance factor (left_height - right_height).
+    pub fn balance_factor(&self) -> i32 {
+        let left_height = Self::height(&self.left) as i32;
+        let right_height = Self::height(&self.right) as i32;
+        left_height - right_height
+    }
+
+    /// Performs automatic rebalancing if needed (the same four cases as in `AVLNode`).
+    pub fn rebalance(self: Box<Self>) -> Box<Self> {
+        match self.balance_factor() {
+            bf if bf > 1 => {
+                if self.left.as_ref().unwrap().balance_factor() >= 0 {
+                    self.rotate_right()
+                } else {
+                    self.rotate_left_right()
+                }
+            }
+            bf if bf < -1 => {
+                if self.right.as_ref().unwrap().balance_factor() <= 0 {
+                    self.rotate_left()
+                } else {
+                    self.rotate_right_left()
+                }
+            }
+            _ => self,
+        }
+    }
+
+    /// Performs a right rotation (left-left case).
+    fn rotate_right(mut self: Box<Self>) -> Box<Self> {
+        let mut new_root = self.left.take().unwrap();
+        self.left = new_root.right.take();
+        self.update();
+        new_root.right = Some(self);
+        new_root.update();
+        new_root
+    }
+
+    /// Performs a left rotation (right-right case).
+    fn rotate_left(mut self: Box<Self>) -> Box<Self> {
+        let mut new_root = self.right.take().unwrap();
+        self.right = new_root.left.take();
+        self.update();
+        new_root.left = Some(self);
+        new_root.update();
+        new_root
+    }
+
+    /// Performs a right-left case rotation.
+    fn rotate_right_left(mut self: Box<Self>) -> Box<Self> {
+        let right = self.right.take().unwrap();
+        self.right = Some(right.rotate_right());
+        self.rotate_left()
+    }
+
+    /// Performs a left-right case rotation.
+    fn rotate_left_right(mut self: Box<Self>) -> Box<Self> {
+        let left = self.left.take().unwrap();
+        self.left = Some(left.rotate_left());
+        self.rotate_right()
+    }
+}
@@ -6,3 +6,33 @@ pub mod avl_tree;
 
 /// Self-balancing Red-Black tree (color-based balanced BST).
 pub mod red_black_tree;
+
+/// Self-balancing interval tree (AVL tree of intervals augmented with subtree maximum ends).
+pub mod interval_tree;
+
+/// Traversal orders shared by all trees.
+pub mod traversal;
+
+/// Errors returned by the fallible tree operations.
+pub mod error;
+
+/// Classification of the elements of two trees walked together (see `merge_diff`).
+pub mod diff;
+
+/// Fixed-size binary encoding of primitive values (see `to_bytes`).
+pub mod bytes;
+
+/// Recorded tree operations for replaying (see `from_insertion_log`).
+pub mod operation;
+
+/// The node color of a `RedBlackTree` (see `RedBlackTree::iter_colored`).
+pub use red_black_tree::Color;
+
+/// Recycling of node allocations (see the `pool` feature).
+mod pool;
+
+/// Element orders of the trees (see `BinarySearchTree::with_comparator`).
+mod order;
+
+/// Rotation counters of the balanced trees (see the `metrics` feature).
+mod metrics;
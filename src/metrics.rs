@@ -0,0 +1,31 @@
+/// The number of rotations performed by a single tree.
+///
+/// The tree hands its counter down to the rotating node helpers, so rotations are attributed
+/// to the tree that performed them even if several trees are used on the same thread.
+/// Without the `metrics` feature the counter is zero-sized and ignores the rotations.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Rotations {
+    #[cfg(feature = "metrics")]
+    count: u64,
+}
+
+impl Rotations {
+    /// Creates a counter with no rotations recorded.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a single rotation.
+    pub(crate) fn record(&mut self) {
+        #[cfg(feature = "metrics")]
+        {
+            self.count += 1;
+        }
+    }
+
+    /// Returns the number of rotations recorded so far.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+}
@@ -0,0 +1,22 @@
+/// A recorded modification of a tree, replayed by `from_insertion_log`.
+///
+/// Since all trees are deterministic, replaying the same operations always reproduces the same
+/// tree shape, which makes failing (e.g. randomly generated) cases easy to rebuild.
+///
+/// # Example:
+///
+/// ```
+/// use data_forest::binary_search_tree::BinarySearchTree;
+/// use data_forest::operation::Op;
+///
+/// let bst = BinarySearchTree::from_insertion_log(&[Op::Insert(2), Op::Insert(1), Op::Remove(2)]);
+/// assert_eq!(bst.in_order(), vec![&1]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<T> {
+    /// Insert the value (see `insert`).
+    Insert(T),
+
+    /// Remove the value (see `remove`).
+    Remove(T),
+}
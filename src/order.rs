@@ -0,0 +1,76 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+/// A type-erased comparator closure.
+type Erased = dyn Any + Send + Sync;
+
+/// The order of the elements of a tree: the `PartialOrd` implementation of `T` or a custom
+/// comparator supplied on construction.
+pub(crate) enum Order<T> {
+    Natural,
+    Custom {
+        /// The comparator, erased so that the order of a tree of borrowed values
+        /// (e.g. `&'a str`) doesn't require the values to be `'static`.
+        cmp: Arc<Erased>,
+
+        /// Calls `cmp` after restoring its concrete type.
+        call: fn(&Erased, &T, &T) -> Ordering,
+    },
+}
+
+impl<T> Order<T> {
+    /// Creates the order defined by the comparator `cmp`.
+    pub(crate) fn custom<F>(cmp: F) -> Self
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    {
+        Order::Custom {
+            cmp: Arc::new(cmp),
+            call: |cmp, a, b| (cmp.downcast_ref::<F>().unwrap())(a, b),
+        }
+    }
+}
+
+impl<T: PartialOrd> Order<T> {
+    /// Compares `a` with `b` in this order (`None` only for incomparable natural values).
+    pub(crate) fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        match self {
+            Order::Natural => a.partial_cmp(b),
+            Order::Custom { cmp, call } => Some(call(cmp.as_ref(), a, b)),
+        }
+    }
+
+    /// Whether `a` comes before `b` in this order.
+    pub(crate) fn less(&self, a: &T, b: &T) -> bool {
+        self.compare(a, b) == Some(Ordering::Less)
+    }
+
+    /// Whether `a` comes before `b` or is equal to it in this order.
+    pub(crate) fn less_or_equal(&self, a: &T, b: &T) -> bool {
+        matches!(self.compare(a, b), Some(Ordering::Less | Ordering::Equal))
+    }
+}
+
+/// Clones share the comparator.
+impl<T> Clone for Order<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Order::Natural => Order::Natural,
+            Order::Custom { cmp, call } => Order::Custom {
+                cmp: Arc::clone(cmp),
+                call: *call,
+            },
+        }
+    }
+}
+
+impl<T> fmt::Debug for Order<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Order::Natural => f.write_str("Natural"),
+            Order::Custom { .. } => f.write_str("Custom"),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+#[cfg(not(feature = "pool"))]
+use std::marker::PhantomData;
+#[cfg(feature = "pool")]
+use std::mem::MaybeUninit;
+
+/// A free-list of node allocations shared by the insertions, removals and rebuilds of a single
+/// tree.
+///
+/// With the `pool` feature enabled, the boxes of removed nodes (and of the nodes taken apart by
+/// rebuilds like `retain` or `split_off`) are kept here and handed out again to the following
+/// insertions and rebuilds instead of being returned to the allocator, so the pool never holds
+/// more allocations than the tree had nodes at its peak.
+/// Without the feature the pool is zero-sized and simply forwards to `Box`.
+pub(crate) struct NodePool<N> {
+    #[cfg(feature = "pool")]
+    free: Vec<Box<MaybeUninit<N>>>,
+
+    #[cfg(not(feature = "pool"))]
+    marker: PhantomData<N>,
+}
+
+impl<N> NodePool<N> {
+    /// Creates an empty pool.
+    pub(crate) fn new() -> Self {
+        NodePool {
+            #[cfg(feature = "pool")]
+            free: Vec::new(),
+            #[cfg(not(feature = "pool"))]
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a pool holding `capacity` allocations ready for the following insertions
+    /// (an empty one without the feature).
+    #[cfg_attr(not(feature = "pool"), allow(unused_variables))]
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        NodePool {
+            #[cfg(feature = "pool")]
+            free: (0..capacity).map(|_| Box::new_uninit()).collect(),
+            #[cfg(not(feature = "pool"))]
+            marker: PhantomData,
+        }
+    }
+
+    /// Places a `node` into a recycled allocation if there is one, or into a new one otherwise.
+    pub(crate) fn alloc(&mut self, node: N) -> Box<N> {
+        #[cfg(feature = "pool")]
+        if let Some(slot) = self.free.pop() {
+            return Box::write(slot, node);
+        }
+
+        Box::new(node)
+    }
+
+    /// Moves the contents out of a boxed `node`, keeping its allocation for later reuse.
+    #[cfg_attr(not(feature = "pool"), allow(clippy::boxed_local))]
+    pub(crate) fn release(&mut self, node: Box<N>) -> N {
+        #[cfg(feature = "pool")]
+        {
+            let raw = Box::into_raw(node);
+            // SAFETY: `raw` comes from a live `Box<N>`, so it is valid for a read of `N`.
+            // After the read the allocation is treated as uninitialized, and
+            // `MaybeUninit<N>` has the same layout as `N`, so it may be owned as such.
+            unsafe {
+                let node = raw.read();
+                self.free.push(Box::from_raw(raw.cast::<MaybeUninit<N>>()));
+                node
+            }
+        }
+
+        #[cfg(not(feature = "pool"))]
+        *node
+    }
+
+    /// Moves up to `count` allocations into a new pool, e.g. for the nodes of a tree split off
+    /// the one owning this pool.
+    #[cfg_attr(not(feature = "pool"), allow(unused_variables))]
+    pub(crate) fn split_off(&mut self, count: usize) -> Self {
+        NodePool {
+            #[cfg(feature = "pool")]
+            free: self.free.split_off(self.free.len().saturating_sub(count)),
+            #[cfg(not(feature = "pool"))]
+            marker: PhantomData,
+        }
+    }
+
+    /// Moves all allocations of `other` into this pool, leaving `other` empty.
+    #[cfg_attr(not(feature = "pool"), allow(unused_variables))]
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        #[cfg(feature = "pool")]
+        self.free.append(&mut other.free);
+    }
+
+    /// Returns the number of allocations available for reuse (always 0 without the feature).
+    pub(crate) fn len(&self) -> usize {
+        #[cfg(feature = "pool")]
+        return self.free.len();
+
+        #[cfg(not(feature = "pool"))]
+        0
+    }
+}
+
+impl<N> Default for NodePool<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cloning a tree copies its nodes only: the spare allocations stay with the original, so the
+/// clone starts with an empty pool.
+impl<N> Clone for NodePool<N> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<N> fmt::Debug for NodePool<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("NodePool");
+        #[cfg(feature = "pool")]
+        debug.field("free", &self.free.len());
+        debug.finish()
+    }
+}
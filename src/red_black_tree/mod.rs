@@ -3,9 +3,16 @@ mod rb_operations;
 /// Internal implementation of `RedBlackTree` nodes.
 pub mod node;
 
+/// Paginated reading in ascending order.
+pub mod cursor;
+
 /// For visualizing (Graphviz, DOT format).
 pub mod visualization;
 
+pub use node::Color;
+
+use crate::metrics::Rotations;
+use crate::pool::NodePool;
 use node::RBNode;
 
 /// A self-balancing Red-Black Tree implementation.
@@ -21,16 +28,22 @@ use node::RBNode;
 /// - All values in the left subtree are less than the node's value
 /// - All values in the right subtree are greater than the node's value
 /// - Duplicate values are not allowed
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RedBlackTree<T: PartialOrd + Clone> {
     /// Root node of the tree (private to maintain invariants)
     root: Option<Box<RBNode<T>>>,
 
-    /// Cached minimum value (None if tree is empty)
+    /// Cached minimum value in the order of `T` (None if tree is empty)
     min_value: Option<T>,
 
-    /// Cached maximum value (None if tree is empty)
+    /// Cached maximum value in the order of `T` (None if tree is empty)
     max_value: Option<T>,
+
+    /// Node allocations kept for reuse (only populated with the `pool` feature)
+    pool: NodePool<RBNode<T>>,
+
+    /// Number of rotations performed over the lifetime of the tree
+    rotations: Rotations,
 }
 
 impl<T: PartialOrd + Clone> RedBlackTree<T> {
@@ -46,6 +59,31 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         self.check_red_property(&self.root) && self.check_black_height(&self.root).is_some()
     }
 
+    /// Returns the black height of the tree: the number of black nodes on a path from the root
+    /// to a leaf, counting the NIL leaf, or 0 if the tree is empty.
+    ///
+    /// In a valid Red-Black Tree all such paths have the same black height, so only the left
+    /// spine is visited (`is_valid_red_black_tree` checks the other paths).
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn black_height(&self) -> usize {
+        if self.root.is_none() {
+            return 0;
+        }
+
+        let mut height = 1; // The NIL leaf is black
+        let mut cursor = &self.root;
+        while let Some(node) = cursor {
+            if node.is_black() {
+                height += 1;
+            }
+            cursor = &node.left;
+        }
+
+        height
+    }
+
     /// Checks that no red node has a red child.
     fn check_red_property(&self, node: &Option<Box<RBNode<T>>>) -> bool {
         match node {
@@ -109,11 +147,51 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         }
         check(&self.root, None, None)
     }
+
+    /// Check the multiset variant of the BST invariant for Red-Black Tree.
+    ///
+    /// Unlike `is_valid_bst`, values equal to a node are allowed in its right subtree
+    /// (left subtree values must still be strictly less than the node's value).
+    pub fn is_valid_bst_multiset(&self) -> bool {
+        fn check<T: PartialOrd>(
+            node: &Option<Box<RBNode<T>>>,
+            min: Option<&T>,
+            max: Option<&T>,
+        ) -> bool {
+            match node {
+                Some(node) => {
+                    if let Some(min_val) = min
+                        && &node.value < min_val
+                    {
+                        return false;
+                    }
+                    if let Some(max_val) = max
+                        && &node.value >= max_val
+                    {
+                        return false;
+                    }
+                    check(&node.left, min, Some(&node.value))
+                        && check(&node.right, Some(&node.value), max)
+                }
+                None => true,
+            }
+        }
+        check(&self.root, None, None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diff::DiffItem;
+    use crate::error::{DecodeError, DuplicateError};
+    use crate::operation::Op;
+    use crate::traversal::TraversalOrder;
+    #[cfg(feature = "rayon")]
+    use rayon::prelude::*;
+    use std::cmp::Ordering;
+    use std::cmp::Reverse;
+    use std::ops::ControlFlow;
 
     #[test]
     fn is_and_isnt_empty_tree() {
@@ -387,4 +465,1537 @@ mod tests {
         assert!(rbt.is_valid_bst());
         assert!(rbt.height() <= 10);
     }
+
+    /// Ordered and compared by `key` only, so that equal elements may carry different payloads.
+    #[derive(Debug, Clone)]
+    struct Keyed {
+        key: i32,
+        payload: &'static str,
+    }
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            self.key.partial_cmp(&other.key)
+        }
+    }
+
+    fn keyed(key: i32, payload: &'static str) -> Keyed {
+        Keyed { key, payload }
+    }
+
+    #[test]
+    fn insert_or_replace_basic() {
+        let mut rbt = RedBlackTree::new();
+
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            assert_eq!(rbt.insert_or_replace(keyed(key, "old")), None);
+        }
+
+        let replaced = rbt.insert_or_replace(keyed(4, "new")).unwrap();
+        assert_eq!(replaced.payload, "old");
+        assert_eq!(rbt.number_of_elements(), 7);
+
+        let payloads: Vec<_> = rbt.in_order().iter().map(|k| k.payload).collect();
+        assert_eq!(
+            payloads,
+            vec!["old", "old", "old", "new", "old", "old", "old"]
+        );
+    }
+
+    #[test]
+    fn insert_or_replace_updates_min_max() {
+        let mut rbt = RedBlackTree::new();
+
+        for key in [2, 1, 3] {
+            rbt.insert(keyed(key, "old"));
+        }
+
+        assert_eq!(
+            rbt.insert_or_replace(keyed(1, "new")).unwrap().payload,
+            "old"
+        );
+        assert_eq!(
+            rbt.insert_or_replace(keyed(3, "new")).unwrap().payload,
+            "old"
+        );
+
+        assert_eq!(rbt.min().unwrap().payload, "new");
+        assert_eq!(rbt.max().unwrap().payload, "new");
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn rotation_count_ascending_inserts() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.rotation_count(), 0);
+
+        for i in 1..=1000 {
+            rbt.insert(i);
+        }
+
+        let rotations = rbt.rotation_count();
+        assert!(rotations > 0);
+
+        rbt.remove(&500);
+        assert!(rbt.rotation_count() >= rotations);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn rotation_count_ignores_rotations_outside_the_tree() {
+        let mut rbt = RedBlackTree::new();
+        for i in 1..=100 {
+            rbt.insert(i);
+        }
+        let rotations = rbt.rotation_count();
+
+        // Rotating detached nodes happens on the same thread.
+        let mut node = Box::new(RBNode::new(1));
+        node.right = Some(Box::new(RBNode::new(2)));
+        assert_eq!(node.rotate_left().value, 2);
+
+        let mut other = RedBlackTree::new();
+        for i in 1..=100 {
+            other.insert(i);
+        }
+
+        rbt.insert(50);
+        assert_eq!(rbt.rotation_count(), rotations);
+        assert_eq!(other.rotation_count(), rotations);
+    }
+
+    #[test]
+    fn from_unsorted_matches_sequential_inserts() {
+        let values = vec![8, 3, 12, 3, 1, 5, 8, 10, 14, 7, 1, 6];
+        let bulk = RedBlackTree::from_unsorted(values.clone());
+
+        let mut rbt = RedBlackTree::new();
+        for &value in &values {
+            rbt.insert(value);
+        }
+
+        assert_eq!(bulk.in_order(), rbt.in_order());
+        assert_eq!(bulk.min(), Some(&1));
+        assert_eq!(bulk.max(), Some(&14));
+        assert!(bulk.is_valid_red_black_tree());
+        assert!(bulk.is_valid_bst());
+    }
+
+    #[test]
+    fn from_unsorted_on_sorted_input() {
+        for n in [0, 1, 2, 3, 10, 100, 1000] {
+            let bulk = RedBlackTree::from_unsorted(0..n);
+
+            let mut rbt = RedBlackTree::new();
+            for value in 0..n {
+                rbt.insert(value);
+            }
+
+            assert_eq!(bulk.number_of_elements(), n as usize);
+            assert!(bulk.is_valid_red_black_tree());
+            assert!(bulk.height() <= rbt.height());
+        }
+    }
+
+    #[test]
+    fn from_unsorted_deduplicates_by_ordering() {
+        // Equal by ordering (same key) but not by `==` (different labels).
+        #[derive(Debug, Clone, PartialEq)]
+        struct Labeled(i32, &'static str);
+
+        impl PartialOrd for Labeled {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        let bulk =
+            RedBlackTree::from_unsorted(vec![Labeled(1, "a"), Labeled(1, "b"), Labeled(2, "c")]);
+
+        assert_eq!(bulk.number_of_elements(), 2);
+        assert_eq!(bulk.in_order(), vec![&Labeled(1, "a"), &Labeled(2, "c")]);
+        assert!(bulk.is_valid_red_black_tree());
+        assert!(bulk.is_valid_bst());
+    }
+
+    #[test]
+    fn retain_range_keeps_middle_window() {
+        let mut rbt = RedBlackTree::new();
+        for i in 0..100 {
+            rbt.insert((i * 37) % 100);
+        }
+
+        rbt.retain_range(&30, &60);
+
+        let expected: Vec<i32> = (30..=60).collect();
+        assert_eq!(rbt.in_order(), expected.iter().collect::<Vec<_>>());
+        assert_eq!(rbt.min(), Some(&30));
+        assert_eq!(rbt.max(), Some(&60));
+        assert!(rbt.is_valid_red_black_tree());
+        assert!(rbt.is_valid_bst());
+
+        rbt.retain_range(&-10, &35);
+        assert_eq!(rbt.in_order(), vec![&30, &31, &32, &33, &34, &35]);
+
+        rbt.retain_range(&5, &1);
+        assert!(rbt.is_empty());
+        assert_eq!(rbt.min(), None);
+        assert_eq!(rbt.max(), None);
+    }
+
+    #[test]
+    fn display_as_set() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(format!("{rbt}"), "{}");
+
+        rbt.insert(2);
+        assert_eq!(format!("{rbt}"), "{2}");
+
+        rbt.insert(3);
+        rbt.insert(1);
+        assert_eq!(format!("{rbt}"), "{1, 2, 3}");
+    }
+
+    #[test]
+    fn traversals_cloned() {
+        let mut rbt = RedBlackTree::new();
+        for value in [4, 2, 5, 1, 3, 6] {
+            rbt.insert(value);
+        }
+
+        let mut sorted = vec![4, 2, 5, 1, 3, 6];
+        sorted.sort();
+        assert_eq!(rbt.in_order_cloned(), sorted);
+
+        let owned = |refs: Vec<&i32>| refs.into_iter().copied().collect::<Vec<_>>();
+        assert_eq!(rbt.pre_order_cloned(), owned(rbt.pre_order()));
+        assert_eq!(rbt.post_order_cloned(), owned(rbt.post_order()));
+        assert_eq!(rbt.level_order_cloned(), owned(rbt.level_order()));
+        assert!(RedBlackTree::<i32>::new().in_order_cloned().is_empty());
+    }
+
+    #[test]
+    fn empty_and_with_value() {
+        let rbt = RedBlackTree::<i32>::empty();
+        assert!(rbt.is_empty());
+        assert_eq!(rbt.min(), None);
+
+        let rbt = RedBlackTree::with_value(5);
+        assert_eq!(rbt.min(), Some(&5));
+        assert_eq!(rbt.max(), Some(&5));
+        assert_eq!(rbt.number_of_elements(), 1);
+        assert!(rbt.contains(&5));
+    }
+
+    #[test]
+    fn find_returns_rank_of_present_values() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.find(&1), None);
+
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            rbt.insert(value);
+        }
+
+        assert_eq!(rbt.find(&20), Some(0));
+        assert_eq!(rbt.find(&50), Some(3));
+        assert_eq!(rbt.find(&80), Some(6));
+        assert_eq!(rbt.find(&45), None);
+
+        rbt.remove(&30);
+        assert_eq!(rbt.find(&40), Some(1));
+        assert_eq!(rbt.find(&80), Some(5));
+    }
+
+    #[test]
+    fn level_order_with_nulls() {
+        let mut rbt = RedBlackTree::new();
+        assert!(rbt.level_order_with_nulls().is_empty());
+
+        for value in [2, 1, 3, 4] {
+            rbt.insert(value);
+        }
+
+        // 4 is rotated above 3, which becomes its left child.
+        assert_eq!(
+            rbt.level_order_with_nulls(),
+            vec![Some(&2), Some(&1), Some(&4), None, None, Some(&3)]
+        );
+    }
+
+    #[test]
+    fn walk_matches_traversals() {
+        let mut rbt = RedBlackTree::new();
+        for value in [4, 2, 5, 1, 3, 6, 8, 7] {
+            rbt.insert(value);
+        }
+
+        let walked = |order| {
+            let mut values = Vec::new();
+            rbt.walk(order, |value| values.push(*value));
+            values
+        };
+        let owned = |refs: Vec<&i32>| refs.into_iter().copied().collect::<Vec<_>>();
+
+        assert_eq!(walked(TraversalOrder::PreOrder), owned(rbt.pre_order()));
+        assert_eq!(walked(TraversalOrder::InOrder), owned(rbt.in_order()));
+        assert_eq!(walked(TraversalOrder::PostOrder), owned(rbt.post_order()));
+        assert_eq!(walked(TraversalOrder::LevelOrder), owned(rbt.level_order()));
+
+        let mut calls = 0;
+        RedBlackTree::<i32>::new().walk(TraversalOrder::InOrder, |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn try_from_vec() {
+        let rbt = RedBlackTree::try_from(vec![3, 1, 2]).unwrap();
+        assert_eq!(rbt.in_order(), vec![&1, &2, &3]);
+        assert_eq!(rbt.min(), Some(&1));
+        assert_eq!(rbt.max(), Some(&3));
+
+        let empty = RedBlackTree::<i32>::try_from(vec![]).unwrap();
+        assert!(empty.is_empty());
+
+        let err = RedBlackTree::try_from(vec![3, 1, 2, 1, 3]).unwrap_err();
+        assert_eq!(err, DuplicateError { value: 1 });
+        assert_eq!(err.to_string(), "duplicate value: 1");
+    }
+
+    #[test]
+    fn stored_equal_to_returns_the_stored_representative() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.stored_equal_to(&keyed(1, "query")), None);
+
+        rbt.insert(keyed(1, "first"));
+        rbt.insert(keyed(2, "second"));
+        rbt.insert(keyed(1, "ignored"));
+
+        let stored = rbt.stored_equal_to(&keyed(1, "query")).unwrap();
+        assert_eq!(stored.payload, "first");
+        assert_eq!(rbt.stored_equal_to(&keyed(3, "query")), None);
+
+        rbt.insert_or_replace(keyed(1, "replaced"));
+        assert_eq!(
+            rbt.stored_equal_to(&keyed(1, "query")).unwrap().payload,
+            "replaced"
+        );
+    }
+
+    #[test]
+    fn has_any_in_range() {
+        let mut rbt = RedBlackTree::new();
+        assert!(!rbt.has_any_in_range(&0, &100));
+
+        for value in [10, 5, 20, 1, 7, 30, 25] {
+            rbt.insert(value);
+        }
+
+        assert!(!rbt.has_any_in_range(&11, &19));
+        assert!(!rbt.has_any_in_range(&31, &40));
+        assert!(!rbt.has_any_in_range(&-5, &0));
+        assert!(rbt.has_any_in_range(&21, &26));
+        assert!(rbt.has_any_in_range(&7, &7));
+        assert!(rbt.has_any_in_range(&-100, &100));
+        assert!(!rbt.has_any_in_range(&26, &21));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_from_unsorted_matches_sequential() {
+        let values: Vec<i32> = (0..10_000).map(|i| (i * 7919) % 5000 - 2500).collect();
+
+        let sequential = RedBlackTree::from_unsorted(values.clone());
+        let parallel = RedBlackTree::par_from_unsorted(values.clone());
+        let collected: RedBlackTree<i32> = values.into_par_iter().collect();
+
+        for rbt in [&parallel, &collected] {
+            assert!(rbt.is_valid_red_black_tree());
+            assert!(rbt.is_valid_bst());
+            assert_eq!(rbt.in_order(), sequential.in_order());
+            assert_eq!(rbt.level_order(), sequential.level_order());
+            assert_eq!(rbt.min(), sequential.min());
+            assert_eq!(rbt.max(), sequential.max());
+        }
+
+        assert!(RedBlackTree::<f64>::par_from_unsorted(vec![f64::NAN]).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_from_unsorted_deduplicates_by_ordering() {
+        // Equal by ordering (same key) but not by `==` (different labels).
+        #[derive(Debug, Clone, PartialEq)]
+        struct Labeled(i32, &'static str);
+
+        impl PartialOrd for Labeled {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        let values = vec![Labeled(1, "a"), Labeled(1, "b"), Labeled(2, "c")];
+        let bulk = RedBlackTree::par_from_unsorted(values);
+
+        assert_eq!(bulk.number_of_elements(), 2);
+        assert_eq!(bulk.in_order(), vec![&Labeled(1, "a"), &Labeled(2, "c")]);
+        assert!(bulk.is_valid_red_black_tree());
+        assert!(bulk.is_valid_bst());
+    }
+
+    #[test]
+    fn same_elements_as() {
+        let mut rbt = RedBlackTree::new();
+        assert!(rbt.same_elements_as(&[]));
+
+        for value in [5, 3, 8, 1, 4] {
+            rbt.insert(value);
+        }
+
+        assert!(rbt.same_elements_as(&[1, 3, 4, 5, 8]));
+        assert!(!rbt.same_elements_as(&[5, 3, 8, 1, 4]));
+        assert!(!rbt.same_elements_as(&[1, 3, 4, 5]));
+        assert!(!rbt.same_elements_as(&[1, 3, 4, 5, 9]));
+    }
+
+    #[test]
+    fn reverse_ordering() {
+        let mut rbt = RedBlackTree::new();
+        for value in [5, 3, 8, 1, 4, 9] {
+            rbt.insert(Reverse(value));
+        }
+
+        let descending: Vec<i32> = rbt.in_order().into_iter().map(|value| value.0).collect();
+        assert_eq!(descending, vec![9, 8, 5, 4, 3, 1]);
+        assert_eq!(rbt.min(), Some(&Reverse(9)));
+        assert_eq!(rbt.max(), Some(&Reverse(1)));
+        assert_eq!(rbt.ceil(&Reverse(6)), Some(&Reverse(5)));
+        assert_eq!(rbt.floor(&Reverse(6)), Some(&Reverse(8)));
+
+        rbt.remove(&Reverse(9));
+        rbt.remove(&Reverse(1));
+        assert_eq!(rbt.min(), Some(&Reverse(8)));
+        assert_eq!(rbt.max(), Some(&Reverse(3)));
+        assert_eq!(rbt.find(&Reverse(8)), Some(0));
+    }
+
+    #[test]
+    fn is_valid_bst_multiset_allows_equal_values_on_the_right() {
+        let mut rbt = RedBlackTree::try_from(vec![1, 2, 3]).unwrap();
+        assert!(rbt.is_valid_bst());
+        assert!(rbt.is_valid_bst_multiset());
+
+        let mut root = Box::new(RBNode::new(2));
+        root.left = Some(Box::new(RBNode::new(1)));
+        root.right = Some(Box::new(RBNode::new(2)));
+        rbt.root = Some(root);
+        assert!(!rbt.is_valid_bst());
+        assert!(rbt.is_valid_bst_multiset());
+
+        let mut root = Box::new(RBNode::new(2));
+        root.left = Some(Box::new(RBNode::new(2)));
+        root.right = Some(Box::new(RBNode::new(3)));
+        rbt.root = Some(root);
+        assert!(!rbt.is_valid_bst());
+        assert!(!rbt.is_valid_bst_multiset());
+
+        let mut root = Box::new(RBNode::new(2));
+        let mut right = Box::new(RBNode::new(3));
+        right.left = Some(Box::new(RBNode::new(1)));
+        root.right = Some(right);
+        rbt.root = Some(root);
+        assert!(!rbt.is_valid_bst_multiset());
+    }
+
+    #[test]
+    fn split_off_partitions_at_key() {
+        let values = [50, 30, 70, 20, 40, 60, 80, 10, 35, 65];
+
+        for key in [0, 10, 35, 36, 50, 80, 81] {
+            let mut rbt = RedBlackTree::new();
+            for value in values {
+                rbt.insert(value);
+            }
+
+            let other = rbt.split_off(&key);
+
+            let mut sorted = values.to_vec();
+            sorted.sort();
+            let (less, rest): (Vec<i32>, Vec<i32>) = sorted.iter().partition(|&&v| v < key);
+            assert_eq!(rbt.in_order_cloned(), less);
+            assert_eq!(other.in_order_cloned(), rest);
+
+            assert_eq!(rbt.min(), less.first());
+            assert_eq!(rbt.max(), less.last());
+            assert_eq!(other.min(), rest.first());
+            assert_eq!(other.max(), rest.last());
+            assert!(rbt.is_valid_red_black_tree() && rbt.is_valid_bst());
+            assert!(other.is_valid_red_black_tree() && other.is_valid_bst());
+            assert_eq!(
+                rbt.find(&less.last().copied().unwrap_or(key)),
+                less.len().checked_sub(1)
+            );
+            assert_eq!(
+                other.find(&rest.last().copied().unwrap_or(key)),
+                rest.len().checked_sub(1)
+            );
+        }
+    }
+
+    #[test]
+    fn append_overlapping_trees() {
+        let mut rbt = RedBlackTree::new();
+        for value in [5, 1, 9, 3, 7] {
+            rbt.insert(value);
+        }
+        let mut other = RedBlackTree::new();
+        for value in [6, 3, 12, 0, 9] {
+            other.insert(value);
+        }
+
+        rbt.append(&mut other);
+
+        assert_eq!(rbt.in_order_cloned(), vec![0, 1, 3, 5, 6, 7, 9, 12]);
+        assert_eq!(rbt.min(), Some(&0));
+        assert_eq!(rbt.max(), Some(&12));
+        assert_eq!(rbt.find(&12), Some(7));
+        assert!(rbt.is_valid_red_black_tree() && rbt.is_valid_bst());
+        assert!(other.is_empty());
+        assert_eq!(other.min(), None);
+        assert_eq!(other.max(), None);
+
+        rbt.append(&mut other);
+        assert_eq!(rbt.number_of_elements(), 8);
+    }
+
+    #[test]
+    fn append_keeps_own_copy_of_equal_values() {
+        let mut rbt = RedBlackTree::new();
+        rbt.insert(keyed(1, "self"));
+        let mut other = RedBlackTree::new();
+        other.insert(keyed(1, "other"));
+        other.insert(keyed(2, "other"));
+
+        rbt.append(&mut other);
+
+        assert_eq!(
+            rbt.stored_equal_to(&keyed(1, "")).map(|k| k.payload),
+            Some("self")
+        );
+        assert_eq!(rbt.number_of_elements(), 2);
+    }
+
+    #[test]
+    fn drain_range_moves_middle_window() {
+        let mut rbt = RedBlackTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80, 10, 35, 65, 75] {
+            rbt.insert(value);
+        }
+
+        let drained = rbt.drain_range(&33, &65);
+
+        assert_eq!(drained.in_order_cloned(), vec![35, 40, 50, 60, 65]);
+        assert_eq!(drained.min(), Some(&35));
+        assert_eq!(drained.max(), Some(&65));
+        assert_eq!(drained.find(&65), Some(4));
+        assert_eq!(rbt.in_order_cloned(), vec![10, 20, 30, 70, 75, 80]);
+        assert_eq!(rbt.min(), Some(&10));
+        assert_eq!(rbt.max(), Some(&80));
+        assert_eq!(rbt.find(&80), Some(5));
+
+        assert!(rbt.is_valid_red_black_tree() && drained.is_valid_red_black_tree());
+        assert!(rbt.drain_range(&31, &69).is_empty());
+        assert!(rbt.drain_range(&80, &10).is_empty());
+        assert_eq!(rbt.number_of_elements(), 6);
+
+        let drained = rbt.drain_range(&0, &100);
+        assert_eq!(drained.in_order_cloned(), vec![10, 20, 30, 70, 75, 80]);
+        assert!(rbt.is_empty());
+        assert_eq!(rbt.min(), None);
+    }
+
+    #[test]
+    fn approx_memory_bytes_scales_linearly() {
+        let mut rbt = RedBlackTree::new();
+        let empty = rbt.approx_memory_bytes();
+        assert_eq!(empty, size_of::<RedBlackTree<i32>>());
+
+        for value in 0..100 {
+            rbt.insert(value);
+        }
+        let hundred = rbt.approx_memory_bytes();
+        for value in 100..200 {
+            rbt.insert(value);
+        }
+        let two_hundred = rbt.approx_memory_bytes();
+
+        assert!(hundred > empty);
+        assert_eq!(two_hundred - hundred, hundred - empty);
+        assert_eq!(hundred - empty, 100 * size_of::<RBNode<i32>>());
+    }
+
+    #[test]
+    fn from_insertion_log_reproduces_the_tree() {
+        let log = [
+            Op::Insert(5),
+            Op::Insert(3),
+            Op::Insert(8),
+            Op::Insert(1),
+            Op::Remove(3),
+            Op::Insert(9),
+            Op::Remove(42),
+            Op::Insert(4),
+            Op::Insert(5),
+            Op::Remove(5),
+        ];
+
+        let mut rbt = RedBlackTree::new();
+        rbt.insert(5);
+        rbt.insert(3);
+        rbt.insert(8);
+        rbt.insert(1);
+        rbt.remove(&3);
+        rbt.insert(9);
+        rbt.remove(&42);
+        rbt.insert(4);
+        rbt.insert(5);
+        rbt.remove(&5);
+
+        let replayed = RedBlackTree::from_insertion_log(&log);
+        assert_eq!(
+            replayed.level_order_with_nulls(),
+            rbt.level_order_with_nulls()
+        );
+        assert_eq!(replayed.in_order(), vec![&1, &4, &8, &9]);
+        assert_eq!(replayed.min(), Some(&1));
+        assert_eq!(replayed.max(), Some(&9));
+
+        assert!(RedBlackTree::<i32>::from_insertion_log(&[]).is_empty());
+    }
+
+    #[test]
+    fn root_value() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.root_value(), None);
+
+        for value in 1..=7 {
+            rbt.insert(value);
+        }
+        assert_eq!(rbt.root_value(), Some(&4));
+        assert_eq!(rbt.root_value(), rbt.level_order().first().copied());
+
+        rbt.remove(&4);
+        assert_eq!(rbt.root_value(), Some(&5));
+    }
+
+    #[test]
+    fn edges_match_find_connections() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.edges().next(), None);
+
+        rbt.insert(4);
+        assert_eq!(rbt.edges().next(), None);
+
+        for value in [2, 6, 1, 3, 5, 7, 8, 0] {
+            rbt.insert(value);
+            assert_eq!(rbt.edges().collect::<Vec<_>>(), rbt.find_connections());
+        }
+        assert_eq!(rbt.edges().count(), rbt.number_of_elements() - 1);
+    }
+
+    #[test]
+    fn clamp_prefers_floor_then_min() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.clamp(&5), None);
+
+        for value in [20, 10, 30] {
+            rbt.insert(value);
+        }
+
+        assert_eq!(rbt.clamp(&5), Some(&10));
+        assert_eq!(rbt.clamp(&10), Some(&10));
+        assert_eq!(rbt.clamp(&25), Some(&20));
+        assert_eq!(rbt.clamp(&20), Some(&20));
+        assert_eq!(rbt.clamp(&30), Some(&30));
+        assert_eq!(rbt.clamp(&99), Some(&30));
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut rbt = RedBlackTree::new();
+        for value in 0..100 {
+            rbt.insert(value * 3);
+        }
+
+        let decoded = RedBlackTree::<i64>::from_bytes(&rbt.to_bytes()).unwrap();
+        assert_eq!(decoded.in_order(), rbt.in_order());
+        assert_eq!(decoded.min(), Some(&0));
+        assert_eq!(decoded.max(), Some(&297));
+        assert!(decoded.is_valid_red_black_tree() && decoded.is_valid_bst());
+        assert!(matches!(
+            RedBlackTree::<i64>::from_bytes(&[0; 4]),
+            Err(DecodeError::MissingLength)
+        ));
+    }
+
+    #[test]
+    fn into_level_order_matches_level_order() {
+        assert!(RedBlackTree::<String>::new().into_level_order().is_empty());
+
+        let mut rbt = RedBlackTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80, 10, 65] {
+            rbt.insert(value.to_string());
+        }
+
+        let expected = rbt.level_order_cloned();
+        assert_eq!(rbt.into_level_order(), expected);
+    }
+
+    #[test]
+    fn all_comparable_detects_nan() {
+        let mut rbt = RedBlackTree::new();
+        assert!(rbt.all_comparable());
+
+        for value in [2.0, 1.0, 3.0, f64::INFINITY] {
+            rbt.insert(value);
+        }
+        assert!(rbt.all_comparable());
+
+        rbt.root.as_mut().unwrap().value = f64::NAN;
+        assert!(!rbt.all_comparable());
+
+        let mut single = RedBlackTree::new();
+        single.root = Some(Box::new(RBNode::new(f64::NAN)));
+        assert!(!single.all_comparable());
+    }
+
+    #[test]
+    fn merge_diff_classifies_elements() {
+        let mut rbt = RedBlackTree::new();
+        let mut other = RedBlackTree::new();
+        assert_eq!(rbt.merge_diff(&other).next(), None);
+
+        for value in [5, 1, 3, 7, 9] {
+            rbt.insert(value);
+        }
+        for value in [4, 3, 9, 10, 0] {
+            other.insert(value);
+        }
+
+        assert_eq!(
+            rbt.merge_diff(&other).collect::<Vec<_>>(),
+            vec![
+                DiffItem::Right(&0),
+                DiffItem::Left(&1),
+                DiffItem::Both(&3, &3),
+                DiffItem::Right(&4),
+                DiffItem::Left(&5),
+                DiffItem::Left(&7),
+                DiffItem::Both(&9, &9),
+                DiffItem::Right(&10),
+            ]
+        );
+        assert!(
+            rbt.merge_diff(&rbt)
+                .all(|item| matches!(item, DiffItem::Both(a, b) if a == b))
+        );
+        assert_eq!(
+            rbt.merge_diff(&RedBlackTree::new()).count(),
+            rbt.number_of_elements()
+        );
+    }
+
+    #[test]
+    fn with_capacity_behaves_like_new() {
+        let values: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+
+        let mut rbt = RedBlackTree::with_capacity(1000);
+        assert!(rbt.is_empty());
+        let mut expected = RedBlackTree::new();
+        for &value in &values {
+            rbt.insert(value);
+            expected.insert(value);
+        }
+
+        assert_eq!(
+            rbt.level_order_with_nulls(),
+            expected.level_order_with_nulls()
+        );
+        assert_eq!(rbt.min(), expected.min());
+        assert_eq!(rbt.max(), expected.max());
+    }
+
+    #[test]
+    fn filter_map_halves_evens_and_drops_odds() {
+        let mut rbt = RedBlackTree::new();
+        for value in [8, 3, 12, 1, 6, 10, 14, 7] {
+            rbt.insert(value);
+        }
+
+        let halves = rbt.filter_map(|&v| (v % 2 == 0).then_some(v / 2));
+        assert_eq!(halves.in_order(), vec![&3, &4, &5, &6, &7]);
+        assert_eq!(halves.min(), Some(&3));
+        assert_eq!(halves.max(), Some(&7));
+        assert!(halves.is_valid_red_black_tree());
+
+        let collapsed = rbt.filter_map(|&v| Some(v / 4));
+        assert_eq!(collapsed.in_order(), vec![&0, &1, &2, &3]);
+
+        let labels = rbt.filter_map(|&v| (v > 10).then(|| v.to_string()));
+        assert_eq!(labels.in_order_cloned(), vec!["12", "14"]);
+
+        assert!(rbt.filter_map(|_| None::<i32>).is_empty());
+    }
+
+    #[test]
+    fn nearest_k_expands_symmetrically() {
+        let mut rbt = RedBlackTree::new();
+        assert!(rbt.nearest_k(&5, 3).is_empty());
+
+        for value in [50, 25, 75, 12, 37, 62, 87] {
+            rbt.insert(value);
+        }
+        for value in 0..100 {
+            rbt.insert(value);
+        }
+
+        assert_eq!(rbt.nearest_k(&50, 5), vec![&50, &49, &51, &48, &52]);
+        assert!(rbt.nearest_k(&50, 0).is_empty());
+        assert_eq!(rbt.nearest_k(&-10, 2), vec![&0, &1]);
+        assert_eq!(rbt.nearest_k(&200, 2), vec![&99, &98]);
+
+        let all = rbt.nearest_k(&0, 1000);
+        assert_eq!(all.len(), 100);
+        assert_eq!(all, rbt.in_order());
+
+        let mut evens = RedBlackTree::new();
+        for value in [10u32, 4, 16, 2, 8, 14, 18, 6, 12] {
+            evens.insert(value);
+        }
+        assert_eq!(evens.nearest_k(&11, 4), vec![&10, &12, &8, &14]);
+        assert_eq!(evens.nearest_k(&1, 3), vec![&2, &4, &6]);
+    }
+
+    #[test]
+    fn remove_min_and_remove_max_empty_the_tree_in_order() {
+        let mut rbt = RedBlackTree::new();
+        assert!(!rbt.remove_min());
+        assert!(!rbt.remove_max());
+
+        let values: Vec<i32> = (0..200).map(|i| (i * 7919) % 200).collect();
+        for &value in &values {
+            rbt.insert(value);
+        }
+
+        for expected in 0..100 {
+            assert_eq!(rbt.min(), Some(&expected));
+            assert!(rbt.remove_min());
+            assert_eq!(rbt.find(&199), Some(198 - expected as usize));
+            assert!(rbt.is_valid_red_black_tree() && rbt.is_valid_bst());
+        }
+        for expected in (100..200).rev() {
+            assert_eq!(rbt.max(), Some(&expected));
+            assert_eq!(rbt.min(), Some(&100));
+            assert!(rbt.remove_max());
+            assert!(rbt.is_valid_red_black_tree() && rbt.is_valid_bst());
+        }
+
+        assert!(rbt.is_empty());
+        assert_eq!(rbt.min(), None);
+        assert_eq!(rbt.max(), None);
+        assert!(!rbt.remove_min());
+    }
+
+    #[test]
+    fn iter_colored_reports_valid_coloring() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.iter_colored().next(), None);
+
+        for value in [50, 20, 80, 10, 30, 25, 27, 90, 95, 5, 1, 60, 70, 65] {
+            rbt.insert(value);
+            assert!(rbt.is_valid_red_black_tree());
+
+            let colored: Vec<(&i32, Color)> = rbt.iter_colored().collect();
+            let values: Vec<&i32> = colored.iter().map(|&(value, _)| value).collect();
+            assert_eq!(values, rbt.in_order());
+
+            let color_of = |value: &i32| colored.iter().find(|(v, _)| *v == value).unwrap().1;
+            assert_eq!(color_of(rbt.root_value().unwrap()), Color::Black);
+            for (parent, child) in rbt.edges() {
+                assert!(color_of(parent) == Color::Black || color_of(child) == Color::Black);
+            }
+        }
+        assert!(rbt.iter_colored().any(|(_, color)| color == Color::Red));
+    }
+
+    #[test]
+    fn try_walk_stops_at_first_match() {
+        let mut rbt = RedBlackTree::new();
+        for value in [50, 20, 80, 10, 30, 60, 90, 25, 35] {
+            rbt.insert(value);
+        }
+
+        let mut visited = 0;
+        let found = rbt.try_walk(TraversalOrder::InOrder, |&value| {
+            visited += 1;
+            if value > 22 {
+                ControlFlow::Break(value)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(found, ControlFlow::Break(25));
+        assert_eq!(visited, 3);
+
+        let mut pre_order = Vec::new();
+        let result = rbt.try_walk(TraversalOrder::PreOrder, |&value| {
+            pre_order.push(value);
+            ControlFlow::<i32>::Continue(())
+        });
+        assert_eq!(result, ControlFlow::Continue(()));
+        assert_eq!(pre_order, rbt.pre_order_cloned());
+
+        let first = rbt.try_walk(TraversalOrder::LevelOrder, |&value| {
+            ControlFlow::Break(value)
+        });
+        assert_eq!(first, ControlFlow::Break(*rbt.root_value().unwrap()));
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let mut rbt: RedBlackTree<i32> = (0..100).collect();
+        assert!(rbt.is_valid_red_black_tree());
+        assert!(rbt.is_valid_bst());
+        assert_eq!(rbt.in_order_cloned(), (0..100).collect::<Vec<_>>());
+        assert_eq!(rbt.min(), Some(&0));
+        assert_eq!(rbt.max(), Some(&99));
+
+        rbt.extend((50..150).rev());
+        assert!(rbt.is_valid_red_black_tree());
+        assert!(rbt.is_valid_bst());
+        assert_eq!(rbt.number_of_elements(), 150);
+        assert_eq!(rbt.max(), Some(&149));
+
+        let empty: RedBlackTree<i32> = std::iter::empty().collect();
+        assert!(empty.is_empty());
+    }
+
+    #[cfg(feature = "pool")]
+    #[test]
+    fn rebuilds_recycle_pooled_nodes() {
+        let mut rbt = RedBlackTree::new();
+        for value in 0..100 {
+            rbt.insert(value);
+        }
+
+        rbt.retain(|value| value % 2 == 0);
+        assert_eq!(rbt.pooled_nodes(), 50);
+
+        // The split off tree takes the allocations for its nodes along.
+        let mut other = rbt.split_off(&50);
+        assert_eq!((rbt.pooled_nodes(), other.pooled_nodes()), (50, 0));
+
+        rbt.append(&mut other);
+        assert_eq!((rbt.pooled_nodes(), other.pooled_nodes()), (50, 0));
+        assert_eq!(rbt.number_of_elements(), 50);
+    }
+
+    #[test]
+    fn clear_and_reuse() {
+        let mut rbt = RedBlackTree::new();
+        rbt.clear();
+        assert!(rbt.is_empty());
+
+        for value in [5, 3, 8, 1, 4] {
+            rbt.insert(value);
+        }
+        rbt.clear();
+
+        assert!(rbt.is_empty());
+        assert_eq!(rbt.min(), None);
+        assert_eq!(rbt.max(), None);
+        assert_eq!(rbt.number_of_elements(), 0);
+        assert!(!rbt.contains(&5));
+
+        for value in [7, 2, 9] {
+            rbt.insert(value);
+        }
+        assert_eq!(rbt.in_order(), vec![&2, &7, &9]);
+        assert_eq!(rbt.min(), Some(&2));
+        assert_eq!(rbt.max(), Some(&9));
+        assert_eq!(rbt.number_of_elements(), 3);
+        assert!(rbt.is_valid_red_black_tree());
+    }
+
+    #[test]
+    fn len_ignores_duplicates_and_absent_removals() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.len(), 0);
+
+        for value in [5, 3, 8, 5, 3, 1, 8, 9] {
+            rbt.insert(value);
+        }
+        assert_eq!(rbt.len(), 5);
+
+        rbt.remove(&42);
+        rbt.remove(&3);
+        rbt.remove(&3);
+        rbt.insert(1);
+        rbt.insert(4);
+        assert_eq!(rbt.len(), 5);
+        assert_eq!(rbt.len(), rbt.in_order().len());
+        assert_eq!(rbt.number_of_elements(), rbt.len());
+
+        for value in [1, 4, 5, 8, 9, 0] {
+            rbt.remove(&value);
+        }
+        assert_eq!(rbt.len(), 0);
+        assert!(rbt.is_empty());
+    }
+
+    #[test]
+    fn take_returns_removed_value() {
+        let mut rbt = RedBlackTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            rbt.insert(value);
+        }
+
+        assert_eq!(rbt.take(&45), None);
+        assert_eq!(rbt.take(&50), Some(50));
+        assert_eq!(rbt.take(&20), Some(20));
+        assert_eq!(rbt.take(&80), Some(80));
+        assert_eq!(rbt.take(&80), None);
+        assert_eq!(rbt.in_order(), vec![&30, &40, &60, &70]);
+        assert_eq!(rbt.min(), Some(&30));
+        assert_eq!(rbt.max(), Some(&70));
+        assert_eq!(rbt.len(), 4);
+
+        let mut empty = RedBlackTree::<i32>::new();
+        assert_eq!(empty.take(&1), None);
+    }
+
+    #[test]
+    fn try_insert_reports_new_values() {
+        let mut rbt = RedBlackTree::new();
+
+        assert!(rbt.try_insert(5));
+        assert!(rbt.try_insert(3));
+        assert!(!rbt.try_insert(5));
+        assert!(!rbt.try_insert(3));
+        assert_eq!(rbt.len(), 2);
+
+        assert!(rbt.try_insert(8));
+        assert!(!rbt.try_insert(8));
+        assert_eq!(rbt.len(), 3);
+        assert_eq!(rbt.in_order(), vec![&3, &5, &8]);
+    }
+
+    #[test]
+    fn get_returns_the_stored_payload() {
+        let mut rbt = RedBlackTree::new();
+        assert!(rbt.get(&keyed(1, "query")).is_none());
+
+        rbt.insert(keyed(2, "two"));
+        rbt.insert(keyed(1, "one"));
+        rbt.insert(keyed(3, "three"));
+
+        assert_eq!(rbt.get(&keyed(1, "query")).unwrap().payload, "one");
+        assert_eq!(rbt.get(&keyed(3, "query")).unwrap().payload, "three");
+        assert!(rbt.get(&keyed(4, "query")).is_none());
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let mut rbt = RedBlackTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            rbt.insert(value);
+        }
+
+        let mut copy = rbt.clone();
+        copy.remove(&50);
+        copy.remove(&20);
+        copy.insert(90);
+
+        assert_eq!(rbt.in_order(), vec![&20, &30, &40, &50, &60, &70, &80]);
+        assert_eq!(rbt.min(), Some(&20));
+        assert_eq!(rbt.max(), Some(&80));
+        assert_eq!(rbt.len(), 7);
+
+        assert_eq!(copy.in_order(), vec![&30, &40, &60, &70, &80, &90]);
+        assert_eq!(copy.min(), Some(&30));
+        assert_eq!(copy.max(), Some(&90));
+        assert_eq!(copy.len(), 6);
+    }
+
+    #[test]
+    fn equality_ignores_insertion_order() {
+        let mut first = RedBlackTree::new();
+        let mut second = RedBlackTree::new();
+        for value in [1, 2, 3, 4, 5] {
+            first.insert(value);
+        }
+        for value in [3, 5, 1, 4, 2, 5] {
+            second.insert(value);
+        }
+        assert_eq!(first, second);
+        assert_eq!(RedBlackTree::<i32>::new(), RedBlackTree::new());
+
+        second.remove(&5);
+        second.insert(6);
+        assert_ne!(first, second);
+
+        second.remove(&6);
+        assert_ne!(first, second);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let rbt: RedBlackTree<i32> = [8, 3, 10, 1, 6, 14, 4, 7, 13].into_iter().collect();
+
+        let json = serde_json::to_string(&rbt).unwrap();
+        assert_eq!(json, "[1,3,4,6,7,8,10,13,14]");
+
+        let restored: RedBlackTree<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, rbt);
+        assert!(restored.is_valid_red_black_tree());
+
+        let unsorted: RedBlackTree<i32> = serde_json::from_str("[5,1,5,3,2]").unwrap();
+        assert_eq!(unsorted.in_order(), vec![&1, &2, &3, &5]);
+        assert!(unsorted.is_valid_red_black_tree());
+
+        assert!(serde_json::from_str::<RedBlackTree<i32>>("{}").is_err());
+    }
+
+    #[test]
+    fn into_sorted_vec_moves_values_out() {
+        let mut rbt = RedBlackTree::new();
+        for value in [5, 2, 8, 1, 9, 3, 2] {
+            rbt.insert(Box::new(value));
+        }
+
+        let sorted = rbt.into_sorted_vec();
+        assert_eq!(sorted.len(), 6);
+        assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            sorted.into_iter().map(|value| *value).collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 8, 9]
+        );
+
+        assert!(RedBlackTree::<i32>::new().into_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn level_order_grouped_by_depth() {
+        let mut rbt = RedBlackTree::new();
+        assert!(rbt.level_order_grouped().is_empty());
+
+        for value in 1..=10 {
+            rbt.insert(value);
+        }
+
+        let levels = rbt.level_order_grouped();
+        assert_eq!(levels.len(), rbt.height() + 1);
+        assert_eq!(levels[0], vec![rbt.root_value().unwrap()]);
+        assert_eq!(levels.concat(), rbt.level_order());
+    }
+
+    #[test]
+    fn insert_multi_counts_duplicates() {
+        let mut rbt = RedBlackTree::new();
+        for value in [5, 3, 5, 8, 5, 3] {
+            rbt.insert_multi(value);
+        }
+
+        assert_eq!(rbt.count(&5), 3);
+        assert_eq!(rbt.count(&3), 2);
+        assert_eq!(rbt.count(&8), 1);
+        assert_eq!(rbt.count(&4), 0);
+        assert_eq!(rbt.len(), 6);
+        assert_eq!(rbt.number_of_distinct(), 3);
+        assert_eq!(rbt.in_order(), vec![&3, &5, &8]);
+
+        // A plain insertion of a present value doesn't change its count.
+        rbt.insert(5);
+        assert_eq!(rbt.count(&5), 3);
+
+        assert_eq!(rbt.take(&5), Some(5));
+        rbt.remove(&5);
+        assert_eq!(rbt.count(&5), 1);
+        assert_eq!(rbt.len(), 4);
+        assert!(rbt.contains(&5));
+
+        rbt.remove(&5);
+        assert_eq!(rbt.count(&5), 0);
+        assert!(!rbt.contains(&5));
+        assert_eq!(rbt.len(), 3);
+        assert_eq!(rbt.number_of_distinct(), 2);
+
+        rbt.remove(&42);
+        assert_eq!(rbt.len(), 3);
+        assert!(rbt.is_valid_red_black_tree());
+    }
+
+    #[test]
+    fn remove_min_and_max_decrement_counts() {
+        let mut rbt = RedBlackTree::new();
+        for value in [5, 1, 9, 1, 9, 9, 3] {
+            rbt.insert_multi(value);
+        }
+
+        assert!(rbt.remove_min());
+        assert_eq!(rbt.count(&1), 1);
+        assert_eq!(rbt.min(), Some(&1));
+        assert_eq!(rbt.len(), 6);
+
+        assert!(rbt.remove_max());
+        assert!(rbt.remove_max());
+        assert_eq!(rbt.count(&9), 1);
+        assert_eq!(rbt.max(), Some(&9));
+        assert_eq!(rbt.len(), 4);
+        assert!(rbt.is_valid_red_black_tree());
+
+        // The last occurrences unlink the nodes.
+        assert!(rbt.remove_min());
+        assert!(rbt.remove_max());
+        assert_eq!(rbt.in_order(), vec![&3, &5]);
+        assert_eq!((rbt.min(), rbt.max()), (Some(&3), Some(&5)));
+        assert_eq!(rbt.len(), 2);
+        assert!(rbt.is_valid_red_black_tree());
+    }
+
+    #[test]
+    fn remove_keeps_count_of_moved_successor() {
+        let mut rbt = RedBlackTree::new();
+        for value in 1..=15 {
+            rbt.insert(value);
+        }
+        for _ in 0..4 {
+            rbt.insert_multi(9);
+        }
+
+        // Removing the nodes around 9 moves it up as a successor, its count must move along.
+        for value in [8, 7, 6, 5, 4] {
+            rbt.remove(&value);
+            assert_eq!(rbt.count(&9), 5);
+            assert!(rbt.is_valid_red_black_tree());
+        }
+        assert_eq!(rbt.len(), 10 + 4);
+    }
+
+    #[test]
+    fn equality_compares_counts() {
+        let mut first = RedBlackTree::new();
+        let mut second = RedBlackTree::new();
+        for value in [1, 1, 2] {
+            first.insert_multi(value);
+        }
+        for value in [1, 2, 2] {
+            second.insert_multi(value);
+        }
+        assert_ne!(first, second);
+
+        second.remove(&2);
+        second.insert_multi(1);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn restructuring_keeps_counts() {
+        let values = [1, 2, 2, 3, 3, 3, 4, 4, 4, 4];
+        let multiset = || {
+            let mut rbt = RedBlackTree::new();
+            for value in values {
+                rbt.insert_multi(value);
+            }
+            rbt
+        };
+
+        let mut rbt = multiset();
+        let mut other = rbt.split_off(&3);
+        assert_eq!((rbt.len(), other.len()), (3, 7));
+        assert_eq!((rbt.count(&2), other.count(&4)), (2, 4));
+
+        rbt.append(&mut other);
+        assert_eq!(rbt, multiset());
+
+        let mut drained = rbt.drain_range(&2, &3);
+        assert_eq!((rbt.len(), drained.len()), (5, 5));
+        assert_eq!(drained.count(&3), 3);
+        assert!(drained.is_valid_red_black_tree());
+
+        drained.append(&mut rbt);
+        assert_eq!(drained, multiset());
+
+        drained.retain_range(&3, &4);
+        assert_eq!(drained.len(), 7);
+        assert_eq!(drained.count(&4), 4);
+        assert!(drained.is_valid_red_black_tree());
+
+        assert_eq!(multiset().into_sorted_vec(), values);
+    }
+
+    #[test]
+    fn bytes_round_trip_keeps_counts() {
+        let mut rbt = RedBlackTree::new();
+        for value in [7i64, 3, 7, 1, 7, 3] {
+            rbt.insert_multi(value);
+        }
+
+        let decoded = RedBlackTree::<i64>::from_bytes(&rbt.to_bytes()).unwrap();
+        assert_eq!(decoded, rbt);
+        assert_eq!(decoded.count(&7), 3);
+        assert_eq!(decoded.len(), 6);
+        assert!(decoded.is_valid_red_black_tree());
+
+        let mut descending = 2u64.to_le_bytes().to_vec();
+        descending.extend([5i64, 4].iter().flat_map(|value| value.to_le_bytes()));
+        assert!(matches!(
+            RedBlackTree::<i64>::from_bytes(&descending),
+            Err(DecodeError::NotAscending { index: 1 })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_keeps_counts() {
+        let mut rbt = RedBlackTree::new();
+        for value in [3, 1, 3, 2, 3] {
+            rbt.insert_multi(value);
+        }
+
+        let json = serde_json::to_string(&rbt).unwrap();
+        assert_eq!(json, "[1,2,3,3,3]");
+
+        let restored: RedBlackTree<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, rbt);
+        assert_eq!(restored.count(&3), 3);
+        assert!(restored.is_valid_red_black_tree());
+    }
+
+    #[test]
+    fn lookups_accept_borrowed_forms() {
+        let mut rbt = RedBlackTree::new();
+        for word in ["bar", "foo", "qux"] {
+            rbt.insert(word.to_string());
+        }
+
+        assert!(rbt.contains("foo"));
+        assert!(!rbt.contains("baz"));
+        assert_eq!(rbt.get("qux"), Some(&"qux".to_string()));
+        assert_eq!(rbt.ceil("baz"), Some(&"foo".to_string()));
+        assert_eq!(rbt.floor("baz"), Some(&"bar".to_string()));
+
+        assert_eq!(rbt.take("bar"), Some("bar".to_string()));
+        rbt.remove("foo");
+        assert!(!rbt.contains("foo"));
+        assert_eq!(rbt.len(), 1);
+    }
+
+    #[test]
+    fn split_off_moves_pivot_to_returned_tree() {
+        let mut rbt = RedBlackTree::new();
+        for value in 1..=200 {
+            rbt.insert(value);
+        }
+
+        let other = rbt.split_off(&120);
+
+        assert!(!rbt.contains(&120));
+        assert!(other.contains(&120));
+        assert_eq!(rbt.len(), 119);
+        assert_eq!(other.len(), 81);
+        assert_eq!((rbt.min(), rbt.max()), (Some(&1), Some(&119)));
+        assert_eq!((other.min(), other.max()), (Some(&120), Some(&200)));
+        assert!(rbt.is_valid_red_black_tree() && rbt.is_valid_bst());
+        assert!(other.is_valid_red_black_tree() && other.is_valid_bst());
+    }
+
+    #[test]
+    fn retain_keeps_matching_values_valid() {
+        let mut rbt = RedBlackTree::new();
+        for value in 1..=100 {
+            rbt.insert(value);
+        }
+
+        rbt.retain(|value| value % 2 == 0);
+
+        let evens: Vec<i32> = (2..=100).step_by(2).collect();
+        assert_eq!(rbt.in_order_cloned(), evens);
+        assert_eq!(rbt.len(), 50);
+        assert_eq!((rbt.min(), rbt.max()), (Some(&2), Some(&100)));
+        assert!(rbt.is_valid_red_black_tree() && rbt.is_valid_bst());
+
+        rbt.retain(|_| false);
+        assert!(rbt.is_empty());
+        assert_eq!((rbt.min(), rbt.max()), (None, None));
+    }
+
+    #[test]
+    fn retain_keeps_counts() {
+        let mut rbt = RedBlackTree::new();
+        for value in [1, 2, 2, 3, 3] {
+            rbt.insert_multi(value);
+        }
+
+        rbt.retain(|_| true);
+        assert_eq!(rbt.len(), 5);
+        assert_eq!(rbt.count(&3), 2);
+
+        rbt.retain(|&value| value != 2);
+        assert_eq!(rbt.len(), 3);
+        assert_eq!(rbt.count(&2), 0);
+        assert!(rbt.is_valid_red_black_tree());
+    }
+
+    #[test]
+    fn for_each_sums_in_order() {
+        let mut rbt = RedBlackTree::new();
+        for value in [8, 3, 10, 1, 6, 14, 4, 7, 13] {
+            rbt.insert(value);
+        }
+
+        let mut sum = 0;
+        rbt.for_each(TraversalOrder::InOrder, |value| sum += value);
+
+        assert_eq!(sum, rbt.in_order().into_iter().sum::<i32>());
+    }
+
+    #[test]
+    fn depth_of_values() {
+        let mut rbt = RedBlackTree::new();
+        for value in 1..=100 {
+            rbt.insert(value);
+        }
+
+        let root = *rbt.level_order()[0];
+        assert_eq!(rbt.depth(&root), Some(0));
+        assert!((1..=100).all(|value| rbt.depth(&value).unwrap() <= rbt.height()));
+        assert!((1..=100).any(|value| rbt.depth(&value) == Some(rbt.height())));
+        assert_eq!(rbt.depth(&0), None);
+        assert_eq!(rbt.depth(&101), None);
+    }
+
+    #[test]
+    fn diameter_of_balanced_tree() {
+        assert_eq!(RedBlackTree::<i32>::new().diameter(), 0);
+
+        let mut rbt = RedBlackTree::new();
+        rbt.insert(1);
+        assert_eq!(rbt.diameter(), 0);
+
+        for value in 2..=1000 {
+            rbt.insert(value);
+        }
+        let diameter = rbt.diameter();
+        assert!(rbt.height() <= diameter && diameter <= 2 * rbt.height());
+    }
+
+    #[test]
+    fn count_leaves_and_internal_nodes() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!((rbt.count_leaves(), rbt.count_internal_nodes()), (0, 0));
+
+        rbt.insert(1);
+        assert_eq!((rbt.count_leaves(), rbt.count_internal_nodes()), (1, 0));
+
+        for value in 2..=100 {
+            rbt.insert(value);
+            rbt.insert_multi(value);
+        }
+        assert_eq!(rbt.count_leaves() + rbt.count_internal_nodes(), 100);
+        assert!(rbt.count_leaves() > 0 && rbt.count_internal_nodes() > 0);
+    }
+
+    #[test]
+    fn black_height_of_known_trees() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(rbt.black_height(), 0);
+
+        rbt.insert(1);
+        assert_eq!(rbt.black_height(), 2);
+
+        // Seven sorted values are built into the perfect all-black tree of three levels.
+        let rbt = RedBlackTree::from_unsorted(1..=7);
+        assert_eq!(rbt.black_height(), 4);
+
+        let mut rbt = RedBlackTree::new();
+        for value in 0..500 {
+            rbt.insert((value * 7919) % 500);
+        }
+        for value in (0..500).step_by(3) {
+            rbt.remove(&value);
+        }
+        assert_eq!(Some(rbt.black_height()), rbt.check_black_height(&rbt.root));
+    }
+
+    #[test]
+    fn display_alternate_annotates_colors() {
+        let mut rbt = RedBlackTree::new();
+        assert_eq!(format!("{rbt:#}"), "<empty>");
+
+        for value in [2, 1, 3] {
+            rbt.insert(value);
+        }
+        assert_eq!(format!("{rbt:#}"), "    3 (B)\n2 (B)\n    1 (B)");
+
+        // The new node is the red left child of 3.
+        rbt.insert(4);
+        rbt.insert(0);
+        assert_eq!(
+            format!("{rbt:#}"),
+            "    4 (B)\n        3 (R)\n2 (B)\n    1 (B)\n        0 (R)"
+        );
+    }
+
+    #[test]
+    fn reverse_in_order_descends() {
+        let mut rbt = RedBlackTree::new();
+        assert!(rbt.reverse_in_order().is_empty());
+
+        for i in 0..100 {
+            rbt.insert((i * 37) % 100);
+        }
+
+        let descending: Vec<i32> = (0..100).rev().collect();
+        let expected: Vec<&i32> = descending.iter().collect();
+        assert_eq!(rbt.reverse_in_order(), expected);
+    }
+
+    #[test]
+    fn btree_set_round_trip() {
+        use std::collections::BTreeSet;
+
+        let set: BTreeSet<i32> = (0..100).map(|i| (i * 37) % 101).collect();
+        let rbt = RedBlackTree::from(set.clone());
+
+        assert!(rbt.is_valid_red_black_tree() && rbt.is_valid_bst());
+        assert_eq!(rbt.len(), set.len());
+        assert_eq!(rbt.min(), set.first());
+        assert_eq!(rbt.max(), set.last());
+        assert_eq!(BTreeSet::from(&rbt), set);
+
+        let empty = RedBlackTree::from(BTreeSet::<i32>::new());
+        assert!(empty.is_empty());
+        assert!(BTreeSet::from(&empty).is_empty());
+    }
+
+    #[test]
+    fn insert_million_sequential_values() {
+        let mut rbt = RedBlackTree::new();
+        for value in 0..1_000_000 {
+            rbt.insert(value);
+        }
+
+        assert_eq!(rbt.len(), 1_000_000);
+        assert_eq!((rbt.min(), rbt.max()), (Some(&0), Some(&999_999)));
+        assert!(rbt.height() <= 2 * 20);
+        assert!(rbt.is_valid_red_black_tree());
+        assert!(rbt.is_valid_bst());
+    }
 }
@@ -1,3 +1,5 @@
+use crate::metrics::Rotations;
+
 /// Node color in a Red-Black Tree.
 ///
 /// Red-Black Trees maintain balance using color properties:
@@ -17,6 +19,7 @@ pub enum Color {
 /// - A `value` of generic type `T`
 /// - Optional left/right child nodes (wrapped in `Box`)
 /// - A color (Red or Black) for maintaining balance properties
+/// - The size of its subtree (for rank queries)
 ///
 /// Maintains the Red-Black Tree invariants through rebalancing operations.
 #[derive(Debug, Clone)]
@@ -32,6 +35,15 @@ pub struct RBNode<T: PartialOrd> {
 
     /// Color of this node (Red or Black).
     pub color: Color,
+
+    /// Number of nodes in this node's subtree (leaf nodes have size 1).
+    pub size: usize,
+
+    /// Number of times the value has been inserted (see `RedBlackTree::insert_multi`).
+    pub count: usize,
+
+    /// Sum of the `count`s of the nodes in this node's subtree.
+    pub total: usize,
 }
 
 impl<T: PartialOrd> RBNode<T> {
@@ -44,9 +56,28 @@ impl<T: PartialOrd> RBNode<T> {
             left: None,
             right: None,
             color: Color::Red,
+            size: 1,
+            count: 1,
+            total: 1,
         }
     }
 
+    /// Returns the size of a node's subtree (0 for `None`).
+    pub fn size(node: &Option<Box<Self>>) -> usize {
+        node.as_ref().map_or(0, |n| n.size)
+    }
+
+    /// Returns the sum of the counts in a node's subtree (0 for `None`).
+    pub fn total(node: &Option<Box<Self>>) -> usize {
+        node.as_ref().map_or(0, |n| n.total)
+    }
+
+    /// Updates this node's subtree size and total count based on children's ones.
+    pub fn update_size(&mut self) {
+        self.size = 1 + Self::size(&self.left) + Self::size(&self.right);
+        self.total = self.count + Self::total(&self.left) + Self::total(&self.right);
+    }
+
     /// Checks if this node is red.
     pub fn is_red(&self) -> bool {
         self.color == Color::Red
@@ -72,12 +103,24 @@ impl<T: PartialOrd> RBNode<T> {
     ///      / \          / \
     ///     b   c        a   b
     /// ```
-    pub fn rotate_left(mut self: Box<Self>) -> Box<Self> {
+    pub fn rotate_left(self: Box<Self>) -> Box<Self> {
+        self.rotate_left_counting(&mut Rotations::new())
+    }
+
+    /// Performs a left rotation like `rotate_left`, recording it in `rotations`.
+    pub(crate) fn rotate_left_counting(
+        mut self: Box<Self>,
+        rotations: &mut Rotations,
+    ) -> Box<Self> {
+        rotations.record();
+
         let mut new_root = self.right.take().expect("Right child must exist for left rotation");
         new_root.color = self.color;
         self.color = Color::Red;
         self.right = new_root.left.take();
+        self.update_size();
         new_root.left = Some(self);
+        new_root.update_size();
         new_root
     }
 
@@ -91,12 +134,24 @@ impl<T: PartialOrd> RBNode<T> {
     ///    / \              / \
     ///   a   b            b   c
     /// ```
-    pub fn rotate_right(mut self: Box<Self>) -> Box<Self> {
+    pub fn rotate_right(self: Box<Self>) -> Box<Self> {
+        self.rotate_right_counting(&mut Rotations::new())
+    }
+
+    /// Performs a right rotation like `rotate_right`, recording it in `rotations`.
+    pub(crate) fn rotate_right_counting(
+        mut self: Box<Self>,
+        rotations: &mut Rotations,
+    ) -> Box<Self> {
+        rotations.record();
+
         let mut new_root = self.left.take().expect("Left child must exist for right rotation");
         new_root.color = self.color;
         self.color = Color::Red;
         self.left = new_root.right.take();
+        self.update_size();
         new_root.right = Some(self);
+        new_root.update_size();
         new_root
     }
 
@@ -1,7 +1,25 @@
 use super::*;
+use crate::bytes::{self, LeBytes};
+use crate::diff::DiffItem;
+use crate::error::{DecodeError, DuplicateError};
+use crate::operation::Op;
+use crate::order::Order;
+use crate::traversal::TraversalOrder;
+use cursor::Cursor;
 use node::Color;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::ops::{ControlFlow, Range, Sub};
+
+/// Upper bound of the height of a tree fitting in memory: at most `2 * log2(n + 1)` for the
+/// `n < 2^64` nodes.
+const MAX_HEIGHT: usize = 128;
 
 impl<T: PartialOrd + Clone> RedBlackTree<T> {
     /// Creates a new empty `RedBlackTree`.
@@ -10,7 +28,251 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
             root: None,
             min_value: None,
             max_value: None,
+            pool: NodePool::new(),
+            rotations: Rotations::new(),
+        }
+    }
+
+    /// Creates a new empty `RedBlackTree` prepared for `capacity` insertions.
+    ///
+    /// With the `pool` feature enabled, `capacity` node allocations are made upfront and
+    /// handed out by the following insertions (see `pooled_nodes`). Without the feature the
+    /// nodes are always allocated on insertion, so this is the same as `new`.
+    ///
+    /// # Complexity:
+    /// *O*(capacity) with the `pool` feature, *O*(1) otherwise.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut tree = Self::new();
+        tree.pool = NodePool::with_capacity(capacity);
+        tree
+    }
+
+    /// Creates a new empty `RedBlackTree` (an alias for `new`).
+    pub fn empty() -> Self {
+        Self::new()
+    }
+
+    /// Creates a `RedBlackTree` containing exactly one element, the `value`.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn with_value(value: T) -> Self {
+        let mut tree = Self::new();
+        tree.insert(value);
+        tree
+    }
+
+    /// Creates a `RedBlackTree` by replaying the recorded operations of the `log` in order,
+    /// reproducing exactly the tree (including its shape) that they have produced.
+    ///
+    /// # Complexity:
+    /// The sum of the complexities of the replayed `insert`s and `remove`s.
+    pub fn from_insertion_log(log: &[Op<T>]) -> Self {
+        let mut tree = Self::new();
+        for op in log {
+            match op {
+                Op::Insert(value) => tree.insert(value.clone()),
+                Op::Remove(value) => tree.remove(value),
+            }
+        }
+        tree
+    }
+
+    /// Builds a valid `RedBlackTree` out of arbitrary (unsorted, possibly repeating) values.
+    ///
+    /// The values are sorted and deduplicated first, then the tree is built bottom-up from the
+    /// sorted sequence, which avoids the rotations and color flips of `n` separate insertions.
+    /// Values that are not comparable to themselves (e.g. `NaN`) are skipped.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) - dominated by sorting.
+    pub fn from_unsorted(iter: impl IntoIterator<Item = T>) -> Self {
+        let mut values: Vec<T> = iter
+            .into_iter()
+            .filter(|value| value.partial_cmp(value).is_some())
+            .collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        values.dedup_by(|a, b| (*a).partial_cmp(b) == Some(Ordering::Equal));
+
+        let mut tree = Self::new();
+        tree.rebuild_from_sorted(values);
+        tree
+    }
+
+    /// Replaces the contents of the tree with strictly ascending `values`, each stored once.
+    fn rebuild_from_sorted(&mut self, values: Vec<T>) {
+        self.min_value = values.first().cloned();
+        self.max_value = values.last().cloned();
+
+        let len = values.len();
+        self.root = Self::build_tree(
+            &mut values.into_iter().map(|value| (value, 1)),
+            len,
+            &mut self.pool,
+        );
+    }
+
+    /// Replaces the contents of the tree with strictly ascending `values` paired with their
+    /// counts (see `insert_multi`).
+    fn rebuild_from_counted(&mut self, values: Vec<(T, usize)>) {
+        self.min_value = values.first().map(|(value, _)| value.clone());
+        self.max_value = values.last().map(|(value, _)| value.clone());
+
+        let len = values.len();
+        self.root = Self::build_tree(&mut values.into_iter(), len, &mut self.pool);
+    }
+
+    /// Builds a tree out of `len` strictly ascending values paired with their counts.
+    ///
+    /// The tree gets the smallest height possible for `len` nodes: the largest black height
+    /// that fits into that many levels is chosen and the remaining levels are spent on red
+    /// nodes.
+    fn build_tree(
+        values: &mut impl Iterator<Item = (T, usize)>,
+        len: usize,
+        pool: &mut NodePool<RBNode<T>>,
+    ) -> Option<Box<RBNode<T>>> {
+        let levels = usize::BITS - len.leading_zeros();
+        let capacities = Self::capacities(levels);
+        let black_height = (1..=levels)
+            .rev()
+            .find(|&bh| {
+                (1 << bh) - 1 <= len && len <= capacities[bh as usize][(levels - bh) as usize]
+            })
+            .unwrap_or(0);
+
+        Self::build_balanced(
+            values,
+            len,
+            black_height,
+            levels - black_height,
+            &capacities,
+            pool,
+        )
+    }
+
+    /// Returns a table where `capacities[b][r]` is the largest number of values a left-leaning
+    /// subtree with black height `b` can hold if no path from its root to a leaf passes through
+    /// more than `r` red nodes (for `b` and `r` up to `levels`).
+    fn capacities(levels: u32) -> Vec<Vec<usize>> {
+        let size = levels as usize + 1;
+        let mut capacities = vec![vec![0usize; size]; size];
+
+        for b in 1..size {
+            for r in 0..size {
+                let two_node = capacities[b - 1][r].saturating_mul(2).saturating_add(1);
+                capacities[b][r] = if r == 0 {
+                    two_node
+                } else {
+                    let three_node = capacities[b - 1][r - 1]
+                        .saturating_mul(2)
+                        .saturating_add(capacities[b - 1][r])
+                        .saturating_add(2);
+                    two_node.max(three_node)
+                };
+            }
+        }
+
+        capacities
+    }
+
+    /// Builds a subtree out of the next `len` (value, count) pairs of an ascending sequence, with
+    /// the given black height and at most `reds` red nodes on any path from its root to a leaf.
+    ///
+    /// The subtree is laid out as a 2-3 tree, so it is left-leaning like the trees produced by
+    /// `insert`: a three-node (a black node with a red left child) is only used where two-nodes
+    /// can't hold `len` values. Requires `2^black_height - 1 <= len <= capacities[black_height][reds]`.
+    fn build_balanced(
+        values: &mut impl Iterator<Item = (T, usize)>,
+        len: usize,
+        black_height: u32,
+        reds: u32,
+        capacities: &[Vec<usize>],
+        pool: &mut NodePool<RBNode<T>>,
+    ) -> Option<Box<RBNode<T>>> {
+        if len == 0 {
+            return None;
+        }
+
+        let child_height = black_height - 1;
+        let child_capacity = capacities[child_height as usize][reds as usize];
+
+        let mut node = if len - 1 <= 2 * child_capacity {
+            let right_len = (len - 1) / 2;
+            let left = Self::build_balanced(
+                values,
+                len - 1 - right_len,
+                child_height,
+                reds,
+                capacities,
+                pool,
+            );
+            let mut node = Self::counted_node(values.next()?, pool);
+            node.left = left;
+            node.right =
+                Self::build_balanced(values, right_len, child_height, reds, capacities, pool);
+            node
+        } else {
+            // The subtrees under the red node may use one red node less on their paths.
+            let red_child_capacity = capacities[child_height as usize][reds as usize - 1];
+            let rest = len - 2;
+            let right_len = (rest / 3)
+                .max(rest.saturating_sub(2 * red_child_capacity))
+                .min(child_capacity);
+            let middle_len = (rest - right_len) / 2;
+            let left_len = rest - right_len - middle_len;
+
+            let left =
+                Self::build_balanced(values, left_len, child_height, reds - 1, capacities, pool);
+            let mut red = Self::counted_node(values.next()?, pool);
+            red.left = left;
+            red.right =
+                Self::build_balanced(values, middle_len, child_height, reds - 1, capacities, pool);
+            red.update_size();
+
+            let mut node = Self::counted_node(values.next()?, pool);
+            node.left = Some(red);
+            node.right =
+                Self::build_balanced(values, right_len, child_height, reds, capacities, pool);
+            node
+        };
+
+        node.color = Color::Black;
+        node.update_size();
+        Some(node)
+    }
+
+    /// Creates a detached node storing `value` the given number of times.
+    fn counted_node((value, count): (T, usize), pool: &mut NodePool<RBNode<T>>) -> Box<RBNode<T>> {
+        let mut node = pool.alloc(RBNode::new(value));
+        node.count = count;
+        node.total = count;
+        node
+    }
+
+    /// Moves the values out of a subtree in ascending order, paired with their counts.
+    fn drain_sorted(
+        root: Option<Box<RBNode<T>>>,
+        pool: &mut NodePool<RBNode<T>>,
+    ) -> Vec<(T, usize)> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = root;
+
+        while current.is_some() || !stack.is_empty() {
+            while let Some(mut node) = current {
+                current = node.left.take();
+                stack.push(node);
+            }
+
+            if let Some(node) = stack.pop() {
+                let node = pool.release(node);
+                current = node.right;
+                result.push((node.value, node.count));
+            }
         }
+
+        result
     }
 
     /// Checks if the tree is empty.
@@ -21,6 +283,39 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         self.root.is_none()
     }
 
+    /// Removes all elements from the tree.
+    ///
+    /// With the `pool` feature enabled, the node allocations are kept for reuse by the following
+    /// insertions instead of being returned to the allocator.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            let node = self.pool.release(node);
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+
+        self.min_value = None;
+        self.max_value = None;
+    }
+
+    /// Returns the number of node allocations kept for reuse by the following insertions.
+    ///
+    /// The pool is filled by `with_capacity`, `clear` and `remove` and drained by `insert`.
+    /// The rebuilding operations (e.g. `retain`, `split_off` or `append`) recycle the
+    /// allocations of the nodes they take apart.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    #[cfg(feature = "pool")]
+    pub fn pooled_nodes(&self) -> usize {
+        self.pool.len()
+    }
+
     /// Inserts a `value` into the tree while maintaining Red-Black Tree properties.
     ///
     /// # Complexity:
@@ -45,7 +340,8 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
             _ => unreachable!(),
         }
 
-        self.root = Self::insert_recursive(self.root.take(), value);
+        self.root =
+            Self::insert_iterative(self.root.take(), value, &mut self.pool, &mut self.rotations);
 
         // Ensure root is black
         if let Some(root) = &mut self.root {
@@ -53,28 +349,167 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         }
     }
 
-    /// Recursively inserts a value and maintains Red-Black Tree properties.
-    fn insert_recursive(node: Option<Box<RBNode<T>>>, value: T) -> Option<Box<RBNode<T>>> {
-        let mut node = match node {
-            None => return Some(Box::new(RBNode::new(value))),
-            Some(n) => n,
-        };
+    /// Inserts a `value` like `insert` and returns whether it has been added, i.e. `false` if
+    /// an equal element was already present (or the `value` is incomparable and was skipped).
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn try_insert(&mut self, value: T) -> bool {
+        let len = self.len();
+        self.insert(value);
+        self.len() > len
+    }
 
-        match value.partial_cmp(&node.value) {
-            Some(Ordering::Less) => {
-                node.left = Self::insert_recursive(node.left.take(), value);
+    /// Inserts a `value` into the tree counting duplicates: if an equal element is already
+    /// present, its count is incremented (and the `value` dropped) instead of ignoring it.
+    ///
+    /// The counts turn the tree into a multiset: `count` reports them, `len` sums them and
+    /// `remove`, `remove_min` and `remove_max` decrement them one by one. Operations moving
+    /// elements between trees (e.g. `split_off` or `append`) carry the counts along.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn insert_multi(&mut self, value: T) {
+        if !self.contains(&value) {
+            self.insert(value);
+            return;
+        }
+
+        let mut cursor = &mut self.root;
+        while let Some(node) = cursor {
+            node.total += 1;
+            match value.partial_cmp(&node.value) {
+                Some(Ordering::Less) => cursor = &mut node.left,
+                Some(Ordering::Greater) => cursor = &mut node.right,
+                _ => {
+                    node.count += 1;
+                    return;
+                }
             }
-            Some(Ordering::Greater) => {
-                node.right = Self::insert_recursive(node.right.take(), value);
+        }
+    }
+
+    /// Returns how many times a `value` is stored in the tree: the number of its insertions
+    /// with `insert_multi` (1 for the elements inserted otherwise), or 0 if it is absent.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn count<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            match value.partial_cmp(node.value.borrow()) {
+                Some(Ordering::Less) => cursor = &node.left,
+                Some(Ordering::Greater) => cursor = &node.right,
+                Some(Ordering::Equal) => return node.count,
+                None => return 0,
             }
-            Some(Ordering::Equal) | None => {
-                // Duplicate or incomparable values are not inserted
-                return Some(node);
+        }
+
+        0
+    }
+
+    /// Inserts a `value` into the tree, replacing the stored element that compares equal to it.
+    ///
+    /// Returns the replaced element, or `None` if the `value` was not present and has been
+    /// inserted (the tree-set analogue of `HashSet::replace`).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(log n) (due to balancing)
+    /// - Best: *O*(1) (root match)
+    pub fn insert_or_replace(&mut self, value: T) -> Option<T> {
+        let mut cursor = &mut self.root;
+
+        while let Some(current_node) = cursor {
+            match value.partial_cmp(&current_node.value) {
+                Some(Ordering::Less) => cursor = &mut current_node.left,
+                Some(Ordering::Greater) => cursor = &mut current_node.right,
+                Some(Ordering::Equal) => {
+                    let replaced = std::mem::replace(&mut current_node.value, value);
+
+                    if self
+                        .min_value
+                        .as_ref()
+                        .and_then(|min| min.partial_cmp(&replaced))
+                        == Some(Ordering::Equal)
+                    {
+                        self.min_value = Some(current_node.value.clone());
+                    }
+                    if self
+                        .max_value
+                        .as_ref()
+                        .and_then(|max| max.partial_cmp(&replaced))
+                        == Some(Ordering::Equal)
+                    {
+                        self.max_value = Some(current_node.value.clone());
+                    }
+
+                    return Some(replaced);
+                }
+                None => return None,
             }
         }
 
+        self.insert(value);
+        None
+    }
+
+    /// Inserts a value and maintains Red-Black Tree properties.
+    ///
+    /// The nodes on the search path are detached onto an explicit stack and balanced bottom-up
+    /// while they are reattached, so deep trees don't grow the call stack.
+    fn insert_iterative(
+        root: Option<Box<RBNode<T>>>,
+        value: T,
+        pool: &mut NodePool<RBNode<T>>,
+        rotations: &mut Rotations,
+    ) -> Option<Box<RBNode<T>>> {
+        // Ancestors of the current subtree, with whether the subtree is their left child
+        // (an array rather than a `Vec`, so inserting into a pooled node doesn't allocate)
+        let mut path: [Option<(Box<RBNode<T>>, bool)>; MAX_HEIGHT] = [const { None }; MAX_HEIGHT];
+        let mut depth = 0;
+        let mut cursor = root;
+
+        let mut subtree = loop {
+            let mut node = match cursor {
+                None => break pool.alloc(RBNode::new(value)),
+                Some(n) => n,
+            };
+
+            match value.partial_cmp(&node.value) {
+                Some(Ordering::Less) => {
+                    cursor = node.left.take();
+                    path[depth] = Some((node, true));
+                    depth += 1;
+                }
+                Some(Ordering::Greater) => {
+                    cursor = node.right.take();
+                    path[depth] = Some((node, false));
+                    depth += 1;
+                }
+                Some(Ordering::Equal) | None => {
+                    // Duplicate or incomparable values are not inserted
+                    break node;
+                }
+            }
+        };
+
         // Balance the tree
-        Some(Self::balance(node))
+        for (mut parent, is_left) in path[..depth].iter_mut().rev().filter_map(Option::take) {
+            if is_left {
+                parent.left = Some(subtree);
+            } else {
+                parent.right = Some(subtree);
+            }
+            subtree = Self::balance(parent, rotations);
+        }
+
+        Some(subtree)
     }
 
     /// Balances the tree after insertion using rotations and color flips.
@@ -83,16 +518,18 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
     /// 1. Right child is red and left child is black: rotate left
     /// 2. Left child and left-left grandchild are both red: rotate right
     /// 3. Both children are red: flip colors
-    fn balance(mut node: Box<RBNode<T>>) -> Box<RBNode<T>> {
+    fn balance(mut node: Box<RBNode<T>>, rotations: &mut Rotations) -> Box<RBNode<T>> {
+        node.update_size();
+
         // Case 1: Right child is red and left child is black - rotate left
         if RBNode::is_red_node(&node.right) && !RBNode::is_red_node(&node.left) {
-            node = node.rotate_left();
+            node = node.rotate_left_counting(rotations);
         }
 
         // Case 2: Left child and left-left grandchild are both red - rotate right
         if RBNode::is_red_node(&node.left)
             && node.left.as_ref().is_some_and(|left| RBNode::is_red_node(&left.left)) {
-            node = node.rotate_right();
+            node = node.rotate_right_counting(rotations);
         }
 
         // Case 3: Both children are red - flip colors
@@ -103,17 +540,34 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         node
     }
 
+    /// Returns the number of rotations performed by the tree over its lifetime.
+    ///
+    /// A double rotation (left-right or right-left case) counts as two rotations.
+    ///
+    /// # Complexity:
+    /// *O*(1) - the counter is maintained by insertions and removals.
+    #[cfg(feature = "metrics")]
+    pub fn rotation_count(&self) -> u64 {
+        self.rotations.count()
+    }
+
     /// Checks if the tree contains a `value`.
     ///
+    /// Like the other lookups, it accepts any borrowed form of the element type.
+    ///
     /// # Complexity:
     /// - Average: *O*(log n)
     /// - Worst: *O*(log n) (due to balancing)
     /// - Best: *O*(1) (root match)
-    pub fn contains(&self, value: &T) -> bool {
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
         let mut cursor = &self.root;
 
         while let Some(current_node) = cursor {
-            match value.partial_cmp(&current_node.value) {
+            match value.partial_cmp(current_node.value.borrow()) {
                 Some(Ordering::Less) => cursor = &current_node.left,
                 Some(Ordering::Greater) => cursor = &current_node.right,
                 Some(Ordering::Equal) => return true,
@@ -124,8 +578,132 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         false
     }
 
+    /// Returns a reference to the stored element that compares equal to the `value`,
+    /// or `None` if there is no such element.
+    ///
+    /// Elements are identified by `partial_cmp` alone, so the stored representative may differ
+    /// from the `value` in everything that the ordering ignores (and even by `==`).
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(log n) (due to balancing)
+    /// - Best: *O*(1) (root match)
+    pub fn stored_equal_to<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            match value.partial_cmp(current_node.value.borrow())? {
+                Ordering::Less => cursor = &current_node.left,
+                Ordering::Greater => cursor = &current_node.right,
+                Ordering::Equal => return Some(&current_node.value),
+            }
+        }
+
+        None
+    }
+
+    /// Returns a reference to the stored element that compares equal to the `value`
+    /// (an alias for `stored_equal_to`), for map-like usage where equality is by key.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.stored_equal_to(value)
+    }
+
+    /// Returns the rank of the `value` (the number of elements less than it) if the tree
+    /// contains it, or `None` otherwise.
+    ///
+    /// Presence and rank are found in a single descent using the subtree sizes of the nodes.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(log n) (due to balancing)
+    /// - Best: *O*(1) (root match)
+    pub fn find(&self, value: &T) -> Option<usize> {
+        let mut rank = 0;
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            match value.partial_cmp(&current_node.value)? {
+                Ordering::Less => cursor = &current_node.left,
+                Ordering::Greater => {
+                    rank += RBNode::size(&current_node.left) + 1;
+                    cursor = &current_node.right;
+                }
+                Ordering::Equal => return Some(rank + RBNode::size(&current_node.left)),
+            }
+        }
+
+        None
+    }
+
+    /// Checks if at least one element `x` of the tree lies in the inclusive range `[lo, hi]`
+    /// (`lo <= x <= hi`), stopping at the first one found. If `lo > hi` the result is `false`.
+    ///
+    /// # Complexity:
+    /// - Average: *O*(log n)
+    /// - Worst: *O*(log n) (due to balancing)
+    /// - Best: *O*(1) (root inside the range)
+    pub fn has_any_in_range(&self, lo: &T, hi: &T) -> bool {
+        let mut cursor = &self.root;
+
+        while let Some(current_node) = cursor {
+            if &current_node.value < lo {
+                cursor = &current_node.right;
+            } else if &current_node.value > hi {
+                cursor = &current_node.left;
+            } else {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Returns the positions `start..end` in ascending order covered by the elements `x` of
+    /// the inclusive range `[lo, hi]` (`lo <= x <= hi`), so that they are exactly
+    /// `in_order()[start..end]`. An empty intersection (e.g. `lo > hi`) gives `start == end`.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - two descents, Red-Black Trees are always balanced
+    pub fn index_range(&self, lo: &T, hi: &T) -> Range<usize> {
+        let start = self.count_prefix(|value| value < lo);
+        let end = self.count_prefix(|value| value <= hi);
+        start..end.max(start)
+    }
+
+    /// Counts the elements satisfying `in_prefix`, which must hold for a prefix of the
+    /// elements in ascending order, descending with the subtree sizes of the nodes.
+    fn count_prefix(&self, in_prefix: impl Fn(&T) -> bool) -> usize {
+        let mut count = 0;
+        let mut cursor = &self.root;
+
+        while let Some(node) = cursor {
+            if in_prefix(&node.value) {
+                count += RBNode::size(&node.left) + 1;
+                cursor = &node.right;
+            } else {
+                cursor = &node.left;
+            }
+        }
+
+        count
+    }
+
     /// Returns a reference to the minimum element of the tree or `None` if tree is empty.
     ///
+    /// The minimum is the first element in the order defined by `T`, so for a tree of
+    /// `std::cmp::Reverse<U>` it wraps the largest `U`.
+    ///
     /// # Complexity:
     /// *O*(1) (due to storing the minimum element inside the tree structure).
     pub fn min(&self) -> Option<&T> {
@@ -134,12 +712,23 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
 
     /// Returns a reference to the maximum element of the tree or `None` if tree is empty.
     ///
+    /// The maximum is the last element in the order defined by `T`, so for a tree of
+    /// `std::cmp::Reverse<U>` it wraps the smallest `U`.
+    ///
     /// # Complexity:
     /// *O*(1) (due to storing the maximum element inside the tree structure).
     pub fn max(&self) -> Option<&T> {
         self.max_value.as_ref()
     }
 
+    /// Returns a reference to the value of the root node or `None` if tree is empty.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn root_value(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
     /// Each time the tree is updated, you need to re-search for the minimum.
     ///
     /// # Complexity
@@ -177,6 +766,27 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         None
     }
 
+    /// Returns the depth of the node holding the `value` (the number of edges from the root,
+    /// which is at depth 0), or `None` if the tree doesn't contain the `value`.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn depth(&self, value: &T) -> Option<usize> {
+        let mut cursor = &self.root;
+        let mut depth = 0;
+
+        while let Some(node) = cursor {
+            match value.partial_cmp(&node.value)? {
+                Ordering::Less => cursor = &node.left,
+                Ordering::Greater => cursor = &node.right,
+                Ordering::Equal => return Some(depth),
+            }
+            depth += 1;
+        }
+
+        None
+    }
+
     /// Returns the height of the tree (longest path from root to leaf).
     ///
     /// # Complexity:
@@ -215,6 +825,62 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         height
     }
 
+    /// Returns the diameter of the tree: the number of edges on the longest path between
+    /// any two nodes (0 for an empty tree and for a single node).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn diameter(&self) -> usize {
+        let mut diameter = 0;
+        // The numbers of levels of the finished subtrees, the right one on top.
+        let mut levels = Vec::new();
+        let mut stack: Vec<_> = self.root.iter().map(|node| (node, false)).collect();
+
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                let right = node.right.as_ref().and_then(|_| levels.pop()).unwrap_or(0);
+                let left = node.left.as_ref().and_then(|_| levels.pop()).unwrap_or(0);
+                diameter = diameter.max(left + right);
+                levels.push(left.max(right) + 1);
+            } else {
+                stack.push((node, true));
+                stack.extend(node.right.iter().map(|right| (right, false)));
+                stack.extend(node.left.iter().map(|left| (left, false)));
+            }
+        }
+
+        diameter
+    }
+
+    /// Returns the number of leaves of the tree (nodes without children).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn count_leaves(&self) -> usize {
+        let mut leaves = 0;
+        let mut stack: Vec<_> = self.root.iter().collect();
+
+        while let Some(node) = stack.pop() {
+            if node.left.is_none() && node.right.is_none() {
+                leaves += 1;
+            }
+            stack.extend(&node.left);
+            stack.extend(&node.right);
+        }
+
+        leaves
+    }
+
+    /// Returns the number of internal nodes of the tree (nodes with at least one child).
+    /// Together with `count_leaves` it adds up to `number_of_distinct()` (the
+    /// number of nodes, which differs from `len()` for repeated `insert_multi`s).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes (to count the leaves).
+    pub fn count_internal_nodes(&self) -> usize {
+        self.number_of_distinct() - self.count_leaves()
+    }
+
     /// Returns references to the elements of the tree in the order of a preorder traversal.
     ///
     /// # Complexity:
@@ -286,17 +952,81 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         result
     }
 
-    /// Returns references to the elements of the tree in the order of a postorder traversal.
+    /// Returns references to the elements of the tree in descending order (the reverse of
+    /// `in_order`), walking the tree right-first instead of reversing a vector.
     ///
     /// # Complexity:
     /// *O*(n) - visits all nodes.
-    ///
-    /// # Example:
-    ///
-    /// If such a tree is given
-    ///```text
-    ///      4
-    ///     / \
+    pub fn reverse_in_order(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        while !stack.is_empty() || current.is_some() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.right;
+            }
+
+            if let Some(node) = stack.pop() {
+                result.push(&node.value);
+                current = &node.left;
+            }
+        }
+
+        result
+    }
+
+    /// Lazily yields the elements of the tree in ascending order together with the colors
+    /// of their nodes.
+    ///
+    /// # Complexity:
+    /// *O*(n) for the full iteration, amortized *O*(1) per element.
+    pub fn iter_colored(&self) -> impl Iterator<Item = (&T, Color)> {
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        std::iter::from_fn(move || {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+
+            let node = stack.pop()?;
+            current = &node.right;
+            Some((&node.value, node.color))
+        })
+    }
+
+    /// Lazily yields the elements of the tree in ascending order together with their counts
+    /// (see `insert_multi`).
+    fn iter_counted(&self) -> impl Iterator<Item = (&T, usize)> {
+        let mut stack = Vec::new();
+        let mut current = &self.root;
+
+        std::iter::from_fn(move || {
+            while let Some(node) = current {
+                stack.push(node);
+                current = &node.left;
+            }
+
+            let node = stack.pop()?;
+            current = &node.right;
+            Some((&node.value, node.count))
+        })
+    }
+
+    /// Returns references to the elements of the tree in the order of a postorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    ///```text
+    ///      4
+    ///     / \
     ///    2   5
     ///   / \   \
     ///  1   3   6
@@ -370,13 +1100,351 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         result
     }
 
-    /// Returns the number of elements of the tree (the number of elements in the vector
-    /// for the preorder traversal).
+    /// Returns references to the elements of the tree grouped by depth: the element `i` of
+    /// the result holds the elements at depth `i` (the root at depth 0) from left to right.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// For the tree given in the example of `level_order` the result is
+    /// `vec![vec![&4], vec![&2, &5], vec![&1, &3, &6]]`.
+    pub fn level_order_grouped(&self) -> Vec<Vec<&T>> {
+        let mut result = Vec::new();
+        let mut level: Vec<&Box<_>> = self.root.iter().collect();
+
+        while !level.is_empty() {
+            result.push(level.iter().map(|node| &node.value).collect());
+            level = level
+                .into_iter()
+                .flat_map(|node| [&node.left, &node.right])
+                .flatten()
+                .collect();
+        }
+
+        result
+    }
+
+    /// Returns references to the elements of the tree in the order of a level order traversal,
+    /// with `None` markers for the absent children of the present nodes, so that the exact shape
+    /// of the tree can be recovered (the format used by LeetCode).
+    ///
+    /// Trailing `None` markers are omitted, an empty tree gives an empty vector.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    ///
+    /// # Example:
+    ///
+    /// If such a tree is given
+    /// ```text
+    ///      4
+    ///     / \
+    ///    2   5
+    ///   / \   \
+    ///  1   3   6
+    /// ```
+    /// Then the result of this traversal will be like this:
+    /// `vec![Some(&4), Some(&2), Some(&5), Some(&1), Some(&3), None, Some(&6)]`.
+    pub fn level_order_with_nulls(&self) -> Vec<Option<&T>> {
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+
+        if self.root.is_some() {
+            queue.push_back(&self.root);
+        }
+
+        while let Some(link) = queue.pop_front() {
+            match link {
+                Some(node) => {
+                    result.push(Some(&node.value));
+                    queue.push_back(&node.left);
+                    queue.push_back(&node.right);
+                }
+                None => result.push(None),
+            }
+        }
+
+        while matches!(result.last(), Some(None)) {
+            result.pop();
+        }
+
+        result
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a preorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn pre_order_cloned(&self) -> Vec<T> {
+        self.pre_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a inorder traversal
+    /// (i.e. sorted in ascending order).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn in_order_cloned(&self) -> Vec<T> {
+        self.in_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a postorder traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn post_order_cloned(&self) -> Vec<T> {
+        self.post_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a level order traversal.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn level_order_cloned(&self) -> Vec<T> {
+        self.level_order().into_iter().cloned().collect()
+    }
+
+    /// Returns owned copies of the elements of the tree in the order of a level order traversal,
+    /// with `None` markers for absent children (see `level_order_with_nulls`).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn level_order_with_nulls_cloned(&self) -> Vec<Option<T>> {
+        self.level_order_with_nulls()
+            .into_iter()
+            .map(|value| value.cloned())
+            .collect()
+    }
+
+    /// Consumes the tree and moves its elements out in the order of a level order traversal,
+    /// without cloning them.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn into_level_order(self) -> Vec<T> {
+        let mut result = Vec::new();
+        let mut queue: VecDeque<Box<RBNode<T>>> = self.root.into_iter().collect();
+
+        while let Some(node) = queue.pop_front() {
+            let node = *node;
+            queue.extend(node.left);
+            queue.extend(node.right);
+            result.push(node.value);
+        }
+
+        result
+    }
+
+    /// Consumes the tree and moves its elements out in ascending order, without cloning them.
+    ///
+    /// An element inserted several times with `insert_multi` is repeated `count` times, all
+    /// but the last occurrence being clones.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        Self::drain_sorted(self.root, &mut self.pool)
+            .into_iter()
+            .flat_map(|(value, count)| std::iter::repeat_n(value, count))
+            .collect()
+    }
+
+    /// Calls `f` on every element of the tree in the given traversal `order`, without
+    /// collecting the elements into a vector.
     ///
     /// # Complexity:
-    /// *O*(n) - traverses entire tree.
+    /// *O*(n) - visits all nodes.
+    pub fn walk<F: FnMut(&T)>(&self, order: TraversalOrder, mut f: F) {
+        let _ = self.try_walk(order, |value| {
+            f(value);
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    /// Calls `f` on every element of the tree in the given traversal `order` (an alias for
+    /// `walk`).
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn for_each<F: FnMut(&T)>(&self, order: TraversalOrder, f: F) {
+        self.walk(order, f);
+    }
+
+    /// Calls `f` on the elements of the tree in the given traversal `order` until it returns
+    /// `ControlFlow::Break`, and returns the break value (or `ControlFlow::Continue(())` if
+    /// all elements have been visited).
+    ///
+    /// # Complexity:
+    /// *O*(k) (up to *O*(n)), where `k` is the number of visited elements (plus the nodes
+    /// on the path to the first of them).
+    pub fn try_walk<B, F: FnMut(&T) -> ControlFlow<B>>(
+        &self,
+        order: TraversalOrder,
+        mut f: F,
+    ) -> ControlFlow<B> {
+        match order {
+            TraversalOrder::PreOrder => {
+                let mut stack: Vec<_> = self.root.iter().collect();
+
+                while let Some(node) = stack.pop() {
+                    f(&node.value)?;
+                    stack.extend(&node.right);
+                    stack.extend(&node.left);
+                }
+            }
+            TraversalOrder::InOrder => {
+                let mut stack = Vec::new();
+                let mut current = &self.root;
+
+                while current.is_some() || !stack.is_empty() {
+                    while let Some(node) = current {
+                        stack.push(node);
+                        current = &node.left;
+                    }
+
+                    if let Some(node) = stack.pop() {
+                        f(&node.value)?;
+                        current = &node.right;
+                    }
+                }
+            }
+            TraversalOrder::PostOrder => {
+                // Each node is pushed twice: to expand its children, then to be visited.
+                let mut stack: Vec<_> = self.root.iter().map(|node| (node, false)).collect();
+
+                while let Some((node, expanded)) = stack.pop() {
+                    if expanded {
+                        f(&node.value)?;
+                    } else {
+                        stack.push((node, true));
+                        stack.extend(node.right.iter().map(|right| (right, false)));
+                        stack.extend(node.left.iter().map(|left| (left, false)));
+                    }
+                }
+            }
+            TraversalOrder::LevelOrder => {
+                let mut queue: VecDeque<_> = self.root.iter().collect();
+
+                while let Some(node) = queue.pop_front() {
+                    f(&node.value)?;
+                    queue.extend(&node.left);
+                    queue.extend(&node.right);
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Checks if the elements of the tree in ascending order are equal to the `other` slice,
+    /// which is expected to be sorted.
+    ///
+    /// A shorthand for comparing `in_order()` against a sorted reference in tests.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes (*O*(1) if the lengths differ).
+    pub fn same_elements_as(&self, other: &[T]) -> bool {
+        RBNode::size(&self.root) == other.len() && self.in_order().into_iter().eq(other)
+    }
+
+    /// Checks that every element of the tree is comparable to itself and to its neighbors
+    /// in ascending order (`partial_cmp` returns `Some(_)`), e.g. that no `NaN` is stored.
+    ///
+    /// Incomparable values break the ordering that searches rely on, which the BST invariant
+    /// check alone may not notice.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn all_comparable(&self) -> bool {
+        let values = self.in_order();
+
+        values
+            .iter()
+            .all(|value| value.partial_cmp(value).is_some())
+            && values
+                .windows(2)
+                .all(|pair| pair[0].partial_cmp(pair[1]).is_some())
+    }
+
+    /// Walks this tree and the `other` one together in ascending order, yielding every element
+    /// as `DiffItem::Left` (only in `self`), `DiffItem::Right` (only in `other`) or
+    /// `DiffItem::Both` (in both trees).
+    ///
+    /// Elements that are incomparable to the current element of the other tree are yielded
+    /// as `DiffItem::Left` first.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - both trees are visited once.
+    pub fn merge_diff<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = DiffItem<'a, T>> {
+        let mut left = self.in_order().into_iter().peekable();
+        let mut right = other.in_order().into_iter().peekable();
+
+        std::iter::from_fn(move || match (left.peek(), right.peek()) {
+            (Some(a), Some(b)) => match a.partial_cmp(b) {
+                Some(Ordering::Greater) => right.next().map(DiffItem::Right),
+                Some(Ordering::Equal) => Some(DiffItem::Both(left.next()?, right.next()?)),
+                _ => left.next().map(DiffItem::Left),
+            },
+            (Some(_), None) => left.next().map(DiffItem::Left),
+            (None, _) => right.next().map(DiffItem::Right),
+        })
+    }
+
+    /// Returns a `Cursor` positioned before the smallest element, which reads the elements
+    /// in ascending order page by page (see `Cursor::next_page`).
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor::new(self)
+    }
+
+    /// Returns the number of elements of the tree, counting every occurrence of the elements
+    /// inserted several times with `insert_multi` (see `number_of_distinct`).
+    ///
+    /// The count is read from the subtree total cached in the root, so duplicates rejected by
+    /// `insert` and failed removals never affect it.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn len(&self) -> usize {
+        RBNode::total(&self.root)
+    }
+
+    /// Returns the number of distinct elements of the tree, i.e. the number of its nodes.
+    /// It differs from `len` only if some elements have been inserted with `insert_multi`.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn number_of_distinct(&self) -> usize {
+        RBNode::size(&self.root)
+    }
+
+    /// Returns the number of elements of the tree.
+    ///
+    /// Kept for compatibility, delegates to [`len`](Self::len).
+    ///
+    /// # Complexity:
+    /// *O*(1)
     pub fn number_of_elements(&self) -> usize {
-        self.pre_order().len()
+        self.len()
+    }
+
+    /// Returns an estimate of the memory used by the tree in bytes: the tree itself plus one
+    /// boxed node allocation per element (and per pooled allocation with the `pool` feature).
+    ///
+    /// The node size includes the child links, the subtree size and total, the count and the
+    /// `color` of every node.
+    ///
+    /// Allocator padding and any heap memory owned by the values themselves are not counted.
+    ///
+    /// # Complexity:
+    /// *O*(1)
+    pub fn approx_memory_bytes(&self) -> usize {
+        let nodes = RBNode::size(&self.root) + self.pool.len();
+        size_of::<Self>() + nodes * size_of::<RBNode<T>>()
     }
 
     /// Returns a value that is the rounded `value` to the nearest larger in the tree,
@@ -387,18 +1455,22 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
     /// - Best case: *O*(1) - when the value matches the root node
     /// - Average case: *O*(log n) - for balanced trees
     /// - Worst case: *O*(log n) - Red-Black Trees are always balanced
-    pub fn ceil(&self, value: &T) -> Option<&T> {
+    pub fn ceil<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
         self.root.as_ref()?;
 
         let mut result = None;
         let mut cursor = &self.root;
 
         while let Some(node) = cursor {
-            if &node.value == value {
+            if node.value.borrow() == value {
                 return Some(&node.value);
             }
 
-            if &node.value < value {
+            if node.value.borrow() < value {
                 cursor = &node.right;
             } else {
                 result = Some(&node.value);
@@ -417,18 +1489,22 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
     /// - Best case: *O*(1) - when the value matches the root node
     /// - Average case: *O*(log n) - for balanced trees
     /// - Worst case: *O*(log n) - Red-Black Trees are always balanced
-    pub fn floor(&self, value: &T) -> Option<&T> {
+    pub fn floor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
         self.root.as_ref()?;
 
         let mut result = None;
         let mut cursor = &self.root;
 
         while let Some(node) = cursor {
-            if &node.value == value {
+            if node.value.borrow() == value {
                 return Some(&node.value);
             }
 
-            if &node.value > value {
+            if node.value.borrow() > value {
                 cursor = &node.left;
             } else {
                 result = Some(&node.value);
@@ -439,6 +1515,137 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         result
     }
 
+    /// Returns the smallest element of the tree that is greater than or equal to `value`
+    /// (an alias for `ceil`).
+    ///
+    /// # Complexity:
+    /// The same as for `ceil`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::red_black_tree::RedBlackTree;
+    ///
+    /// let mut rbt = RedBlackTree::new();
+    /// for value in [10, 20, 30] {
+    ///     rbt.insert(value);
+    /// }
+    ///
+    /// assert_eq!(rbt.successor_or_equal(&15), Some(&20));
+    /// assert_eq!(rbt.successor_or_equal(&20), Some(&20));
+    /// assert_eq!(rbt.successor_or_equal(&31), None);
+    /// ```
+    pub fn successor_or_equal(&self, value: &T) -> Option<&T> {
+        self.ceil(value)
+    }
+
+    /// Returns the largest element of the tree that is less than or equal to `value`
+    /// (an alias for `floor`).
+    ///
+    /// # Complexity:
+    /// The same as for `floor`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use data_forest::red_black_tree::RedBlackTree;
+    ///
+    /// let mut rbt = RedBlackTree::new();
+    /// for value in [10, 20, 30] {
+    ///     rbt.insert(value);
+    /// }
+    ///
+    /// assert_eq!(rbt.predecessor_or_equal(&15), Some(&10));
+    /// assert_eq!(rbt.predecessor_or_equal(&20), Some(&20));
+    /// assert_eq!(rbt.predecessor_or_equal(&9), None);
+    /// ```
+    pub fn predecessor_or_equal(&self, value: &T) -> Option<&T> {
+        self.floor(value)
+    }
+
+    /// Snaps a `value` to the nearest element of the tree at or below it (the `floor`), falling
+    /// back to the minimum when there is no such element (the `value` is below the range).
+    /// Returns `None` only if the tree is empty.
+    ///
+    /// # Example:
+    ///
+    /// For a tree of `10, 20, 30`: `clamp(&5)` is `Some(&10)`, `clamp(&25)` is `Some(&20)`,
+    /// `clamp(&20)` is `Some(&20)` and `clamp(&99)` is `Some(&30)`.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn clamp(&self, value: &T) -> Option<&T> {
+        self.floor(value).or_else(|| self.min())
+    }
+
+    /// Returns the (up to) `k` elements closest to `value`, ordered by their distance to it
+    /// (the difference of the larger and the smaller one). Of two equally distant elements
+    /// the smaller one comes first.
+    ///
+    /// The elements below and above `value` are walked outward simultaneously, starting from
+    /// its floor and ceil, so only the returned elements are visited after the first descent.
+    ///
+    /// # Complexity:
+    /// *O*(log n + k) - Red-Black Trees are always balanced
+    pub fn nearest_k(&self, value: &T, k: usize) -> Vec<&T>
+    where
+        T: Sub<Output = T>,
+    {
+        let distance = |other: &T| {
+            if other > value {
+                other.clone() - value.clone()
+            } else {
+                value.clone() - other.clone()
+            }
+        };
+
+        // The nodes still to be visited on each side, the nearest to `value` on top.
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+        let mut cursor = &self.root;
+        while let Some(node) = cursor {
+            if &node.value < value {
+                below.push(node);
+                cursor = &node.right;
+            } else {
+                above.push(node);
+                cursor = &node.left;
+            }
+        }
+
+        let mut result = Vec::new();
+        while result.len() < k {
+            let take_below = match (below.last(), above.last()) {
+                (Some(b), Some(a)) => distance(&b.value) <= distance(&a.value),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+
+            if take_below {
+                let node = below.pop().unwrap();
+                result.push(&node.value);
+
+                let mut cursor = &node.left;
+                while let Some(next) = cursor {
+                    below.push(next);
+                    cursor = &next.right;
+                }
+            } else {
+                let node = above.pop().unwrap();
+                result.push(&node.value);
+
+                let mut cursor = &node.right;
+                while let Some(next) = cursor {
+                    above.push(next);
+                    cursor = &next.left;
+                }
+            }
+        }
+
+        result
+    }
+
     /// Performs a tree traversal and returns all pairs of connections between nodes.
     pub fn find_connections(&self) -> Vec<(&T, &T)> {
         let mut result = Vec::new();
@@ -462,21 +1669,141 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         result
     }
 
+    /// Returns all connections between nodes like `find_connections` (in the same order),
+    /// together with the color of the child node of each of them.
+    ///
+    /// The connections start at the root, which is always black.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn find_connections_with_colors(&self) -> Vec<(&T, &T, Color)> {
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+
+        if let Some(root) = &self.root {
+            queue.push_back(root);
+        }
+
+        while let Some(node) = queue.pop_front() {
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back(child);
+                result.push((&node.value, &child.value, child.color));
+            }
+        }
+
+        result
+    }
+
+    /// Lazily yields all pairs of connections between nodes (parent, child), in the same order
+    /// as `find_connections` (level order, the left child before the right one).
+    ///
+    /// Only the nodes of at most two levels are buffered, so the whole edge list
+    /// is never allocated at once.
+    ///
+    /// # Complexity:
+    /// *O*(n) for the full iteration, amortized *O*(1) per edge.
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T)> {
+        let mut queue: VecDeque<(&T, &RBNode<T>)> = VecDeque::new();
+        if let Some(root) = &self.root {
+            for child in [&root.left, &root.right].into_iter().flatten() {
+                queue.push_back((&root.value, child));
+            }
+        }
+
+        std::iter::from_fn(move || {
+            let (parent, node) = queue.pop_front()?;
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back((&node.value, child));
+            }
+            Some((parent, &node.value))
+        })
+    }
+
     /// Removes a `value` from the tree while maintaining Red-Black Tree properties.
     ///
     /// # Complexity:
     /// - Average: *O*(log n)
     /// - Worst: *O*(log n) (due to balancing)
     /// - Best: *O*(1) (leaf node)
-    pub fn remove(&mut self, value: &T)
+    pub fn remove<Q>(&mut self, value: &Q)
     where
-        T: PartialOrd + Clone,
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
     {
+        self.take(value);
+    }
+
+    /// Removes a `value` from the tree like `remove` and returns the stored element equal to
+    /// it, or `None` if there was no such element.
+    ///
+    /// If the element has been inserted several times with `insert_multi`, only one occurrence
+    /// is removed: the count is decremented and a clone of the stored element is returned,
+    /// the node is unlinked only when its last occurrence is removed.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.root.as_ref()?;
+
+        // The totals along the path may only shrink once the value is known to be repeated.
+        if self.count(value) > 1 {
+            let mut cursor = &mut self.root;
+            while let Some(node) = cursor {
+                node.total -= 1;
+                match value.partial_cmp(node.value.borrow()) {
+                    Some(Ordering::Less) => cursor = &mut node.left,
+                    Some(Ordering::Greater) => cursor = &mut node.right,
+                    _ => {
+                        node.count -= 1;
+                        return Some(node.value.clone());
+                    }
+                }
+            }
+        }
+
+        let mut removed = None;
+        self.root = Self::remove_recursive(
+            self.root.take(),
+            value,
+            &mut removed,
+            &mut self.pool,
+            &mut self.rotations,
+        );
+
+        // Ensure root is black
+        if let Some(root) = &mut self.root {
+            root.color = Color::Black;
+        }
+
+        self.min_value = self.refind_min();
+        self.max_value = self.refind_max();
+
+        removed
+    }
+
+    /// Removes the minimum element of the tree without returning it, descending only the
+    /// left spine instead of searching for it like `remove`.
+    /// Returns whether an element was removed (`false` if the tree is empty).
+    ///
+    /// Like in `remove`, only one occurrence of a minimum inserted several times with
+    /// `insert_multi` is removed.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn remove_min(&mut self) -> bool {
         if self.root.is_none() {
-            return;
+            return false;
+        }
+
+        if self.decrement_repeated_extreme(true) {
+            return true;
         }
 
-        self.root = Self::remove_recursive(self.root.take(), value);
+        self.root = Self::remove_min_node(self.root.take(), &mut self.pool, &mut self.rotations);
 
         // Ensure root is black
         if let Some(root) = &mut self.root {
@@ -484,32 +1811,250 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
         }
 
         self.min_value = self.refind_min();
+        if self.root.is_none() {
+            self.max_value = None;
+        }
+
+        true
+    }
+
+    /// Removes the maximum element of the tree without returning it, descending only the
+    /// right spine instead of searching for it like `remove`.
+    /// Returns whether an element was removed (`false` if the tree is empty).
+    ///
+    /// Like in `remove`, only one occurrence of a maximum inserted several times with
+    /// `insert_multi` is removed.
+    ///
+    /// # Complexity:
+    /// *O*(log n) - Red-Black Trees are always balanced
+    pub fn remove_max(&mut self) -> bool {
+        if self.root.is_none() {
+            return false;
+        }
+
+        if self.decrement_repeated_extreme(false) {
+            return true;
+        }
+
+        self.root = Self::remove_max_node(self.root.take(), &mut self.pool, &mut self.rotations);
+
+        // Ensure root is black
+        if let Some(root) = &mut self.root {
+            root.color = Color::Black;
+        }
+
         self.max_value = self.refind_max();
+        if self.root.is_none() {
+            self.min_value = None;
+        }
+
+        true
+    }
+
+    /// Removes one occurrence of the minimum (`leftmost`) or the maximum element if its count
+    /// is above one, leaving the node in place. Returns whether the element was repeated.
+    fn decrement_repeated_extreme(&mut self, leftmost: bool) -> bool {
+        let mut count = 0;
+        let mut cursor = self.root.as_deref();
+        while let Some(node) = cursor {
+            count = node.count;
+            cursor = if leftmost { &node.left } else { &node.right }.as_deref();
+        }
+
+        if count < 2 {
+            return false;
+        }
+
+        // The totals along the spine may only shrink once the extreme is known to be repeated.
+        let mut cursor = self.root.as_deref_mut();
+        while let Some(node) = cursor {
+            node.total -= 1;
+            let next = if leftmost {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+            if next.is_none() {
+                node.count -= 1;
+                break;
+            }
+            cursor = next.as_deref_mut();
+        }
+
+        true
+    }
+
+    /// Keeps only the values for which `f` returns `true`, visiting them in ascending order.
+    /// An element inserted several times with `insert_multi` is visited once and kept or
+    /// dropped with all its occurrences.
+    ///
+    /// The remaining values are rebuilt into a valid Red-Black Tree and the min/max caches are refreshed.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is rebuilt from its in-order sequence.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let values = Self::drain_sorted(self.root.take(), &mut self.pool)
+            .into_iter()
+            .filter(|(value, _)| f(value))
+            .collect();
+
+        self.rebuild_from_counted(values);
+    }
+
+    /// Removes every value outside of the inclusive range `[lo, hi]`, keeping only the
+    /// values `x` with `lo <= x <= hi`.
+    ///
+    /// The remaining values are rebuilt into a valid Red-Black Tree. If `lo > hi` the tree is emptied.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is rebuilt from its in-order sequence.
+    pub fn retain_range(&mut self, lo: &T, hi: &T) {
+        let values = Self::drain_sorted(self.root.take(), &mut self.pool)
+            .into_iter()
+            .skip_while(|(value, _)| value < lo)
+            .take_while(|(value, _)| value <= hi)
+            .collect();
+
+        self.rebuild_from_counted(values);
+    }
+
+    /// Moves all values greater than or equal to `key` into a new tree, leaving the values less
+    /// than `key` in `self` (like `BTreeSet::split_off`). The `key` itself, if present, ends up
+    /// in the returned tree.
+    ///
+    /// Both parts are rebuilt into balanced trees, so they keep the Red-Black Tree properties.
+    ///
+    /// # Complexity:
+    /// *O*(n) - both parts are rebuilt from the in-order sequence.
+    pub fn split_off(&mut self, key: &T) -> Self {
+        let mut values = Self::drain_sorted(self.root.take(), &mut self.pool);
+        let rest = values.split_off(values.partition_point(|(value, _)| value < key));
+
+        let mut other = Self::new();
+        other.pool = self.pool.split_off(rest.len());
+        other.rebuild_from_counted(rest);
+        self.rebuild_from_counted(values);
+
+        other
+    }
+
+    /// Removes all values of the inclusive range `[lo, hi]` from the tree and returns them as
+    /// a new tree.
+    ///
+    /// Both trees are rebuilt into balanced ones, so they keep the Red-Black Tree properties.
+    /// If `lo > hi` nothing is removed.
+    ///
+    /// # Complexity:
+    /// *O*(n) - both trees are rebuilt from the in-order sequence.
+    pub fn drain_range(&mut self, lo: &T, hi: &T) -> Self {
+        let mut drained = Self::new();
+        if lo > hi {
+            return drained;
+        }
+
+        let mut values = Self::drain_sorted(self.root.take(), &mut self.pool);
+        let start = values.partition_point(|(value, _)| value < lo);
+        let end = values.partition_point(|(value, _)| value <= hi);
+
+        drained.pool = self.pool.split_off(end - start);
+        drained.rebuild_from_counted(values.drain(start..end).collect());
+        self.rebuild_from_counted(values);
+
+        drained
+    }
+
+    /// Moves all values of `other` into `self`, leaving `other` empty (like `BTreeSet::append`).
+    ///
+    /// If a value is present in both trees, the copy from `self` is kept. The two in-order
+    /// sequences are merged and the result is rebuilt into a balanced tree.
+    ///
+    /// # Complexity:
+    /// *O*(n + m) - both trees are visited once.
+    pub fn append(&mut self, other: &mut Self) {
+        let values = Self::merge_sorted(
+            Self::drain_sorted(self.root.take(), &mut self.pool),
+            Self::drain_sorted(other.root.take(), &mut other.pool),
+        );
+        self.pool.append(&mut other.pool);
+        self.rebuild_from_counted(values);
+
+        other.min_value = None;
+        other.max_value = None;
+    }
+
+    /// Merges two strictly ascending sequences of (value, count) pairs into one, keeping the
+    /// pairs of `left` on ties.
+    fn merge_sorted(left: Vec<(T, usize)>, right: Vec<(T, usize)>) -> Vec<(T, usize)> {
+        let mut result = Vec::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+
+        while let (Some((a, _)), Some((b, _))) = (left.peek(), right.peek()) {
+            match a.partial_cmp(b) {
+                Some(Ordering::Greater) => result.extend(right.next()),
+                Some(Ordering::Equal) => {
+                    right.next();
+                    result.extend(left.next());
+                }
+                _ => result.extend(left.next()),
+            }
+        }
+
+        result.extend(left);
+        result.extend(right);
+        result
+    }
+
+    /// Builds a new balanced tree out of the `Some` results of `f` applied to the elements
+    /// in ascending order, dropping the `None`s (and the duplicates among the results).
+    ///
+    /// # Complexity:
+    /// *O*(n log n) - dominated by sorting the results (see `from_unsorted`).
+    pub fn filter_map<U, F>(&self, f: F) -> RedBlackTree<U>
+    where
+        U: PartialOrd + Clone,
+        F: Fn(&T) -> Option<U>,
+    {
+        RedBlackTree::from_unsorted(self.in_order().into_iter().filter_map(f))
     }
 
     /// Recursively removes a value and maintains Red-Black Tree properties.
-    fn remove_recursive(node: Option<Box<RBNode<T>>>, value: &T) -> Option<Box<RBNode<T>>> {
+    fn remove_recursive<Q>(
+        node: Option<Box<RBNode<T>>>,
+        value: &Q,
+        removed: &mut Option<T>,
+        pool: &mut NodePool<RBNode<T>>,
+        rotations: &mut Rotations,
+    ) -> Option<Box<RBNode<T>>>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
         let mut node = node?;
 
-        match value.partial_cmp(&node.value) {
+        match value.partial_cmp(node.value.borrow()) {
             Some(Ordering::Less) => {
                 if node.left.is_some() {
                     // Ensure we can delete from left subtree
                     if !RBNode::is_red_node(&node.left)
                         && node.left.as_ref().is_some_and(|left| !RBNode::is_red_node(&left.left)) {
-                        node = Self::move_red_left(node);
+                        node = Self::move_red_left(node, rotations);
                     }
-                    node.left = Self::remove_recursive(node.left.take(), value);
+                    node.left =
+                        Self::remove_recursive(node.left.take(), value, removed, pool, rotations);
                 }
             }
             _ => {
                 // Handle equal or greater case
                 if RBNode::is_red_node(&node.left) {
-                    node = node.rotate_right();
+                    node = node.rotate_right_counting(rotations);
                 }
 
                 // Value found at bottom
-                if value.partial_cmp(&node.value) == Some(Ordering::Equal) && node.right.is_none() {
+                if value.partial_cmp(node.value.borrow()) == Some(Ordering::Equal)
+                    && node.right.is_none()
+                {
+                    *removed = Some(pool.release(node).value);
                     return None;
                 }
 
@@ -517,80 +2062,127 @@ impl<T: PartialOrd + Clone> RedBlackTree<T> {
                     // Ensure we can delete from right subtree
                     if !RBNode::is_red_node(&node.right)
                         && node.right.as_ref().is_some_and(|right| !RBNode::is_red_node(&right.left)) {
-                        node = Self::move_red_right(node);
+                        node = Self::move_red_right(node, rotations);
                     }
 
-                    if value.partial_cmp(&node.value) == Some(Ordering::Equal) {
-                        // Replace with successor
-                        let min_value = Self::find_min(&node.right);
-                        node.value = min_value.clone();
-                        node.right = Self::remove_min(node.right.take());
+                    if value.partial_cmp(node.value.borrow()) == Some(Ordering::Equal) {
+                        // Replace with successor (together with its multiplicity)
+                        let successor = Self::find_min(&node.right);
+                        let (min_value, min_count) = (successor.value.clone(), successor.count);
+                        *removed = Some(std::mem::replace(&mut node.value, min_value));
+                        node.count = min_count;
+                        node.right = Self::remove_min_node(node.right.take(), pool, rotations);
                     } else {
-                        node.right = Self::remove_recursive(node.right.take(), value);
+                        node.right = Self::remove_recursive(
+                            node.right.take(),
+                            value,
+                            removed,
+                            pool,
+                            rotations,
+                        );
                     }
                 }
             }
         }
 
-        Some(Self::fix_up(node))
+        Some(Self::fix_up(node, rotations))
     }
 
-    /// Finds the minimum value in a subtree.
-    fn find_min(node: &Option<Box<RBNode<T>>>) -> &T {
+    /// Finds the node with the minimum value in a subtree.
+    fn find_min(node: &Option<Box<RBNode<T>>>) -> &RBNode<T> {
         let mut current = node.as_ref().unwrap();
         while let Some(left) = &current.left {
             current = left;
         }
-        &current.value
+        current
     }
 
     /// Removes the minimum node from a subtree.
-    fn remove_min(node: Option<Box<RBNode<T>>>) -> Option<Box<RBNode<T>>> {
+    fn remove_min_node(
+        node: Option<Box<RBNode<T>>>,
+        pool: &mut NodePool<RBNode<T>>,
+        rotations: &mut Rotations,
+    ) -> Option<Box<RBNode<T>>> {
         let mut node = node?;
 
-        node.left.as_ref()?;
+        if node.left.is_none() {
+            pool.release(node);
+            return None;
+        }
 
         if !RBNode::is_red_node(&node.left)
             && node.left.as_ref().is_some_and(|left| !RBNode::is_red_node(&left.left)) {
-            node = Self::move_red_left(node);
+            node = Self::move_red_left(node, rotations);
+        }
+
+        node.left = Self::remove_min_node(node.left.take(), pool, rotations);
+        Some(Self::fix_up(node, rotations))
+    }
+
+    /// Removes the maximum node from a subtree.
+    fn remove_max_node(
+        node: Option<Box<RBNode<T>>>,
+        pool: &mut NodePool<RBNode<T>>,
+        rotations: &mut Rotations,
+    ) -> Option<Box<RBNode<T>>> {
+        let mut node = node?;
+
+        if RBNode::is_red_node(&node.left) {
+            node = node.rotate_right_counting(rotations);
+        }
+
+        if node.right.is_none() {
+            let node = pool.release(node);
+            return node.left;
+        }
+
+        if !RBNode::is_red_node(&node.right)
+            && node
+                .right
+                .as_ref()
+                .is_some_and(|right| !RBNode::is_red_node(&right.left))
+        {
+            node = Self::move_red_right(node, rotations);
         }
 
-        node.left = Self::remove_min(node.left.take());
-        Some(Self::fix_up(node))
+        node.right = Self::remove_max_node(node.right.take(), pool, rotations);
+        Some(Self::fix_up(node, rotations))
     }
 
     /// Moves a red node to the left to prepare for deletion.
-    fn move_red_left(mut node: Box<RBNode<T>>) -> Box<RBNode<T>> {
+    fn move_red_left(mut node: Box<RBNode<T>>, rotations: &mut Rotations) -> Box<RBNode<T>> {
         node.flip_colors();
         if node.right.as_ref().is_some_and(|right| RBNode::is_red_node(&right.left)) {
             if let Some(right) = node.right.take() {
-                node.right = Some(right.rotate_right());
+                node.right = Some(right.rotate_right_counting(rotations));
             }
-            node = node.rotate_left();
+            node = node.rotate_left_counting(rotations);
             node.flip_colors();
         }
         node
     }
 
     /// Moves a red node to the right to prepare for deletion.
-    fn move_red_right(mut node: Box<RBNode<T>>) -> Box<RBNode<T>> {
+    fn move_red_right(mut node: Box<RBNode<T>>, rotations: &mut Rotations) -> Box<RBNode<T>> {
         node.flip_colors();
         if node.left.as_ref().is_some_and(|left| RBNode::is_red_node(&left.left)) {
-            node = node.rotate_right();
+            node = node.rotate_right_counting(rotations);
             node.flip_colors();
         }
         node
     }
 
     /// Fixes up the tree after deletion to maintain Red-Black properties.
-    fn fix_up(mut node: Box<RBNode<T>>) -> Box<RBNode<T>> {
+    fn fix_up(mut node: Box<RBNode<T>>, rotations: &mut Rotations) -> Box<RBNode<T>> {
+        node.update_size();
+
         if RBNode::is_red_node(&node.right) {
-            node = node.rotate_left();
+            node = node.rotate_left_counting(rotations);
         }
 
         if RBNode::is_red_node(&node.left)
             && node.left.as_ref().is_some_and(|left| RBNode::is_red_node(&left.left)) {
-            node = node.rotate_right();
+            node = node.rotate_right_counting(rotations);
         }
 
         if RBNode::is_red_node(&node.left) && RBNode::is_red_node(&node.right) {
@@ -606,3 +2198,219 @@ impl<T: PartialOrd + Clone> Default for RedBlackTree<T> {
         Self::new()
     }
 }
+
+/// Two trees are equal if they hold the same elements with the same counts (see
+/// `insert_multi`), regardless of their shape (and so of the order of the insertions).
+impl<T: PartialOrd + Clone> PartialEq for RedBlackTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter_counted().eq(other.iter_counted())
+    }
+}
+
+impl<T: PartialOrd + Clone + Eq> Eq for RedBlackTree<T> {}
+
+impl<T: PartialOrd + Clone> FromIterator<T> for RedBlackTree<T> {
+    /// Builds a tree by inserting the values one by one (duplicates are dropped like by `insert`).
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: PartialOrd + Clone> Extend<T> for RedBlackTree<T> {
+    /// Inserts the values one by one (duplicates are dropped like by `insert`).
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// Serializes the tree as the sequence of its elements in ascending order, repeating every
+/// element as many times as it has been inserted with `insert_multi`.
+#[cfg(feature = "serde")]
+impl<T: PartialOrd + Clone + Serialize> Serialize for RedBlackTree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.iter_counted()
+                .flat_map(|(value, count)| std::iter::repeat_n(value, count)),
+        )
+    }
+}
+
+/// Deserializes a sequence of elements, inserting them one by one with `insert_multi` (so
+/// repeated elements get counted), so the result is always a valid Red-Black Tree.
+#[cfg(feature = "serde")]
+impl<'de, T: PartialOrd + Clone + Deserialize<'de>> Deserialize<'de> for RedBlackTree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut tree = Self::new();
+        for value in Vec::<T>::deserialize(deserializer)? {
+            tree.insert_multi(value);
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: PartialOrd + Clone + Send> RedBlackTree<T> {
+    /// Builds the same tree as `from_unsorted`, sorting the values in parallel.
+    ///
+    /// # Complexity:
+    /// *O*(n log n) work, dominated by the parallel sort.
+    pub fn par_from_unsorted(iter: impl IntoParallelIterator<Item = T>) -> Self {
+        let mut values: Vec<T> = iter
+            .into_par_iter()
+            .filter(|value| value.partial_cmp(value).is_some())
+            .collect();
+        values.par_sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        values.dedup_by(|a, b| (*a).partial_cmp(b) == Some(Ordering::Equal));
+
+        let mut tree = Self::new();
+        tree.rebuild_from_sorted(values);
+        tree
+    }
+}
+
+/// Collects a parallel iterator with `par_from_unsorted`.
+#[cfg(feature = "rayon")]
+impl<T: PartialOrd + Clone + Send> FromParallelIterator<T> for RedBlackTree<T> {
+    fn from_par_iter<I: IntoParallelIterator<Item = T>>(par_iter: I) -> Self {
+        Self::par_from_unsorted(par_iter)
+    }
+}
+
+/// Builds the tree out of `values` inserted in order, failing on the first value that is
+/// already present instead of silently skipping it.
+impl<T: PartialOrd + Clone> TryFrom<Vec<T>> for RedBlackTree<T> {
+    type Error = DuplicateError<T>;
+
+    fn try_from(values: Vec<T>) -> Result<Self, Self::Error> {
+        let mut tree = Self::new();
+
+        for value in values {
+            if tree.contains(&value) {
+                return Err(DuplicateError { value });
+            }
+            tree.insert(value);
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<T: PartialOrd + Clone + LeBytes> RedBlackTree<T> {
+    /// Serializes the tree as the number of its elements (a little-endian `u64`) followed by
+    /// the little-endian bytes of the elements in ascending order. An element inserted several
+    /// times with `insert_multi` is repeated `count` times.
+    ///
+    /// # Complexity:
+    /// *O*(n) - visits all nodes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let values: Vec<&T> = self
+            .iter_counted()
+            .flat_map(|(value, count)| std::iter::repeat_n(value, count))
+            .collect();
+        bytes::encode(values.into_iter())
+    }
+
+    /// Deserializes a tree serialized with `to_bytes`, rebuilding it balanced. Runs of equal
+    /// elements are stored once with their length as the count.
+    ///
+    /// Fails if the length prefix is missing or does not match the data, or if the elements
+    /// are not ascending.
+    ///
+    /// # Complexity:
+    /// *O*(n) - the tree is built bottom-up from the sorted sequence.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DecodeError> {
+        let values = bytes::decode_counted(data, &Order::Natural)?;
+
+        let mut tree = Self::new();
+        tree.rebuild_from_counted(values);
+        Ok(tree)
+    }
+}
+
+/// Builds a balanced tree directly from the already sorted and deduplicated elements of the
+/// set, in *O*(n) and without rotations.
+impl<T: Ord + Clone> From<BTreeSet<T>> for RedBlackTree<T> {
+    fn from(set: BTreeSet<T>) -> Self {
+        let mut tree = Self::new();
+        tree.rebuild_from_sorted(set.into_iter().collect());
+        tree
+    }
+}
+
+/// Collects clones of the elements of the tree into a set (the counts of `insert_multi` are
+/// not kept).
+impl<T: Ord + Clone> From<&RedBlackTree<T>> for BTreeSet<T> {
+    fn from(tree: &RedBlackTree<T>) -> Self {
+        tree.in_order().into_iter().cloned().collect()
+    }
+}
+
+/// Formats the tree as a set of its elements in ascending order, e.g. `{1, 2, 3}`.
+///
+/// The alternate form (`{:#}`) draws the tree sideways instead, for debugging: one node per
+/// line with the root at the left margin, every level indented by four more spaces and the
+/// right subtree above its parent, or `<empty>` for an empty tree. Every node is annotated with its
+/// color, `(R)` or `(B)`.
+///
+/// # Example:
+///
+/// A tree of `2, 1, 3` is formatted by `{}` and `{:#}` like this:
+/// ```text
+/// {1, 2, 3}
+///
+///     3 (B)
+/// 2 (B)
+///     1 (B)
+/// ```
+impl<T: PartialOrd + Clone + fmt::Display> fmt::Display for RedBlackTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_sideways(f);
+        }
+
+        write!(f, "{{")?;
+        for (i, value) in self.in_order().into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value}")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<T: PartialOrd + Clone + fmt::Display> RedBlackTree<T> {
+    /// Draws the tree sideways (the alternate form of `Display`).
+    fn fmt_sideways(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.root.is_none() {
+            return write!(f, "<empty>");
+        }
+
+        // A reverse in-order traversal puts the right subtree above its parent.
+        let mut stack = Vec::new();
+        let mut current = (&self.root, 0);
+        let mut separator = "";
+
+        loop {
+            while let (Some(node), depth) = current {
+                stack.push((node, depth));
+                current = (&node.right, depth + 1);
+            }
+
+            let Some((node, depth)) = stack.pop() else {
+                break;
+            };
+            let color = if node.is_red() { "R" } else { "B" };
+            let indent = 4 * depth;
+            write!(f, "{separator}{:indent$}{} ({color})", "", node.value)?;
+            separator = "\n";
+            current = (&node.left, depth + 1);
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,28 @@
+use super::node::Color;
 use std::fs::File;
 use std::io;
 use std::io::Write;
 
+/// Writes pairs of connections between `RBNode`s in `RedBlackTree` as a graphviz description
+/// into any writer (a file, a buffer or stdout).
+///
+/// This is a simple version that shows connections without colors.
+pub fn write_graphviz<T: std::fmt::Display, W: Write>(
+    connections: &[(T, T)],
+    out: &mut W,
+) -> io::Result<()> {
+    writeln!(out, "digraph RBT {{")?;
+    writeln!(out, "    node [shape=circle];")?;
+
+    for (parent, child) in connections {
+        writeln!(out, "    {parent} -> {child};")?;
+    }
+
+    writeln!(out, "}}")?;
+
+    Ok(())
+}
+
 /// Converts pairs of connections between `RBNode`s in `RedBlackTree` to graphviz description.
 ///
 /// This is a simple version that shows connections without colors.
@@ -10,19 +31,56 @@ pub fn convert_to_graphviz<T: std::fmt::Display>(
     filename: &str,
 ) -> io::Result<()> {
     let mut file = File::create(filename)?;
+    write_graphviz(connections, &mut file)
+}
 
-    writeln!(&mut file, "digraph RBT {{")?;
-    writeln!(&mut file, "    node [shape=circle];")?;
+/// Writes the `RBNode`s of a `RedBlackTree` with their colors (as yielded by `iter_colored`)
+/// and the connections between them (as returned by `find_connections`) as a graphviz
+/// description into any writer.
+///
+/// Every node is declared on its own, filled with its color: red nodes are drawn red, black
+/// nodes black with white text. So a tree of a single node, which has no connections, is
+/// still drawn.
+pub fn write_graphviz_colored<T: std::fmt::Display, W: Write>(
+    nodes: &[(T, Color)],
+    connections: &[(T, T)],
+    out: &mut W,
+) -> io::Result<()> {
+    writeln!(out, "digraph RBT {{")?;
+    writeln!(out, "    node [shape=circle, style=filled];")?;
+
+    for (node, color) in nodes {
+        writeln!(out, "    {node} {};", node_attributes(*color))?;
+    }
 
     for (parent, child) in connections {
-        writeln!(&mut file, "    {parent} -> {child};")?;
+        writeln!(out, "    {parent} -> {child};")?;
     }
 
-    writeln!(&mut file, "}}")?;
+    writeln!(out, "}}")?;
 
     Ok(())
 }
 
+/// Converts the `RBNode`s of a `RedBlackTree` with their colors and the connections between
+/// them to a colored graphviz description (see `write_graphviz_colored`).
+pub fn convert_to_graphviz_colored<T: std::fmt::Display>(
+    nodes: &[(T, Color)],
+    connections: &[(T, T)],
+    filename: &str,
+) -> io::Result<()> {
+    let mut file = File::create(filename)?;
+    write_graphviz_colored(nodes, connections, &mut file)
+}
+
+/// Returns the graphviz attributes filling a node with its `color`.
+fn node_attributes(color: Color) -> &'static str {
+    match color {
+        Color::Red => r#"[fillcolor="red", fontcolor="black"]"#,
+        Color::Black => r#"[fillcolor="black", fontcolor="white"]"#,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +162,65 @@ mod tests {
         convert_to_graphviz(&connections, "dots/RBT/rbt_after_deletions.dot").unwrap();
         assert!(Path::new("dots/RBT/rbt_after_deletions.dot").exists());
     }
+
+    #[test]
+    fn write_graphviz_into_buffer() {
+        let mut rbt = RedBlackTree::new();
+        for value in [2, 1, 3] {
+            rbt.insert(value);
+        }
+
+        let mut buf = Vec::new();
+        write_graphviz(&rbt.find_connections(), &mut buf).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+
+        assert!(dot.starts_with("digraph RBT {\n"));
+        assert!(dot.contains("    2 -> 1;\n"));
+        assert!(dot.contains("    2 -> 3;\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn colored_graphviz_into_buffer() {
+        setup();
+
+        let mut rbt = RedBlackTree::new();
+        for value in [7, 3, 18, 10, 22, 8, 11, 26] {
+            rbt.insert(value);
+        }
+
+        let nodes: Vec<_> = rbt.iter_colored().collect();
+        let connections = rbt.find_connections();
+
+        let mut buf = Vec::new();
+        write_graphviz_colored(&nodes, &connections, &mut buf).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+
+        let root = rbt.root_value().unwrap();
+        assert!(dot.contains(&format!(
+            r#"    {root} [fillcolor="black", fontcolor="white"];"#
+        )));
+        assert!(dot.contains(r#"fillcolor="red""#));
+        assert_eq!(dot.matches("fillcolor").count(), nodes.len());
+        for (parent, child) in &connections {
+            assert!(dot.contains(&format!("    {parent} -> {child};")));
+        }
+
+        convert_to_graphviz_colored(&nodes, &connections, "dots/RBT/rbt_colored.dot").unwrap();
+        assert!(Path::new("dots/RBT/rbt_colored.dot").exists());
+    }
+
+    #[test]
+    fn colored_graphviz_of_single_node() {
+        let mut rbt = RedBlackTree::new();
+        rbt.insert(42);
+
+        let nodes: Vec<_> = rbt.iter_colored().collect();
+        let mut buf = Vec::new();
+        write_graphviz_colored(&nodes, &rbt.find_connections(), &mut buf).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+
+        assert!(dot.contains(r#"    42 [fillcolor="black", fontcolor="white"];"#));
+        assert!(!dot.contains("->"));
+    }
 }
@@ -0,0 +1,31 @@
+/// The order in which a tree traversal visits the nodes (see `walk`).
+///
+/// # Example:
+///
+/// If such a tree is given
+/// ```text
+///      4
+///     / \
+///    2   5
+///   / \   \
+///  1   3   6
+/// ```
+/// Then the values are visited like this:
+/// - `PreOrder`: 4, 2, 1, 3, 5, 6
+/// - `InOrder`: 1, 2, 3, 4, 5, 6
+/// - `PostOrder`: 1, 3, 2, 6, 5, 4
+/// - `LevelOrder`: 4, 2, 5, 1, 3, 6
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalOrder {
+    /// Node, then its left subtree, then its right subtree.
+    PreOrder,
+
+    /// Left subtree, then the node, then its right subtree (ascending order).
+    InOrder,
+
+    /// Left subtree, then right subtree, then the node.
+    PostOrder,
+
+    /// Level by level from the root, left to right within a level.
+    LevelOrder,
+}
@@ -272,3 +272,157 @@ proptest! {
         }
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_from_unsorted(values in prop::collection::vec(any::<i32>(), 0..111)) {
+        let bulk = AVLTree::from_unsorted(values.clone());
+        let mut avl = AVLTree::new();
+
+        for &v in &values {
+            avl.insert(v);
+        }
+
+        assert!(bulk.is_balanced());
+        assert!(bulk.is_valid_bst());
+        assert_eq!(bulk.in_order(), avl.in_order());
+        assert_eq!(bulk.min(), avl.min());
+        assert_eq!(bulk.max(), avl.max());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_find(values in prop::collection::vec(-1000..1000i32, 1..111), removed in prop::collection::vec(-1000..1000i32, 0..50)) {
+        let mut avl = AVLTree::new();
+        for &v in &values {
+            avl.insert(v);
+        }
+        for v in &removed {
+            avl.remove(v);
+        }
+
+        let mut sorted: Vec<i32> = values.into_iter().filter(|v| !removed.contains(v)).collect();
+        sorted.sort();
+        sorted.dedup();
+
+        for query in -1001..1001 {
+            assert_eq!(avl.find(&query), sorted.binary_search(&query).ok());
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_index_range(values in prop::collection::vec(-1000..1000i32, 0..111), lo in -1100..1100i32, hi in -1100..1100i32) {
+        let mut avl = AVLTree::new();
+        for &v in &values {
+            avl.insert(v);
+        }
+
+        let range = avl.index_range(&lo, &hi);
+        let count = avl.in_order().into_iter().filter(|&&v| lo <= v && v <= hi).count();
+        assert_eq!(range.end - range.start, count);
+
+        let in_range: Vec<&i32> = avl.in_order()[range].to_vec();
+        assert!(in_range.iter().all(|&&v| lo <= v && v <= hi));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_kth_smallest_and_largest(values in prop::collection::vec(-1000..1000i32, 0..111), removed in prop::collection::vec(-1000..1000i32, 0..50)) {
+        let mut avl = AVLTree::new();
+        for &v in &values {
+            avl.insert(v);
+        }
+        for v in &removed {
+            avl.remove(v);
+        }
+
+        let sorted: Vec<i32> = avl.in_order_cloned();
+        assert_eq!(avl.kth_smallest(0), None);
+        assert_eq!(avl.kth_largest(0), None);
+        for k in 1..=sorted.len() {
+            assert_eq!(avl.kth_smallest(k), Some(&sorted[k - 1]));
+            assert_eq!(avl.kth_largest(k), Some(&sorted[sorted.len() - k]));
+        }
+        assert_eq!(avl.kth_smallest(sorted.len() + 1), None);
+        assert_eq!(avl.kth_largest(sorted.len() + 1), None);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_pop_min_sorted(values in prop::collection::vec(any::<i32>(), 0..111)) {
+        let mut avl = AVLTree::new();
+        for &v in &values {
+            avl.insert(v);
+        }
+        let expected = avl.in_order_cloned();
+
+        let mut popped = Vec::new();
+        while let Some(value) = avl.pop_min() {
+            popped.push(value);
+            assert!(avl.is_balanced());
+            assert_eq!(avl.min(), avl.in_order().first().copied());
+        }
+
+        assert_eq!(popped, expected);
+        assert!(avl.is_empty());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_append_union(
+        left in prop::collection::vec(-50i32..50, 0..111),
+        right in prop::collection::vec(-50i32..50, 0..111)
+    ) {
+        let mut avl = AVLTree::new();
+        for &v in &left {
+            avl.insert(v);
+        }
+        let mut other = AVLTree::new();
+        for &v in &right {
+            other.insert(v);
+        }
+
+        avl.append(&mut other);
+
+        let mut expected: Vec<i32> = left.iter().chain(&right).copied().collect();
+        expected.sort();
+        expected.dedup();
+        assert_eq!(avl.in_order_cloned(), expected);
+        assert_eq!(avl.min(), expected.first());
+        assert_eq!(avl.max(), expected.last());
+        assert!(avl.is_balanced());
+        assert!(other.is_empty());
+        assert_eq!(other.min(), None);
+        assert_eq!(other.max(), None);
+    }
+}
@@ -306,3 +306,127 @@ proptest! {
         }
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_find(values in prop::collection::vec(-1000..1000i32, 1..111), removed in prop::collection::vec(-1000..1000i32, 0..50)) {
+        let mut bst = BinarySearchTree::new();
+        for &v in &values {
+            bst.insert(v);
+        }
+        for v in &removed {
+            bst.remove(v);
+        }
+
+        let mut sorted: Vec<i32> = values.into_iter().filter(|v| !removed.contains(v)).collect();
+        sorted.sort();
+        sorted.dedup();
+
+        for query in -1001..1001 {
+            assert_eq!(bst.find(&query), sorted.binary_search(&query).ok());
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_index_range(values in prop::collection::vec(-1000..1000i32, 0..111), lo in -1100..1100i32, hi in -1100..1100i32) {
+        let mut bst = BinarySearchTree::new();
+        for &v in &values {
+            bst.insert(v);
+        }
+
+        let range = bst.index_range(&lo, &hi);
+        let count = bst.in_order().into_iter().filter(|&&v| lo <= v && v <= hi).count();
+        assert_eq!(range.end - range.start, count);
+
+        let in_range: Vec<&i32> = bst.in_order()[range].to_vec();
+        assert!(in_range.iter().all(|&&v| lo <= v && v <= hi));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_iter(values in prop::collection::vec(-1000..1000i32, 0..111)) {
+        let mut bst = BinarySearchTree::new();
+        for &v in &values {
+            bst.insert(v);
+        }
+
+        let in_order = bst.in_order();
+        assert_eq!(bst.iter().collect::<Vec<_>>(), in_order);
+        assert_eq!(bst.iter().rev().collect::<Vec<_>>(), in_order.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(bst.iter().len(), in_order.len());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_range(values in prop::collection::vec(-1000..1000i32, 0..111), lo in -1100..1100i32, hi in -1100..1100i32) {
+        let mut bst = BinarySearchTree::new();
+        for &v in &values {
+            bst.insert(v);
+        }
+
+        let expected: Vec<&i32> = bst.in_order().into_iter().filter(|&&v| lo <= v && v <= hi).collect();
+        assert_eq!(bst.range(&lo, &hi).collect::<Vec<_>>(), expected);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_set_relationships(a in prop::collection::vec(0..40i32, 0..30), b in prop::collection::vec(0..40i32, 0..30)) {
+        let mut bst_a = BinarySearchTree::new();
+        let mut bst_b = BinarySearchTree::new();
+        for &v in &a {
+            bst_a.insert(v);
+        }
+        for &v in &b {
+            bst_b.insert(v);
+        }
+
+        let set_a: HashSet<i32> = a.into_iter().collect();
+        let set_b: HashSet<i32> = b.into_iter().collect();
+        assert_eq!(bst_a.is_subset(&bst_b), set_a.is_subset(&set_b));
+        assert_eq!(bst_a.is_superset(&bst_b), set_a.is_superset(&set_b));
+        assert_eq!(bst_a.is_disjoint(&bst_b), set_a.is_disjoint(&set_b));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_in_order_morris(values in prop::collection::vec(-1000..1000i32, 0..111)) {
+        let mut bst = BinarySearchTree::new();
+        for &v in &values {
+            bst.insert(v);
+        }
+
+        let mut walked = Vec::new();
+        bst.in_order_morris(|&value| walked.push(value));
+        assert_eq!(walked, bst.in_order_cloned());
+    }
+}
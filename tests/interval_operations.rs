@@ -0,0 +1,67 @@
+use data_forest::interval_tree::IntervalTree;
+
+use proptest::prelude::*;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+fn interval() -> impl Strategy<Value = (i32, i32)> {
+    (-1000..1000, 0..100).prop_map(|(start, len)| (start, start + len))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_overlapping(intervals in prop::collection::vec(interval(), 0..111), query in interval()) {
+        let mut tree = IntervalTree::new();
+
+        for &interval in &intervals {
+            tree.insert(interval);
+        }
+
+        let mut expected: Vec<&(i32, i32)> = intervals
+            .iter()
+            .filter(|&&(start, end)| start <= query.1 && end >= query.0)
+            .collect();
+        expected.sort();
+        expected.dedup();
+
+        assert!(tree.is_valid());
+        assert_eq!(tree.overlapping(query), expected);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_remove_maintains_invariants(intervals in prop::collection::vec(interval(), 1..111)) {
+        let mut tree = IntervalTree::new();
+
+        for &interval in &intervals {
+            tree.insert(interval);
+        }
+
+        let mut intervals = intervals;
+        intervals.shuffle(&mut StdRng::seed_from_u64(111));
+
+        for (i, interval) in intervals.iter().enumerate() {
+            tree.remove(interval);
+
+            assert!(
+                tree.is_valid(),
+                "Tree invalid after removing {:?} (operation #{})",
+                interval, i
+            );
+            assert!(!tree.contains(interval));
+        }
+
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "pool")]
+
+use data_forest::avl_tree::AVLTree;
+use data_forest::binary_search_tree::BinarySearchTree;
+use data_forest::red_black_tree::RedBlackTree;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn refill_after_clear_reuses_nodes() {
+    let values: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+
+    let mut bst = BinarySearchTree::new();
+    let first = allocations_during(|| values.iter().for_each(|&v| bst.insert(v)));
+    bst.clear();
+    assert_eq!(bst.pooled_nodes(), values.len());
+    let refill = allocations_during(|| values.iter().for_each(|&v| bst.insert(v)));
+    assert!(refill < first, "{refill} >= {first}");
+    assert_eq!(refill, 0);
+    assert_eq!(bst.number_of_elements(), values.len());
+    bst.clear();
+    let entries = allocations_during(|| {
+        values.iter().for_each(|&v| {
+            bst.entry(v).or_insert();
+        })
+    });
+    assert_eq!(entries, 0);
+    assert_eq!(bst.number_of_elements(), values.len());
+
+    let mut avl = AVLTree::new();
+    let first = allocations_during(|| values.iter().for_each(|&v| avl.insert(v)));
+    avl.clear();
+    assert_eq!(avl.pooled_nodes(), values.len());
+    let refill = allocations_during(|| values.iter().for_each(|&v| avl.insert(v)));
+    assert!(refill < first, "{refill} >= {first}");
+    assert_eq!(refill, 0);
+    assert!(avl.is_balanced());
+
+    let mut rbt = RedBlackTree::new();
+    let first = allocations_during(|| values.iter().for_each(|&v| rbt.insert(v)));
+    rbt.clear();
+    assert_eq!(rbt.pooled_nodes(), values.len());
+    let refill = allocations_during(|| values.iter().for_each(|&v| rbt.insert(v)));
+    assert!(refill < first, "{refill} >= {first}");
+    assert_eq!(refill, 0);
+    assert!(rbt.is_valid_red_black_tree());
+
+    for &v in &values[..500] {
+        rbt.remove(&v);
+    }
+    assert_eq!(rbt.pooled_nodes(), 500);
+    assert_eq!(
+        allocations_during(|| values[..500].iter().for_each(|&v| rbt.insert(v))),
+        0
+    );
+    assert_eq!(rbt.pooled_nodes(), 0);
+}
+
+#[test]
+fn with_capacity_preallocates_nodes() {
+    let mut bst = BinarySearchTree::with_capacity(1000);
+    assert_eq!(bst.pooled_nodes(), 1000);
+    assert_eq!(
+        allocations_during(|| (0..1000).for_each(|v| bst.insert(v))),
+        0
+    );
+    assert_eq!(bst.pooled_nodes(), 0);
+
+    let mut avl = AVLTree::with_capacity(1000);
+    assert_eq!(
+        allocations_during(|| (0..1000).for_each(|v| avl.insert(v))),
+        0
+    );
+    assert!(avl.is_balanced());
+
+    let mut rbt = RedBlackTree::with_capacity(1000);
+    assert_eq!(
+        allocations_during(|| (0..1000).for_each(|v| rbt.insert(v))),
+        0
+    );
+    assert!(rbt.is_valid_red_black_tree());
+}
+
+#[test]
+fn clone_starts_with_empty_pool() {
+    let mut bst = BinarySearchTree::new();
+    (0..100).for_each(|v| bst.insert(v));
+    (0..50).for_each(|v| bst.remove(&v));
+    assert_eq!(bst.pooled_nodes(), 50);
+    assert_eq!(bst.clone().pooled_nodes(), 0);
+
+    let mut avl = AVLTree::new();
+    (0..100).for_each(|v| avl.insert(v));
+    avl.clear();
+    assert_eq!(avl.clone().pooled_nodes(), 0);
+
+    let mut rbt = RedBlackTree::new();
+    (0..100).for_each(|v| rbt.insert(v));
+    rbt.clear();
+    assert_eq!(rbt.clone().pooled_nodes(), 0);
+}
@@ -374,3 +374,141 @@ proptest! {
         assert!(rbt.height() as f64 <= max_height);
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_from_unsorted(values in prop::collection::vec(any::<i32>(), 0..111)) {
+        let mut bulk = RedBlackTree::from_unsorted(values.clone());
+        let mut rbt = RedBlackTree::new();
+
+        for &v in &values {
+            rbt.insert(v);
+        }
+
+        assert!(bulk.is_valid_red_black_tree());
+        assert!(bulk.is_valid_bst());
+        assert_eq!(bulk.in_order(), rbt.in_order());
+        assert_eq!(bulk.min(), rbt.min());
+        assert_eq!(bulk.max(), rbt.max());
+
+        for &v in &values {
+            bulk.remove(&v);
+            assert!(bulk.is_valid_red_black_tree());
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_find(values in prop::collection::vec(-1000..1000i32, 1..111), removed in prop::collection::vec(-1000..1000i32, 0..50)) {
+        let mut rbt = RedBlackTree::new();
+        for &v in &values {
+            rbt.insert(v);
+        }
+        for v in &removed {
+            rbt.remove(v);
+        }
+
+        let mut sorted: Vec<i32> = values.into_iter().filter(|v| !removed.contains(v)).collect();
+        sorted.sort();
+        sorted.dedup();
+
+        for query in -1001..1001 {
+            assert_eq!(rbt.find(&query), sorted.binary_search(&query).ok());
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_index_range(values in prop::collection::vec(-1000..1000i32, 0..111), lo in -1100..1100i32, hi in -1100..1100i32) {
+        let mut rbt = RedBlackTree::new();
+        for &v in &values {
+            rbt.insert(v);
+        }
+
+        let range = rbt.index_range(&lo, &hi);
+        let count = rbt.in_order().into_iter().filter(|&&v| lo <= v && v <= hi).count();
+        assert_eq!(range.end - range.start, count);
+
+        let in_range: Vec<&i32> = rbt.in_order()[range].to_vec();
+        assert!(in_range.iter().all(|&&v| lo <= v && v <= hi));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_insert_multi_counts(values in prop::collection::vec(-20..20i32, 0..111), removed in prop::collection::vec(-25..25i32, 0..111)) {
+        let mut rbt = RedBlackTree::new();
+        let mut counts = std::collections::BTreeMap::new();
+
+        for &v in &values {
+            rbt.insert_multi(v);
+            *counts.entry(v).or_insert(0) += 1;
+        }
+        for v in &removed {
+            rbt.remove(v);
+            if let Some(count) = counts.get_mut(v) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(v);
+                }
+            }
+            assert!(rbt.is_valid_red_black_tree());
+        }
+
+        for v in -25..25 {
+            assert_eq!(rbt.count(&v), counts.get(&v).copied().unwrap_or(0));
+        }
+        assert_eq!(rbt.len(), counts.values().sum::<usize>());
+        assert_eq!(rbt.number_of_distinct(), counts.len());
+        assert_eq!(rbt.in_order_cloned(), counts.keys().copied().collect::<Vec<_>>());
+        assert_eq!(rbt.min(), counts.keys().next());
+        assert_eq!(rbt.max(), counts.keys().next_back());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 111,
+        ..ProptestConfig::default()
+    })]
+    #[test]
+    fn prop_black_height(values in prop::collection::vec(-1000..1000i32, 1..111), removed in prop::collection::vec(-1000..1000i32, 0..55)) {
+        let mut rbt = RedBlackTree::new();
+        for &v in &values {
+            rbt.insert(v);
+        }
+        for v in &removed {
+            rbt.remove(v);
+        }
+        assert!(rbt.is_valid_red_black_tree());
+
+        if rbt.is_empty() {
+            assert_eq!(rbt.black_height(), 0);
+        } else {
+            // Black nodes on every path, without the NIL leaf.
+            let black = rbt.black_height() - 1;
+            assert!(black >= 1);
+            assert!((1 << black) - 1 <= rbt.len());
+            let levels = rbt.height() + 1;
+            assert!(black <= levels && levels <= 2 * black);
+        }
+    }
+}